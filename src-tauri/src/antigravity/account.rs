@@ -4,12 +4,18 @@ use serde_json::Value;
 
 /// 将 jetskiStateSync.agentManagerInitState 作为 SessionResponse proto 解码
 pub fn decode_jetski_state_proto(b64: &str) -> Result<Value, String> {
+    let msg = decode_session_response(b64)?;
+    Ok(session_response_to_json(&msg))
+}
+
+/// 将 jetskiStateSync.agentManagerInitState 解码为原始 SessionResponse 消息
+pub fn decode_session_response(b64: &str) -> Result<crate::proto::SessionResponse, String> {
     if b64.trim().is_empty() {
         return Err("jetskiStateSync.agentManagerInitState 为空".to_string());
     }
 
     let bytes = base64::engine::general_purpose::STANDARD
-        .decode(b64)
+        .decode(b64.trim())
         .map_err(|e| {
             format!(
                 "jetskiStateSync.agentManagerInitState Base64 解码失败(len={}): {}",
@@ -18,15 +24,13 @@ pub fn decode_jetski_state_proto(b64: &str) -> Result<Value, String> {
             )
         })?;
 
-    let msg = crate::proto::SessionResponse::decode(bytes.as_slice()).map_err(|e| {
+    crate::proto::SessionResponse::decode(bytes.as_slice()).map_err(|e| {
         format!(
             "jetskiStateSync.agentManagerInitState Protobuf 解码失败(len={}): {}",
             bytes.len(),
             e
         )
-    })?;
-
-    Ok(session_response_to_json(&msg))
+    })
 }
 
 fn session_response_to_json(msg: &crate::proto::SessionResponse) -> Value {
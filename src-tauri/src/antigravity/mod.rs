@@ -2,4 +2,6 @@ pub mod account;
 pub mod cleanup;
 pub mod path_config;
 pub mod restore;
+pub mod snapshot;
 pub mod starter;
+pub mod state_db;
@@ -1,14 +1,13 @@
 // Antigravity 用户数据恢复模块
 // 负责将备份数据恢复到 Antigravity 应用数据库
 
-use rusqlite::{params, Connection};
 use serde_json::Value;
 use std::fs;
 use std::path::PathBuf;
 
 // 导入相关模块
+use super::state_db::{self, StateDb, WriteOp};
 use crate::constants::database;
-use crate::platform;
 
 /// 恢复 Antigravity 状态（精简版）
 ///
@@ -34,78 +33,46 @@ pub async fn save_antigravity_account_to_file(
 
     println!("✅ 账户文件读取成功");
 
-    let app_data = match platform::get_antigravity_db_path() {
-        Some(p) => p,
-        None => {
-            let possible_paths = platform::get_all_antigravity_db_paths();
-            if possible_paths.is_empty() {
-                return Err("未找到 Antigravity 安装位置".to_string());
-            }
-            possible_paths[0].clone()
-        }
-    };
-
-    // 确保数据库目录存在
-    if let Some(parent) = app_data.parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("创建数据库目录失败: {}", e))?;
-    }
+    let app_data = state_db::resolve_default_path()?;
 
     let mut msg = String::new();
 
     // 内联恢复逻辑：仅写回 AGENT_STATE 并删除 AUTH_STATUS
     let restore_db = |db_path: &PathBuf, db_name: &str| -> Result<usize, String> {
         tracing::info!(target: "restore::database", db_name = %db_name, "开始恢复数据库（仅 jetskiStateSync.agentManagerInitState，移除 antigravityAuthStatus）");
-        let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
-
-        let mut restored_count = 0;
-
-        if let Some(val) = account_data.get(database::AGENT_STATE) {
-            if let Some(val_str) = val.as_str() {
-                match conn.execute(
-                    "INSERT OR REPLACE INTO ItemTable (key, value) VALUES (?, ?)",
-                    params![database::AGENT_STATE, val_str],
-                ) {
-                    Ok(_) => {
-                        tracing::debug!(target: "restore::database", key = %database::AGENT_STATE, "注入数据成功");
-                        restored_count += 1;
-                    }
-                    Err(e) => {
-                        tracing::error!(target: "restore::database", key = %database::AGENT_STATE, error = %e, "写入数据失败");
-                    }
-                }
-            } else {
+        let mut db = StateDb::open(db_path)?;
+
+        let mut ops = Vec::new();
+
+        match account_data.get(database::AGENT_STATE) {
+            Some(Value::String(val_str)) => ops.push(WriteOp::Put(database::AGENT_STATE, val_str)),
+            Some(_) => {
                 tracing::warn!(target: "restore::database", key = %database::AGENT_STATE, "字段不是字符串类型，跳过");
             }
-        } else {
-            tracing::debug!(target: "restore::database", key = %database::AGENT_STATE, "备份中未找到字段，跳过");
+            None => {
+                tracing::debug!(target: "restore::database", key = %database::AGENT_STATE, "备份中未找到字段，跳过");
+            }
         }
 
-        if let Some(val) = account_data.get(database::AUTH_STATUS) {
-            if let Some(val_str) = val.as_str() {
-                match conn.execute(
-                    "INSERT OR REPLACE INTO ItemTable (key, value) VALUES (?, ?)",
-                    params![database::AUTH_STATUS, val_str],
-                ) {
-                    Ok(_) => {
-                        tracing::debug!(target: "restore::database", key = %database::AUTH_STATUS, "注入认证状态成功");
-                        restored_count += 1;
-                    }
-                    Err(e) => {
-                        tracing::error!(target: "restore::database", key = %database::AUTH_STATUS, error = %e, "写入认证状态失败");
-                    }
-                }
+        match account_data.get(database::AUTH_STATUS) {
+            Some(Value::String(val_str)) => ops.push(WriteOp::Put(database::AUTH_STATUS, val_str)),
+            Some(_) => {
+                tracing::warn!(target: "restore::database", key = %database::AUTH_STATUS, "字段不是字符串类型，跳过");
             }
-        } else {
-             if let Err(e) = conn.execute(
-                "DELETE FROM ItemTable WHERE key = ?",
-                [database::AUTH_STATUS],
-            ) {
-                tracing::warn!(target: "restore::database", error = %e, "删除 antigravityAuthStatus 失败（忽略）");
-            } else {
-                tracing::debug!(target: "restore::database", "旧备份无认证状态，已清理旧数据");
+            None => {
+                // 旧备份无认证状态，清理旧数据
+                ops.push(WriteOp::Delete(database::AUTH_STATUS));
             }
         }
 
+        db.write(&ops)?;
+
+        let restored_count = ops
+            .iter()
+            .filter(|op| matches!(op, WriteOp::Put(..)))
+            .count();
+        tracing::debug!(target: "restore::database", restored_count = restored_count, "注入数据成功");
+
         Ok(restored_count)
     };
 
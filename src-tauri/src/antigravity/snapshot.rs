@@ -0,0 +1,293 @@
+//! state.vscdb 快照模块
+//!
+//! 在 `clear_all_data` / `restore` / `switch` 等破坏性操作之前，通过 SQLite 备份 API
+//! 保存一份完整的 state.vscdb（以及 state.vscdb.backup）副本，只保留最近
+//! [`MAX_SNAPSHOTS`] 份。切换出错时可通过 [`restore_latest`] 撤销上一次操作。
+//!
+//! 目录结构：
+//! ```text
+//! ~/.antigravity-agent/state-snapshots/
+//!   └── 20250101-120000-123_switch/
+//!       ├── snapshot.json
+//!       ├── state.vscdb
+//!       └── state.vscdb.backup   (可选)
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::state_db::{self, StateDb};
+use crate::directories;
+
+/// 最多保留的快照数量
+pub const MAX_SNAPSHOTS: usize = 10;
+
+const META_FILE: &str = "snapshot.json";
+const DB_FILE: &str = "state.vscdb";
+const BACKUP_DB_FILE: &str = "state.vscdb.backup";
+
+/// 快照元数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    /// 快照 ID（即目录名，按时间排序）
+    pub id: String,
+    /// 触发快照的操作
+    pub operation: String,
+    /// 创建时间（RFC 3339）
+    pub created_at: String,
+    /// 被快照的数据库路径
+    pub source_path: String,
+    /// 是否同时保存了 state.vscdb.backup
+    pub has_backup_db: bool,
+}
+
+/// 在破坏性操作前为当前 state.vscdb 创建快照
+pub fn take(operation: &str) -> Result<SnapshotInfo, String> {
+    let live_db = state_db::resolve_default_path()?;
+    take_from(&directories::get_snapshots_directory(), &live_db, operation)
+}
+
+/// 列出所有快照（最新的在前）
+pub fn list() -> Result<Vec<SnapshotInfo>, String> {
+    list_in(&directories::get_snapshots_directory())
+}
+
+/// 用最近一次快照覆盖当前 state.vscdb，成功后删除该快照
+///
+/// 重复调用会依次回退到更早的快照。
+pub fn restore_latest() -> Result<SnapshotInfo, String> {
+    let live_db = state_db::resolve_default_path()?;
+    restore_latest_in(&directories::get_snapshots_directory(), &live_db)
+}
+
+/// 删除早于 `max_age` 的快照，返回删除的数量
+pub fn prune_older_than(max_age: chrono::Duration) -> Result<usize, String> {
+    prune_older_than_in(
+        &directories::get_snapshots_directory(),
+        chrono::Local::now() - max_age,
+    )
+}
+
+fn take_from(
+    snapshots_dir: &Path,
+    live_db: &Path,
+    operation: &str,
+) -> Result<SnapshotInfo, String> {
+    let now = chrono::Local::now();
+    let id = format!("{}_{}", now.format("%Y%m%d-%H%M%S-%3f"), operation);
+    let snapshot_dir = snapshots_dir.join(&id);
+
+    fs::create_dir_all(&snapshot_dir).map_err(|e| format!("创建快照目录失败: {}", e))?;
+
+    let result = (|| {
+        StateDb::open_readonly(live_db)?.backup_to(&snapshot_dir.join(DB_FILE))?;
+
+        let live_backup_db = live_db.with_extension("vscdb.backup");
+        let has_backup_db = match StateDb::open_readonly(&live_backup_db) {
+            Ok(db) => {
+                db.backup_to(&snapshot_dir.join(BACKUP_DB_FILE))?;
+                true
+            }
+            Err(e) => {
+                tracing::debug!(target: "snapshot::take", error = %e, "跳过 state.vscdb.backup");
+                false
+            }
+        };
+
+        let info = SnapshotInfo {
+            id: id.clone(),
+            operation: operation.to_string(),
+            created_at: now.to_rfc3339(),
+            source_path: live_db.display().to_string(),
+            has_backup_db,
+        };
+        let json = serde_json::to_string_pretty(&info)
+            .map_err(|e| format!("序列化快照信息失败: {}", e))?;
+        fs::write(snapshot_dir.join(META_FILE), json)
+            .map_err(|e| format!("写入快照信息失败: {}", e))?;
+
+        Ok::<_, String>(info)
+    })();
+
+    match result {
+        Ok(info) => {
+            tracing::info!(target: "snapshot::take", id = %info.id, "已创建 state.vscdb 快照");
+            prune(snapshots_dir, MAX_SNAPSHOTS);
+            Ok(info)
+        }
+        Err(e) => {
+            let _ = fs::remove_dir_all(&snapshot_dir);
+            Err(format!("创建 state.vscdb 快照失败: {}", e))
+        }
+    }
+}
+
+fn list_in(snapshots_dir: &Path) -> Result<Vec<SnapshotInfo>, String> {
+    let mut snapshots: Vec<SnapshotInfo> = snapshot_dirs(snapshots_dir)?
+        .into_iter()
+        .filter_map(|dir| {
+            let content = fs::read_to_string(dir.join(META_FILE)).ok()?;
+            serde_json::from_str(&content).ok()
+        })
+        .collect();
+
+    snapshots.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(snapshots)
+}
+
+fn restore_latest_in(snapshots_dir: &Path, live_db: &Path) -> Result<SnapshotInfo, String> {
+    let info = list_in(snapshots_dir)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| "没有可撤销的操作（快照为空）".to_string())?;
+    let snapshot_dir = snapshots_dir.join(&info.id);
+
+    tracing::info!(
+        target: "snapshot::restore",
+        id = %info.id,
+        operation = %info.operation,
+        "开始从快照恢复 state.vscdb"
+    );
+
+    StateDb::open(live_db)?.restore_from(&snapshot_dir.join(DB_FILE))?;
+
+    if info.has_backup_db {
+        let live_backup_db = live_db.with_extension("vscdb.backup");
+        let snapshot_backup_db = snapshot_dir.join(BACKUP_DB_FILE);
+        if live_backup_db.exists() {
+            StateDb::open(&live_backup_db)?.restore_from(&snapshot_backup_db)?;
+        } else {
+            StateDb::open_readonly(&snapshot_backup_db)?.backup_to(&live_backup_db)?;
+        }
+    }
+
+    if let Err(e) = fs::remove_dir_all(&snapshot_dir) {
+        tracing::warn!(target: "snapshot::restore", id = %info.id, error = %e, "删除已恢复的快照失败");
+    }
+
+    tracing::info!(target: "snapshot::restore", id = %info.id, "✅ 已从快照恢复 state.vscdb");
+    Ok(info)
+}
+
+/// 删除超出保留数量的旧快照
+fn prune(snapshots_dir: &Path, keep: usize) {
+    let Ok(mut dirs) = snapshot_dirs(snapshots_dir) else {
+        return;
+    };
+
+    // 目录名以时间开头，倒序即最新在前
+    dirs.sort();
+    dirs.reverse();
+
+    for dir in dirs.into_iter().skip(keep) {
+        match fs::remove_dir_all(&dir) {
+            Ok(()) => {
+                tracing::debug!(target: "snapshot::prune", dir = %dir.display(), "已删除旧快照")
+            }
+            Err(e) => {
+                tracing::warn!(target: "snapshot::prune", dir = %dir.display(), error = %e, "删除旧快照失败")
+            }
+        }
+    }
+}
+
+fn prune_older_than_in(
+    snapshots_dir: &Path,
+    cutoff: chrono::DateTime<chrono::Local>,
+) -> Result<usize, String> {
+    let mut removed = 0;
+    for info in list_in(snapshots_dir)? {
+        let expired = chrono::DateTime::parse_from_rfc3339(&info.created_at)
+            .is_ok_and(|created_at| created_at < cutoff);
+        if !expired {
+            continue;
+        }
+        fs::remove_dir_all(snapshots_dir.join(&info.id))
+            .map_err(|e| format!("删除快照 {} 失败: {}", info.id, e))?;
+        removed += 1;
+    }
+    if removed > 0 {
+        tracing::info!(target: "snapshot::prune", removed, "已删除过期快照");
+    }
+    Ok(removed)
+}
+
+fn snapshot_dirs(snapshots_dir: &Path) -> Result<Vec<PathBuf>, String> {
+    if !snapshots_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = fs::read_dir(snapshots_dir).map_err(|e| format!("读取快照目录失败: {}", e))?;
+    Ok(entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.join(META_FILE).is_file())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::antigravity::state_db::WriteOp;
+    use rusqlite::Connection;
+
+    fn create_db(path: &Path) {
+        let conn = Connection::open(path).unwrap();
+        conn.execute(
+            "CREATE TABLE ItemTable (key TEXT UNIQUE ON CONFLICT REPLACE, value BLOB)",
+            [],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_take_and_undo() {
+        let dir = tempfile::tempdir().unwrap();
+        let live = dir.path().join("state.vscdb");
+        let snapshots = dir.path().join("snapshots");
+        create_db(&live);
+
+        let mut db = StateDb::open(&live).unwrap();
+        db.write(&[WriteOp::Put("account", "a")]).unwrap();
+        take_from(&snapshots, &live, "switch").unwrap();
+        db.write(&[WriteOp::Put("account", "b")]).unwrap();
+
+        let info = restore_latest_in(&snapshots, &live).unwrap();
+        assert_eq!(info.operation, "switch");
+        assert_eq!(db.get("account").unwrap().as_deref(), Some("a"));
+        assert!(list_in(&snapshots).unwrap().is_empty());
+        assert!(restore_latest_in(&snapshots, &live).is_err());
+    }
+
+    #[test]
+    fn test_prune_keeps_latest() {
+        let dir = tempfile::tempdir().unwrap();
+        let live = dir.path().join("state.vscdb");
+        let snapshots = dir.path().join("snapshots");
+        create_db(&live);
+
+        for i in 0..MAX_SNAPSHOTS + 2 {
+            take_from(&snapshots, &live, &format!("op{i:02}")).unwrap();
+        }
+
+        let list = list_in(&snapshots).unwrap();
+        assert_eq!(list.len(), MAX_SNAPSHOTS);
+        assert_eq!(list[0].operation, format!("op{:02}", MAX_SNAPSHOTS + 1));
+    }
+
+    #[test]
+    fn test_prune_older_than() {
+        let dir = tempfile::tempdir().unwrap();
+        let live = dir.path().join("state.vscdb");
+        let snapshots = dir.path().join("snapshots");
+        create_db(&live);
+        take_from(&snapshots, &live, "switch").unwrap();
+
+        let past = chrono::Local::now() - chrono::Duration::days(1);
+        assert_eq!(prune_older_than_in(&snapshots, past).unwrap(), 0);
+        let future = chrono::Local::now() + chrono::Duration::days(1);
+        assert_eq!(prune_older_than_in(&snapshots, future).unwrap(), 1);
+        assert!(list_in(&snapshots).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,356 @@
+//! Antigravity state.vscdb 安全访问层
+//!
+//! Antigravity 运行时会持有 state.vscdb，直接 `Connection::open` 后写入很容易遇到
+//! `database is locked`，而原来的 `unwrap_or(0)` 会把这类失败吞掉。这里统一：
+//! - 打开时设置 busy timeout，并检查 `ItemTable` 是否存在
+//! - 写操作放在事务中执行，写完后回读校验
+//! - 锁冲突单独报告为 [`StateDbError::Locked`]
+//! - WAL 模式下写入后执行 checkpoint，确保主文件中可见
+
+use rusqlite::backup::Backup;
+use rusqlite::{params, Connection, ErrorCode, OpenFlags, OptionalExtension, TransactionBehavior};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::platform;
+
+/// 等待 Antigravity 释放数据库锁的最长时间
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 在线备份每一步复制的页数
+const BACKUP_PAGES_PER_STEP: std::os::raw::c_int = 256;
+
+/// 在线备份每一步之间的间隔，给 Antigravity 留出获取锁的机会
+const BACKUP_STEP_PAUSE: Duration = Duration::from_millis(10);
+
+/// state.vscdb 访问错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateDbError {
+    /// 未找到 Antigravity 安装位置
+    NotInstalled,
+    /// 数据库文件不存在
+    NotFound(PathBuf),
+    /// 数据库被其他进程锁定（通常是 Antigravity 正在写入）
+    Locked(String),
+    /// 数据库中没有 ItemTable 表
+    MissingTable(PathBuf),
+    /// 写入后回读的值与期望不一致
+    VerifyFailed(String),
+    /// 其他 SQLite 错误
+    Sqlite(String),
+}
+
+impl fmt::Display for StateDbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotInstalled => write!(f, "未找到 Antigravity 安装位置"),
+            Self::NotFound(path) => {
+                write!(f, "Antigravity 状态数据库不存在: {}", path.display())
+            }
+            Self::Locked(e) => write!(
+                f,
+                "Antigravity 状态数据库被占用，请稍后重试或先关闭 Antigravity: {}",
+                e
+            ),
+            Self::MissingTable(path) => {
+                write!(f, "数据库中缺少 ItemTable 表: {}", path.display())
+            }
+            Self::VerifyFailed(key) => write!(f, "写入校验失败，回读值不一致: {}", key),
+            Self::Sqlite(e) => write!(f, "数据库操作失败: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for StateDbError {}
+
+impl From<rusqlite::Error> for StateDbError {
+    fn from(e: rusqlite::Error) -> Self {
+        match e.sqlite_error_code() {
+            Some(ErrorCode::DatabaseBusy) | Some(ErrorCode::DatabaseLocked) => {
+                Self::Locked(e.to_string())
+            }
+            _ => Self::Sqlite(e.to_string()),
+        }
+    }
+}
+
+impl From<StateDbError> for String {
+    fn from(e: StateDbError) -> Self {
+        e.to_string()
+    }
+}
+
+/// 对 ItemTable 的单个写操作
+#[derive(Debug, Clone, Copy)]
+pub enum WriteOp<'a> {
+    /// INSERT OR REPLACE
+    Put(&'a str, &'a str),
+    /// DELETE
+    Delete(&'a str),
+}
+
+impl WriteOp<'_> {
+    fn key(&self) -> &str {
+        match self {
+            WriteOp::Put(key, _) | WriteOp::Delete(key) => key,
+        }
+    }
+}
+
+/// state.vscdb 连接封装
+pub struct StateDb {
+    conn: Connection,
+    path: PathBuf,
+}
+
+impl StateDb {
+    /// 以读写模式打开指定数据库（文件必须已存在）
+    pub fn open(path: &Path) -> Result<Self, StateDbError> {
+        Self::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_WRITE)
+    }
+
+    /// 以只读模式打开指定数据库
+    pub fn open_readonly(path: &Path) -> Result<Self, StateDbError> {
+        Self::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+    }
+
+    /// 打开当前 Antigravity 的 state.vscdb（只读）
+    pub fn open_default_readonly() -> Result<Self, StateDbError> {
+        Self::open_readonly(&resolve_default_path()?)
+    }
+
+    fn open_with_flags(path: &Path, flags: OpenFlags) -> Result<Self, StateDbError> {
+        if !path.exists() {
+            return Err(StateDbError::NotFound(path.to_path_buf()));
+        }
+
+        let conn = Connection::open_with_flags(path, flags | OpenFlags::SQLITE_OPEN_NO_MUTEX)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+
+        let db = Self {
+            conn,
+            path: path.to_path_buf(),
+        };
+        db.ensure_item_table()?;
+        Ok(db)
+    }
+
+    fn ensure_item_table(&self) -> Result<(), StateDbError> {
+        let exists: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'ItemTable'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        match exists {
+            Some(_) => Ok(()),
+            None => Err(StateDbError::MissingTable(self.path.clone())),
+        }
+    }
+
+    /// 读取单个键
+    pub fn get(&self, key: &str) -> Result<Option<String>, StateDbError> {
+        Ok(self
+            .conn
+            .query_row("SELECT value FROM ItemTable WHERE key = ?", [key], |row| {
+                row.get(0)
+            })
+            .optional()?)
+    }
+
+    /// 读取 ItemTable 全部键值（按 key 排序）
+    pub fn get_all(&self) -> Result<Vec<(String, String)>, StateDbError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT key, value FROM ItemTable ORDER BY key")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// 在单个事务中执行一组写操作，提交后逐一回读校验
+    ///
+    /// 返回实际受影响的行数。任一操作失败时整个事务回滚。
+    pub fn write(&mut self, ops: &[WriteOp<'_>]) -> Result<usize, StateDbError> {
+        // IMMEDIATE：开始事务时就拿写锁，避免执行到一半才发现被占用
+        let tx = self
+            .conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+        let mut affected = 0;
+        for op in ops {
+            affected += match op {
+                WriteOp::Put(key, value) => tx.execute(
+                    "INSERT OR REPLACE INTO ItemTable (key, value) VALUES (?, ?)",
+                    params![key, value],
+                )?,
+                WriteOp::Delete(key) => tx.execute("DELETE FROM ItemTable WHERE key = ?", [key])?,
+            };
+        }
+        tx.commit()?;
+
+        self.checkpoint_if_wal();
+
+        for op in ops {
+            let actual = self.get(op.key())?;
+            let expected = match op {
+                WriteOp::Put(_, value) => Some(*value),
+                WriteOp::Delete(_) => None,
+            };
+            if actual.as_deref() != expected {
+                return Err(StateDbError::VerifyFailed(op.key().to_string()));
+            }
+        }
+
+        tracing::debug!(
+            target: "state_db::write",
+            path = %self.path.display(),
+            ops = ops.len(),
+            affected = affected,
+            "写入完成并已校验"
+        );
+        Ok(affected)
+    }
+
+    /// 通过 SQLite 在线备份 API 把当前数据库一致地复制到 `dest`
+    ///
+    /// 与直接复制文件不同，备份过程中 Antigravity 的写入不会导致副本损坏。
+    pub fn backup_to(&self, dest: &Path) -> Result<(), StateDbError> {
+        let mut dst = Connection::open(dest)?;
+        let backup = Backup::new(&self.conn, &mut dst)?;
+        backup.run_to_completion(BACKUP_PAGES_PER_STEP, BACKUP_STEP_PAUSE, None)?;
+        Ok(())
+    }
+
+    /// 用 `src` 的完整内容覆盖当前数据库（备份 API 反向执行）
+    ///
+    /// `src` 必须是一个包含 ItemTable 的有效数据库。
+    pub fn restore_from(&mut self, src: &Path) -> Result<(), StateDbError> {
+        let source = Self::open_readonly(src)?;
+        {
+            let backup = Backup::new(&source.conn, &mut self.conn)?;
+            backup.run_to_completion(BACKUP_PAGES_PER_STEP, BACKUP_STEP_PAUSE, None)?;
+        }
+        self.checkpoint_if_wal();
+        self.ensure_item_table()
+    }
+
+    /// WAL 模式下把已提交的数据合并回主文件
+    ///
+    /// Antigravity 读取时也会处理 WAL，这里主要是为了让文件级备份拿到完整数据；
+    /// 被占用时 PASSIVE checkpoint 会直接返回，不视为错误。
+    fn checkpoint_if_wal(&self) {
+        let mode: Result<String, _> = self
+            .conn
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0));
+        if !matches!(mode.as_deref(), Ok(m) if m.eq_ignore_ascii_case("wal")) {
+            return;
+        }
+
+        if let Err(e) = self
+            .conn
+            .query_row("PRAGMA wal_checkpoint(PASSIVE)", [], |_| Ok(()))
+        {
+            tracing::warn!(target: "state_db::write", error = %e, "WAL checkpoint 失败（忽略）");
+        }
+    }
+}
+
+/// 解析当前 Antigravity state.vscdb 路径
+pub fn resolve_default_path() -> Result<PathBuf, StateDbError> {
+    match platform::get_antigravity_db_path() {
+        Some(p) => Ok(p),
+        None => platform::get_all_antigravity_db_paths()
+            .into_iter()
+            .next()
+            .ok_or(StateDbError::NotInstalled),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_db(dir: &Path) -> PathBuf {
+        let path = dir.join("state.vscdb");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute(
+            "CREATE TABLE ItemTable (key TEXT UNIQUE ON CONFLICT REPLACE, value BLOB)",
+            [],
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn test_write_and_verify() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = create_db(dir.path());
+
+        let mut db = StateDb::open(&path).unwrap();
+        db.write(&[WriteOp::Put("a", "1"), WriteOp::Put("b", "2")])
+            .unwrap();
+        db.write(&[WriteOp::Delete("a")]).unwrap();
+
+        assert_eq!(db.get("a").unwrap(), None);
+        assert_eq!(db.get("b").unwrap().as_deref(), Some("2"));
+        assert_eq!(db.get_all().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_missing_table_and_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.vscdb");
+
+        assert!(matches!(
+            StateDb::open(&path),
+            Err(StateDbError::NotFound(_))
+        ));
+
+        Connection::open(&path).unwrap();
+        assert!(matches!(
+            StateDb::open(&path),
+            Err(StateDbError::MissingTable(_))
+        ));
+    }
+
+    #[test]
+    fn test_backup_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = create_db(dir.path());
+        let copy = dir.path().join("copy.vscdb");
+
+        let mut db = StateDb::open(&path).unwrap();
+        db.write(&[WriteOp::Put("a", "1")]).unwrap();
+        db.backup_to(&copy).unwrap();
+
+        db.write(&[WriteOp::Delete("a"), WriteOp::Put("b", "2")])
+            .unwrap();
+        db.restore_from(&copy).unwrap();
+
+        assert_eq!(db.get("a").unwrap().as_deref(), Some("1"));
+        assert_eq!(db.get("b").unwrap(), None);
+    }
+
+    #[test]
+    fn test_lock_contention_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = create_db(dir.path());
+
+        // 模拟 Antigravity 持有写锁：RESERVED 锁下仍可读，但不能写
+        let holder = Connection::open(&path).unwrap();
+        holder.execute_batch("BEGIN IMMEDIATE").unwrap();
+
+        let mut db = StateDb::open(&path).unwrap();
+        db.conn.busy_timeout(Duration::from_millis(50)).unwrap();
+        assert!(matches!(
+            db.write(&[WriteOp::Put("a", "1")]),
+            Err(StateDbError::Locked(_))
+        ));
+    }
+}
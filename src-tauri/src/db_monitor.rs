@@ -1,12 +1,12 @@
 //! 数据库监控模块 - 简化版本：newData, oldData, diff
 
+use crate::antigravity::state_db::{StateDb, StateDbError};
 use serde::Serialize;
 use serde_json::Value;
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter};
 use tokio::sync::Mutex;
 use tokio::time::{interval, Duration};
-use tracing::{error, info, warn};
+use tracing::{info, warn};
 
 // 数据差异结构
 #[derive(Debug, Clone, Serialize)]
@@ -17,17 +17,16 @@ pub struct DataDiff {
 }
 
 // 数据库监控器
+#[derive(Default)]
 pub struct DatabaseMonitor {
-    app_handle: AppHandle,
     last_data: Arc<Mutex<Option<Value>>>,
     is_running: Arc<Mutex<bool>>,
 }
 
 impl DatabaseMonitor {
     /// 创建新的数据库监控器
-    pub fn new(app_handle: AppHandle) -> Self {
+    pub fn new() -> Self {
         Self {
-            app_handle,
             last_data: Arc::new(Mutex::new(None)),
             is_running: Arc::new(Mutex::new(false)),
         }
@@ -39,7 +38,6 @@ impl DatabaseMonitor {
 
         let last_data = self.last_data.clone();
         let is_running = self.is_running.clone();
-        let app_handle = self.app_handle.clone();
 
         // 标记监控为运行状态
         *is_running.lock().await = true;
@@ -71,19 +69,12 @@ impl DatabaseMonitor {
                             if diff.has_changes {
                                 info!("📢 检测到数据库变化: {}", diff.summary);
 
-                                // 构建简化的事件数据：newData, oldData, diff
-                                let event_data = serde_json::json!({
-                                    "newData": new_data,
-                                    "oldData": old_data,
-                                    "diff": diff
+                                // 通过事件总线推送：newData, oldData, diff
+                                crate::events::publish(crate::events::AppEvent::DbChanged {
+                                    new_data: new_data.clone(),
+                                    old_data: old_data.clone(),
+                                    diff,
                                 });
-
-                                // 推送事件到前端
-                                if let Err(e) = app_handle.emit("database-changed", &event_data) {
-                                    error!("❌ 推送数据库变化事件失败: {}", e);
-                                } else {
-                                    info!("✅ 数据库变化事件推送成功");
-                                }
                             }
                         }
 
@@ -107,47 +98,26 @@ impl DatabaseMonitor {
 
     /// 获取完整数据库数据
     async fn get_complete_data() -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
-        // 检测数据库路径
-        let db_path = if cfg!(windows) {
-            dirs::home_dir()
-                .unwrap_or_default()
-                .join("AppData")
-                .join("Roaming")
-                .join("Antigravity")
-                .join("User")
-                .join("globalStorage")
-                .join("state.vscdb")
-        } else {
-            dirs::config_dir()
-                .unwrap_or_default()
-                .join("Antigravity")
-                .join("User")
-                .join("globalStorage")
-                .join("state.vscdb")
-        };
-
         let mut complete_data = serde_json::Map::new();
 
-        if db_path.exists() {
-            let conn = rusqlite::Connection::open(&db_path)?;
-
-            // 查询所有数据（完整的ItemTable）
-            let mut stmt = conn.prepare("SELECT key, value FROM ItemTable ORDER BY key")?;
-
-            let rows: Vec<(String, String)> = stmt
-                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
-                .collect::<Result<Vec<_>, _>>()?;
+        // 只读打开，数据库不存在时返回空数据
+        let db = match StateDb::open_default_readonly() {
+            Ok(db) => db,
+            Err(StateDbError::NotInstalled) | Err(StateDbError::NotFound(_)) => {
+                return Ok(Value::Object(complete_data));
+            }
+            Err(e) => return Err(Box::new(e)),
+        };
 
-            // 构建完整数据对象
-            for (key, value) in rows {
-                // 尝试解析为JSON，如果失败则保持原始字符串
-                let json_value: Value = match serde_json::from_str(&value) {
-                    Ok(parsed) => parsed,
-                    Err(_) => Value::String(value.clone()),
-                };
+        // 查询所有数据（完整的ItemTable），构建完整数据对象
+        for (key, value) in db.get_all()? {
+            // 尝试解析为JSON，如果失败则保持原始字符串
+            let json_value: Value = match serde_json::from_str(&value) {
+                Ok(parsed) => parsed,
+                Err(_) => Value::String(value.clone()),
+            };
 
-                complete_data.insert(key, json_value);
-            }
+            complete_data.insert(key, json_value);
         }
 
         Ok(Value::Object(complete_data))
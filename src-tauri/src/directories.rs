@@ -61,11 +61,46 @@ pub fn get_accounts_directory() -> PathBuf {
     accounts_dir
 }
 
+/// 获取 bundle 导入导出目录（本地接口只能读写该目录下的文件）
+pub fn get_bundles_directory() -> PathBuf {
+    get_config_directory().join("bundles")
+}
+
+/// 获取 state.vscdb 快照目录
+pub fn get_snapshots_directory() -> PathBuf {
+    get_config_directory().join("state-snapshots")
+}
+
 /// 获取应用设置文件路径
 pub fn get_app_settings_file() -> PathBuf {
     get_config_directory().join("app_settings.json")
 }
 
+/// 获取本地服务发现文件路径（端口、PID 等，供 VS Code 扩展和 CLI 查找正在运行的实例）
+pub fn get_server_discovery_file() -> PathBuf {
+    get_config_directory().join("server.json")
+}
+
+/// 获取本地服务 Unix 域套接字路径
+pub fn get_server_socket_file() -> PathBuf {
+    get_config_directory().join("agent.sock")
+}
+
+/// 获取本地服务实例 token 文件路径
+pub fn get_server_token_file() -> PathBuf {
+    get_config_directory().join("server.token")
+}
+
+/// 获取单实例锁文件路径
+pub fn get_instance_lock_file() -> PathBuf {
+    get_config_directory().join("instance.lock")
+}
+
+/// 获取定时任务状态文件路径（上次执行时间与运行记录）
+pub fn get_scheduler_state_file() -> PathBuf {
+    get_config_directory().join("scheduler_state.json")
+}
+
 /// 获取窗口状态文件路径
 pub fn get_window_state_file() -> PathBuf {
     get_config_directory().join("window_state.json")
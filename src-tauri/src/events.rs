@@ -0,0 +1,224 @@
+//! 后端事件总线
+//!
+//! 业务代码通过 [`publish`] 发布 [`AppEvent`]，事件会同时推送到：
+//!
+//! - Tauri 前端窗口（事件名为 [`AppEvent::name`]，载荷为事件数据）
+//! - 已连接的 VSCode 扩展（WebSocket `event` 消息）
+//! - `/api/events` 的 Server-Sent Events 订阅者，便于脚本监听
+//!
+//! 后两者使用 [`AppEvent::external_data`]，不包含账户凭据等敏感数据。
+//!
+//! ```text
+//! publish(AppEvent) ──┬──▶ app.emit(name, data)          Tauri webview
+//!                     ├──▶ websocket::broadcast_event   扩展客户端
+//!                     └──▶ broadcast::Sender            SSE 订阅者
+//! ```
+
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::broadcast;
+
+use crate::app_settings::AppSettings;
+use crate::db_monitor::DataDiff;
+use crate::services::account::AccountMetrics;
+
+/// SSE 订阅者未及时消费时最多缓存的事件数
+const CHANNEL_CAPACITY: usize = 64;
+
+/// Antigravity 进程状态检测间隔
+const PROCESS_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 后端事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(
+    tag = "type",
+    content = "data",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum AppEvent {
+    /// 已切换到指定账户
+    AccountSwitched { email: String },
+    /// 切换账户失败
+    AccountSwitchFailed { email: String, error: String },
+    /// 当前账户已保存到账户目录（`created` 表示此前没有保存过该账户）
+    AccountSaved { email: String, created: bool },
+    /// 账户配额已更新
+    QuotaUpdated { metrics: AccountMetrics },
+    /// Antigravity 状态数据库发生变化（完整数据只推送给 Tauri 前端）
+    DbChanged {
+        new_data: Value,
+        old_data: Value,
+        diff: DataDiff,
+    },
+    /// Antigravity 进程已启动
+    AntigravityStarted,
+    /// Antigravity 进程已退出
+    AntigravityExited,
+    /// 账户的 refresh token 已被吊销（健康检查发现状态由其他状态变为吊销）
+    TokenRevoked { email: String },
+    /// 应用设置已变更
+    SettingsChanged { settings: Box<AppSettings> },
+}
+
+impl AppEvent {
+    /// 事件名（Tauri 事件名 / WebSocket 事件名 / SSE `event` 字段）
+    pub fn name(&self) -> &'static str {
+        match self {
+            AppEvent::AccountSwitched { .. } => "account_switched",
+            AppEvent::AccountSwitchFailed { .. } => "account_switch_failed",
+            AppEvent::AccountSaved { .. } => "account_saved",
+            AppEvent::QuotaUpdated { .. } => "quota_updated",
+            AppEvent::DbChanged { .. } => "db_changed",
+            AppEvent::AntigravityStarted => "antigravity_started",
+            AppEvent::AntigravityExited => "antigravity_exited",
+            AppEvent::TokenRevoked { .. } => "token_revoked",
+            AppEvent::SettingsChanged { .. } => "settings_changed",
+        }
+    }
+
+    /// 事件数据，无数据的事件为 `null`
+    pub fn data(&self) -> Value {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|mut v| v.get_mut("data").map(Value::take))
+            .unwrap_or(Value::Null)
+    }
+
+    /// 推送给 WebSocket / SSE 订阅者的事件数据
+    ///
+    /// 这些通道不要求认证，`db_changed` 只携带变化的字段名，
+    /// 不包含数据库内容（其中有 OAuth token）。
+    pub fn external_data(&self) -> Value {
+        match self {
+            AppEvent::DbChanged { diff, .. } => serde_json::json!({ "diff": diff }),
+            _ => self.data(),
+        }
+    }
+
+    /// 编码为一条 SSE 消息
+    pub fn to_sse(&self) -> String {
+        format!("event: {}\ndata: {}\n\n", self.name(), self.external_data())
+    }
+}
+
+static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+lazy_static::lazy_static! {
+    static ref CHANNEL: broadcast::Sender<AppEvent> = broadcast::channel(CHANNEL_CAPACITY).0;
+}
+
+/// 初始化事件总线并启动 Antigravity 进程状态检测
+pub fn init(app_handle: AppHandle) {
+    if APP_HANDLE.set(app_handle).is_err() {
+        return;
+    }
+    tauri::async_runtime::spawn(watch_antigravity_process());
+}
+
+/// 发布事件
+pub fn publish(event: AppEvent) {
+    let name = event.name();
+    let data = event.data();
+    tracing::debug!(target: "events", event = name, "发布事件");
+
+    if let Some(app) = APP_HANDLE.get() {
+        if let Err(e) = app.emit(name, &data) {
+            tracing::warn!(target: "events", event = name, error = %e, "推送事件到前端失败");
+        }
+    }
+    crate::server::websocket::broadcast_event(name, event.external_data());
+    // 没有订阅者时发送失败，属于正常情况
+    let _ = CHANNEL.send(event);
+}
+
+/// 订阅事件（用于 SSE）
+pub fn subscribe() -> broadcast::Receiver<AppEvent> {
+    CHANNEL.subscribe()
+}
+
+/// 定期检测 Antigravity 进程，状态变化时发布启动 / 退出事件
+async fn watch_antigravity_process() {
+    // 进程枚举是阻塞操作，放到阻塞线程池执行
+    async fn is_running() -> Option<bool> {
+        tokio::task::spawn_blocking(crate::platform::is_antigravity_running)
+            .await
+            .ok()
+    }
+
+    let mut was_running = is_running().await.unwrap_or(false);
+    let mut interval = tokio::time::interval(PROCESS_POLL_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        let Some(running) = is_running().await else {
+            continue;
+        };
+        if running != was_running {
+            publish(if running {
+                AppEvent::AntigravityStarted
+            } else {
+                AppEvent::AntigravityExited
+            });
+            was_running = running;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_encoding() {
+        let event = AppEvent::DbChanged {
+            new_data: serde_json::json!({ "a": 1 }),
+            old_data: Value::Null,
+            diff: DataDiff {
+                has_changes: true,
+                changed_fields: vec!["a: added".to_string()],
+                summary: "1 fields changed".to_string(),
+            },
+        };
+        assert_eq!(event.name(), "db_changed");
+        let data = event.data();
+        assert_eq!(data["newData"]["a"], 1);
+        assert!(data.get("diff").is_some());
+
+        assert_eq!(AppEvent::AntigravityExited.data(), Value::Null);
+        assert_eq!(
+            AppEvent::AccountSwitched {
+                email: "a@example.com".to_string()
+            }
+            .to_sse(),
+            "event: account_switched\ndata: {\"email\":\"a@example.com\"}\n\n"
+        );
+    }
+
+    #[test]
+    fn test_db_changed_sse_omits_tokens() {
+        let state = serde_json::json!({
+            "jetskiStateSync.agentManagerInitState": "c2VjcmV0LXJlZnJlc2gtdG9rZW4=",
+            "antigravityAuthStatus": { "apiKey": "secret-access-token" }
+        });
+        let event = AppEvent::DbChanged {
+            new_data: state.clone(),
+            old_data: state,
+            diff: DataDiff {
+                has_changes: true,
+                changed_fields: vec!["antigravityAuthStatus: changed".to_string()],
+                summary: "1 fields changed".to_string(),
+            },
+        };
+
+        let frame = event.to_sse();
+        assert!(frame.starts_with("event: db_changed\n"));
+        assert!(frame.contains("antigravityAuthStatus: changed"));
+        assert!(!frame.contains("secret-access-token"), "{}", frame);
+        assert!(!frame.contains("c2VjcmV0LXJlZnJlc2gtdG9rZW4="), "{}", frame);
+        assert!(event.external_data().get("newData").is_none());
+    }
+}
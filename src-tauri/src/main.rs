@@ -12,6 +12,7 @@ mod app_settings;
 mod config_manager;
 mod constants;
 mod directories;
+mod events;
 mod platform;
 mod proto;
 mod server; // New module
@@ -35,15 +36,17 @@ fn init_tracing() -> WorkerGuard {
     let app_settings_path = crate::directories::get_app_settings_file();
     let settings = crate::app_settings::load_settings_from_disk(&app_settings_path);
 
-    // 日志过滤器：默认 info，降低 h2/hyper 噪音（可被 RUST_LOG 覆盖）
-    // Debug Mode 开启时：仅放开应用相关的 debug（以及 frontend），避免依赖库（如 reqwest）刷屏。
-    let default_filter = if settings.debug_mode {
-        "info,antigravity_agent=debug,frontend=debug,app=debug,window=debug,account=debug,restore=debug,cleanup=debug,backup=debug,h2=warn,hyper=warn"
-    } else {
-        "info,h2=warn,hyper=warn"
-    };
-    let env_filter =
-        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_filter));
+    // 日志过滤器（可被 RUST_LOG 覆盖），未设置 RUST_LOG 时随 Debug Mode 在运行时调整
+    let env_filter = EnvFilter::try_from_default_env().ok();
+    let follows_settings = env_filter.is_none();
+    let (env_filter, filter_handle) = tracing_subscriber::reload::Layer::new(
+        env_filter.unwrap_or_else(|| {
+            EnvFilter::new(crate::utils::tracing_config::default_filter(settings.debug_mode))
+        }),
+    );
+    if follows_settings {
+        crate::utils::tracing_config::set_filter_handle(filter_handle);
+    }
 
     // 创建日志目录
     let log_dir = crate::directories::get_log_directory();
@@ -96,6 +99,32 @@ fn main() {
         Err(e) => tracing::error!(target: "app::startup", "⚠️ 账户目录迁移检查失败: {}", e),
     }
 
+    // 单实例：已有实例在运行时把启动参数转发给它后退出
+    let launch_command =
+        match services::launch::LaunchCommand::from_args(std::env::args().skip(1)) {
+            Ok(command) => command,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(2);
+            }
+        };
+    let forwarded = launch_command
+        .clone()
+        .unwrap_or(services::launch::LaunchCommand::Show);
+    match services::launch::forward_to_running_instance(&forwarded) {
+        Ok(Some(message)) => {
+            tracing::info!(target: "app::startup", "已有实例在运行，命令已转发: {}", message);
+            println!("{}", message);
+            return;
+        }
+        Ok(None) => {}
+        Err(e) => {
+            tracing::error!(target: "app::startup", "已有实例在运行，命令执行失败: {}", e);
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+
     // 初始化 AppState (内部已包含 Arc<Mutex>)
     let app_state = AppState::default();
 
@@ -117,10 +146,37 @@ fn main() {
             let handle = app.handle().clone();
             server::init(handle, app_state.clone());
 
+            // 注册 antigravity-agent:// 协议（涉及外部命令，放到后台线程）
+            std::thread::spawn(services::deep_link::register_scheme);
+
+            // 首个实例自行执行启动参数中的命令
+            if let Some(command) = launch_command.clone() {
+                let handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = services::launch::execute(&handle, command).await {
+                        tracing::error!(target: "app::launch", "启动命令执行失败: {}", e);
+                    }
+                });
+            }
+
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![])
-
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .invoke_handler(tauri::generate_handler![server::discovery::get_server_url])
+
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_app, _event| {
+            // macOS 通过 Apple Event 而不是启动参数传递深链接
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Opened { urls } = _event {
+                for url in urls {
+                    let handle = _app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = services::deep_link::handle(&handle, url.as_str()).await {
+                            tracing::error!(target: "app::deep_link", "深链接处理失败: {}", e);
+                        }
+                    });
+                }
+            }
+        });
 }
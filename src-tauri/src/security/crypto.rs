@@ -2,20 +2,42 @@
 //!
 //! 使用 ChaCha20-Poly1305 认证加密 + Argon2id 密钥派生
 //!
-//! 输出格式（Base64 编码）：
-//! [version: 1 byte][salt: 16 bytes][nonce: 12 bytes][ciphertext + tag]
+//! v2 输出格式（Base64 编码，当前写入格式）：
+//! ```text
+//! [version=2: 1][kdf: 1][m_cost: u32 LE][t_cost: u32 LE][p_cost: u32 LE][flags: 1]
+//! [salt_len: 1][salt][nonce: 12][ad_len: u32 LE][associated data][ciphertext + tag]
+//! ```
+//! 密文之前的全部头部（含 KDF 参数与关联数据）都作为 AEAD 的 associated data 参与认证，
+//! 因此参数可以随时调整而不影响旧文件，关联数据（如 bundle manifest）可明文读取但无法篡改。
+//! `flags` 的最低位表示需要密钥文件作为第二因子（其 SHA-256 作为 Argon2 secret）。
+//!
+//! v1 格式（只读）：`[version=1][salt: 16][nonce: 12][ciphertext + tag]`，固定 m=64MB, t=3, p=4。
+//!
+//! 解密时自动识别旧版导出使用的 XOR + Base64 混淆格式（无版本头），
+//! 旧文件可通过 [`reencrypt_with_options`] 转换为当前格式。
 
 use argon2::{Algorithm, Argon2, ParamsBuilder, Version};
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use chacha20poly1305::{
-    aead::{Aead, KeyInit},
+    aead::{Aead, KeyInit, Payload},
     ChaCha20Poly1305, Nonce,
 };
 use rand::RngCore;
-use zeroize::Zeroize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::{Zeroize, Zeroizing};
+
+/// v1 格式版本（只读）
+const CRYPTO_VERSION_V1: u8 = 1;
 
 /// 当前加密格式版本
-const CRYPTO_VERSION: u8 = 1;
+const CRYPTO_VERSION: u8 = 2;
+
+/// KDF 算法标识：Argon2id (v0x13)
+const KDF_ARGON2ID: u8 = 1;
+
+/// flags：需要密钥文件
+const FLAG_KEYFILE: u8 = 0b0000_0001;
 
 /// Salt 长度（字节）
 const SALT_LEN: usize = 16;
@@ -23,69 +45,359 @@ const SALT_LEN: usize = 16;
 /// Nonce 长度（字节）
 const NONCE_LEN: usize = 12;
 
-/// 使用密码加密配置数据（用于账户导出）
+/// Poly1305 tag 长度（字节）
+const TAG_LEN: usize = 16;
+
+/// 解密时接受的 KDF 参数上限，防止恶意文件耗尽内存 / CPU
+const MAX_M_COST: u32 = 1024 * 1024; // 1 GB
+const MAX_T_COST: u32 = 32;
+const MAX_P_COST: u32 = 16;
+
+/// 加密时允许的最低 KDF 参数（OWASP 建议的 Argon2id 下限：19 MiB 内存、2 次迭代）
+const MIN_M_COST: u32 = 19 * 1024;
+const MIN_T_COST: u32 = 2;
+const MIN_P_COST: u32 = 1;
+
+/// 加密数据格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EncryptedFormat {
+    /// 当前格式：自描述头部（KDF 参数、关联数据、密钥文件标记）
+    V2,
+    /// 带版本头的 Argon2id + ChaCha20-Poly1305，参数固定
+    V1,
+    /// 旧版导出：密码逐字节 XOR 后 Base64 编码，没有任何头部
+    LegacyXor,
+}
+
+impl EncryptedFormat {
+    /// 是否为应当转换为当前格式的旧格式
+    pub fn needs_reencrypt(self) -> bool {
+        self != Self::V2
+    }
+}
+
+/// Argon2id 参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// 内存开销（KB）
+    pub m_cost: u32,
+    /// 迭代次数
+    pub t_cost: u32,
+    /// 并行度
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        // 适中的参数（内存 64MB，3 次迭代，4 并行度）
+        Self {
+            m_cost: 65536,
+            t_cost: 3,
+            p_cost: 4,
+        }
+    }
+}
+
+impl KdfParams {
+    /// 校验参数上限，避免恶意文件头部耗尽内存 / CPU（解密时只做这项检查，兼容已有文件）
+    fn validate(&self) -> Result<(), String> {
+        if self.m_cost > MAX_M_COST || self.t_cost > MAX_T_COST || self.p_cost > MAX_P_COST {
+            return Err(format!(
+                "KDF 参数超出允许范围: m={} t={} p={}",
+                self.m_cost, self.t_cost, self.p_cost
+            ));
+        }
+        Ok(())
+    }
+
+    /// 加密时额外要求参数不低于下限，避免生成容易暴力破解的文件
+    fn validate_for_encryption(&self) -> Result<(), String> {
+        self.validate()?;
+        if self.m_cost < MIN_M_COST || self.t_cost < MIN_T_COST || self.p_cost < MIN_P_COST {
+            return Err(format!(
+                "KDF 参数过低: m={} t={} p={}（最低 m={} t={} p={}）",
+                self.m_cost, self.t_cost, self.p_cost, MIN_M_COST, MIN_T_COST, MIN_P_COST
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// v2 加密选项
+#[derive(Debug, Clone, Default)]
+pub struct EncryptOptions<'a> {
+    pub kdf: KdfParams,
+    /// 明文存储但参与认证的关联数据
+    pub associated_data: &'a [u8],
+    /// 密钥文件内容（第二因子）
+    pub keyfile: Option<&'a [u8]>,
+}
+
+/// 解密结果
+#[derive(Debug, Clone)]
+pub struct Decrypted {
+    pub plaintext: String,
+    pub format: EncryptedFormat,
+    /// v2 头部中的关联数据（其他格式为空）
+    pub associated_data: Vec<u8>,
+}
+
+/// 解析后的 v2 头部
+struct V2Header<'a> {
+    kdf: KdfParams,
+    keyfile_required: bool,
+    salt: &'a [u8],
+    nonce: &'a [u8],
+    associated_data: &'a [u8],
+    /// 头部总长度（即密文起始位置）
+    len: usize,
+}
+
+/// 根据头部识别加密数据格式（输入为 Base64 解码后的字节）
+///
+/// 旧格式没有头部，因此只要不符合版本头布局即视为旧格式；
+/// 旧格式的首字节恰好等于版本号时，由 [`decrypt_with_options`] 回退处理。
+pub fn detect_format(data: &[u8]) -> EncryptedFormat {
+    match data.first() {
+        Some(&CRYPTO_VERSION) if parse_v2_header(data).is_ok() => EncryptedFormat::V2,
+        Some(&CRYPTO_VERSION_V1) if data.len() >= 1 + SALT_LEN + NONCE_LEN + TAG_LEN => {
+            EncryptedFormat::V1
+        }
+        _ => EncryptedFormat::LegacyXor,
+    }
+}
+
+fn parse_v2_header(data: &[u8]) -> Result<V2Header<'_>, String> {
+    let invalid = || "加密数据头部无效".to_string();
+    let take = |pos: &mut usize, n: usize| -> Result<&[u8], String> {
+        let slice = data.get(*pos..*pos + n).ok_or_else(invalid)?;
+        *pos += n;
+        Ok(slice)
+    };
+    let read_u32 = |pos: &mut usize| -> Result<u32, String> {
+        Ok(u32::from_le_bytes(take(pos, 4)?.try_into().unwrap()))
+    };
+
+    let mut pos = 0;
+    if take(&mut pos, 1)?[0] != CRYPTO_VERSION {
+        return Err(invalid());
+    }
+    let kdf_id = take(&mut pos, 1)?[0];
+    if kdf_id != KDF_ARGON2ID {
+        return Err(format!("不支持的 KDF 算法: {}", kdf_id));
+    }
+    let kdf = KdfParams {
+        m_cost: read_u32(&mut pos)?,
+        t_cost: read_u32(&mut pos)?,
+        p_cost: read_u32(&mut pos)?,
+    };
+    let flags = take(&mut pos, 1)?[0];
+    let salt_len = take(&mut pos, 1)?[0] as usize;
+    let salt = take(&mut pos, salt_len)?;
+    let nonce = take(&mut pos, NONCE_LEN)?;
+    let ad_len = read_u32(&mut pos)? as usize;
+    let associated_data = take(&mut pos, ad_len)?;
+
+    if data.len() < pos + TAG_LEN {
+        return Err(invalid());
+    }
+
+    Ok(V2Header {
+        kdf,
+        keyfile_required: flags & FLAG_KEYFILE != 0,
+        salt,
+        nonce,
+        associated_data,
+        len: pos,
+    })
+}
+
+/// 按指定选项加密为 v2 格式
 ///
 /// 算法：Argon2id 密钥派生 + ChaCha20-Poly1305 认证加密
-pub async fn encrypt_config_data(json_data: String, password: String) -> Result<String, String> {
+pub fn encrypt_with_options(
+    plaintext: &[u8],
+    password: &str,
+    options: &EncryptOptions,
+) -> Result<String, String> {
+    options.kdf.validate_for_encryption()?;
+    encrypt_v2(plaintext, password, options)
+}
+
+fn encrypt_v2(
+    plaintext: &[u8],
+    password: &str,
+    options: &EncryptOptions,
+) -> Result<String, String> {
     if password.is_empty() {
         return Err("密码不能为空".to_string());
     }
+    options.kdf.validate()?;
 
-    // 生成随机 salt
+    // 生成随机 salt 和 nonce
     let mut salt_bytes = [0u8; SALT_LEN];
     rand::thread_rng().fill_bytes(&mut salt_bytes);
-
-    // 派生密钥
-    let mut key = derive_key(&password, &salt_bytes)?;
-
-    // 生成随机 nonce
     let mut nonce_bytes = [0u8; NONCE_LEN];
     rand::thread_rng().fill_bytes(&mut nonce_bytes);
-    let nonce = Nonce::from_slice(&nonce_bytes);
 
-    // 加密
-    let cipher = ChaCha20Poly1305::new_from_slice(&key)
-        .map_err(|e| format!("初始化加密器失败: {}", e))?;
+    // 组装头部
+    let mut output = Vec::with_capacity(64 + options.associated_data.len() + plaintext.len());
+    output.push(CRYPTO_VERSION);
+    output.push(KDF_ARGON2ID);
+    output.extend_from_slice(&options.kdf.m_cost.to_le_bytes());
+    output.extend_from_slice(&options.kdf.t_cost.to_le_bytes());
+    output.extend_from_slice(&options.kdf.p_cost.to_le_bytes());
+    output.push(if options.keyfile.is_some() {
+        FLAG_KEYFILE
+    } else {
+        0
+    });
+    output.push(SALT_LEN as u8);
+    output.extend_from_slice(&salt_bytes);
+    output.extend_from_slice(&nonce_bytes);
+    output.extend_from_slice(&(options.associated_data.len() as u32).to_le_bytes());
+    output.extend_from_slice(options.associated_data);
 
+    // 派生密钥（离开作用域时清除）
+    let key = derive_key(password, &salt_bytes, &options.kdf, options.keyfile)?;
+
+    // 加密，整个头部作为 associated data
+    let cipher = ChaCha20Poly1305::new_from_slice(key.as_slice())
+        .map_err(|e| format!("初始化加密器失败: {}", e))?;
     let ciphertext = cipher
-        .encrypt(nonce, json_data.as_bytes())
+        .encrypt(
+            Nonce::from_slice(&nonce_bytes),
+            Payload {
+                msg: plaintext,
+                aad: &output,
+            },
+        )
         .map_err(|e| format!("加密失败: {}", e))?;
 
-    // 清除密钥
-    key.zeroize();
-
-    // 组装输出: version + salt + nonce + ciphertext
-    let mut output = Vec::with_capacity(1 + SALT_LEN + NONCE_LEN + ciphertext.len());
-    output.push(CRYPTO_VERSION);
-    output.extend_from_slice(&salt_bytes);
-    output.extend_from_slice(&nonce_bytes);
     output.extend_from_slice(&ciphertext);
-
     Ok(BASE64.encode(&output))
 }
 
-/// 使用密码解密配置数据（用于账户导入）
-pub async fn decrypt_config_data(
-    encrypted_data: String,
-    password: String,
-) -> Result<String, String> {
+/// 解密任意受支持格式的数据，自动识别 v2 / v1 / 旧版 XOR 格式
+///
+/// `keyfile` 仅在 v2 头部标记需要密钥文件时使用。
+pub fn decrypt_with_options(
+    encrypted_data: &str,
+    password: &str,
+    keyfile: Option<&[u8]>,
+) -> Result<Decrypted, String> {
     if password.is_empty() {
         return Err("密码不能为空".to_string());
     }
 
     let data = BASE64
-        .decode(&encrypted_data)
+        .decode(encrypted_data.trim())
         .map_err(|_| "Base64 解码失败，文件可能已损坏".to_string())?;
 
-    // 检查最小长度
-    let min_len = 1 + SALT_LEN + NONCE_LEN + 16; // 16 是 Poly1305 tag 长度
-    if data.len() < min_len {
-        return Err("加密数据格式无效".to_string());
+    let format = detect_format(&data);
+    let result = match format {
+        EncryptedFormat::V2 => decrypt_v2(&data, password, keyfile),
+        EncryptedFormat::V1 => decrypt_v1(&data, password).map(|plaintext| Decrypted {
+            plaintext,
+            format,
+            associated_data: Vec::new(),
+        }),
+        EncryptedFormat::LegacyXor => {
+            let plaintext = decrypt_legacy_xor(&data, password)?;
+            tracing::info!(target: "security::crypto", "检测到旧版 XOR 格式的导出文件");
+            return Ok(Decrypted {
+                plaintext,
+                format,
+                associated_data: Vec::new(),
+            });
+        }
+    };
+
+    // 旧格式首字节可能恰好等于版本号，认证失败时再尝试旧格式
+    result.or_else(|e| {
+        decrypt_legacy_xor(&data, password)
+            .map(|plaintext| Decrypted {
+                plaintext,
+                format: EncryptedFormat::LegacyXor,
+                associated_data: Vec::new(),
+            })
+            .map_err(|_| e)
+    })
+}
+
+/// 将任意受支持格式的加密数据按指定选项转换为当前格式
+///
+/// `options.keyfile` 同时用于解密原数据和加密新数据；`options.associated_data`
+/// 为空时沿用原数据中的关联数据。返回新的加密数据以及原始格式。
+pub fn reencrypt_with_options(
+    encrypted_data: &str,
+    password: &str,
+    options: &EncryptOptions,
+) -> Result<(String, EncryptedFormat), String> {
+    let decrypted = decrypt_with_options(encrypted_data, password, options.keyfile)?;
+    let associated_data = if options.associated_data.is_empty() {
+        &decrypted.associated_data
+    } else {
+        options.associated_data
+    };
+    let reencrypted = encrypt_with_options(
+        decrypted.plaintext.as_bytes(),
+        password,
+        &EncryptOptions {
+            associated_data,
+            ..options.clone()
+        },
+    )?;
+    Ok((reencrypted, decrypted.format))
+}
+
+fn decrypt_v2(data: &[u8], password: &str, keyfile: Option<&[u8]>) -> Result<Decrypted, String> {
+    let header = parse_v2_header(data)?;
+    header.kdf.validate()?;
+
+    if header.keyfile_required && keyfile.is_none() {
+        return Err("该文件需要密钥文件才能解密".to_string());
     }
+    let keyfile = if header.keyfile_required {
+        keyfile
+    } else {
+        None
+    };
+
+    // 派生密钥（离开作用域时清除，包括提前返回的错误路径）
+    let key = derive_key(password, header.salt, &header.kdf, keyfile)?;
+
+    // 解密
+    let cipher = ChaCha20Poly1305::new_from_slice(key.as_slice())
+        .map_err(|e| format!("初始化解密器失败: {}", e))?;
+    let plaintext = cipher
+        .decrypt(
+            Nonce::from_slice(header.nonce),
+            Payload {
+                msg: &data[header.len..],
+                aad: &data[..header.len],
+            },
+        )
+        .map_err(|_| {
+            if header.keyfile_required {
+                "解密失败：密码或密钥文件错误，或数据已损坏".to_string()
+            } else {
+                "解密失败：密码错误或数据已损坏".to_string()
+            }
+        })?;
+
+    Ok(Decrypted {
+        plaintext: String::from_utf8(plaintext)
+            .map_err(|_| "解密后的数据不是有效的 UTF-8 文本".to_string())?,
+        format: EncryptedFormat::V2,
+        associated_data: header.associated_data.to_vec(),
+    })
+}
 
-    // 解析格式
+fn decrypt_v1(data: &[u8], password: &str) -> Result<String, String> {
     let version = data[0];
-    if version != CRYPTO_VERSION {
+    if version != CRYPTO_VERSION_V1 {
         return Err(format!("不支持的加密格式版本: {}", version));
     }
 
@@ -93,11 +405,11 @@ pub async fn decrypt_config_data(
     let nonce_bytes = &data[1 + SALT_LEN..1 + SALT_LEN + NONCE_LEN];
     let ciphertext = &data[1 + SALT_LEN + NONCE_LEN..];
 
-    // 派生密钥
-    let mut key = derive_key(&password, salt)?;
+    // 派生密钥（v1 固定使用默认参数，离开作用域时清除）
+    let key = derive_key(password, salt, &KdfParams::default(), None)?;
 
     // 解密
-    let cipher = ChaCha20Poly1305::new_from_slice(&key)
+    let cipher = ChaCha20Poly1305::new_from_slice(key.as_slice())
         .map_err(|e| format!("初始化解密器失败: {}", e))?;
 
     let nonce = Nonce::from_slice(nonce_bytes);
@@ -105,30 +417,62 @@ pub async fn decrypt_config_data(
         .decrypt(nonce, ciphertext)
         .map_err(|_| "解密失败：密码错误或数据已损坏".to_string())?;
 
-    // 清除密钥
-    key.zeroize();
-
     String::from_utf8(plaintext).map_err(|_| "解密后的数据不是有效的 UTF-8 文本".to_string())
 }
 
+/// 解密旧版 XOR 混淆数据
+///
+/// 旧格式没有完整性校验，错误的密码也能"解密"出数据，
+/// 因此要求结果必须是合法的 JSON（旧版导出内容均为 JSON）。
+fn decrypt_legacy_xor(data: &[u8], password: &str) -> Result<String, String> {
+    let password_bytes = password.as_bytes();
+    let plaintext: Vec<u8> = data
+        .iter()
+        .enumerate()
+        .map(|(i, byte)| byte ^ password_bytes[i % password_bytes.len()])
+        .collect();
+
+    let text =
+        String::from_utf8(plaintext).map_err(|_| "解密失败：密码错误或数据已损坏".to_string())?;
+    serde_json::from_str::<serde::de::IgnoredAny>(&text)
+        .map_err(|_| "解密失败：密码错误或数据已损坏".to_string())?;
+
+    Ok(text)
+}
+
 /// 使用 Argon2id 从密码派生 32 字节密钥
-fn derive_key(password: &str, salt: &[u8]) -> Result<[u8; 32], String> {
-    // 使用适中的参数（内存 64MB，3 次迭代，4 并行度）
+///
+/// 提供密钥文件时，其 SHA-256 作为 Argon2 secret 参与派生。
+fn derive_key(
+    password: &str,
+    salt: &[u8],
+    kdf: &KdfParams,
+    keyfile: Option<&[u8]>,
+) -> Result<Zeroizing<[u8; 32]>, String> {
     let params = ParamsBuilder::new()
-        .m_cost(65536) // 64 MB
-        .t_cost(3)
-        .p_cost(4)
+        .m_cost(kdf.m_cost)
+        .t_cost(kdf.t_cost)
+        .p_cost(kdf.p_cost)
         .output_len(32)
         .build()
         .map_err(|e| format!("构建 Argon2 参数失败: {}", e))?;
 
-    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+    let mut secret = keyfile.map(Sha256::digest);
+    let argon2 = match &secret {
+        Some(secret) => {
+            Argon2::new_with_secret(secret, Algorithm::Argon2id, Version::V0x13, params)
+                .map_err(|e| format!("初始化 Argon2 失败: {}", e))?
+        }
+        None => Argon2::new(Algorithm::Argon2id, Version::V0x13, params),
+    };
 
     // 使用 hash_password_into 获取原始字节
-    let mut key = [0u8; 32];
-    argon2
-        .hash_password_into(password.as_bytes(), salt, &mut key)
-        .map_err(|e| format!("密钥派生失败: {}", e))?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    let result = argon2.hash_password_into(password.as_bytes(), salt, key.as_mut_slice());
+    if let Some(secret) = secret.as_mut() {
+        secret.as_mut_slice().zeroize();
+    }
+    result.map_err(|e| format!("密钥派生失败: {}", e))?;
 
     Ok(key)
 }
@@ -137,46 +481,247 @@ fn derive_key(password: &str, salt: &[u8]) -> Result<[u8; 32], String> {
 mod tests {
     use super::*;
 
-    #[tokio::test]
-    async fn test_encrypt_decrypt_roundtrip() {
-        let original = r#"{"email": "test@example.com", "token": "secret123"}"#.to_string();
-        let password = "test_password".to_string();
+    fn encrypt(data: &str, password: &str) -> Result<String, String> {
+        encrypt_with_options(data.as_bytes(), password, &EncryptOptions::default())
+    }
+
+    fn decrypt(data: &str, password: &str) -> Result<(String, EncryptedFormat), String> {
+        decrypt_with_options(data, password, None).map(|d| (d.plaintext, d.format))
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        let original = r#"{"email": "test@example.com", "token": "secret123"}"#;
+        let password = "test_password";
 
-        let encrypted = encrypt_config_data(original.clone(), password.clone())
-            .await
-            .expect("加密失败");
+        let encrypted = encrypt(original, password).expect("加密失败");
 
         assert_ne!(encrypted, original);
         assert!(encrypted.len() > original.len());
 
-        let decrypted = decrypt_config_data(encrypted, password)
-            .await
-            .expect("解密失败");
+        let (decrypted, _) = decrypt(&encrypted, password).expect("解密失败");
 
         assert_eq!(decrypted, original);
     }
 
-    #[tokio::test]
-    async fn test_wrong_password_fails() {
-        let original = "test data".to_string();
-        let password = "correct_password".to_string();
+    #[test]
+    fn test_wrong_password_fails() {
+        let encrypted = encrypt("test data", "correct_password").expect("加密失败");
 
-        let encrypted = encrypt_config_data(original, password)
-            .await
-            .expect("加密失败");
-
-        let result = decrypt_config_data(encrypted, "wrong_password".to_string()).await;
+        let result = decrypt(&encrypted, "wrong_password");
 
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("密码错误"));
     }
 
-    #[tokio::test]
-    async fn test_empty_password_rejected() {
-        let result = encrypt_config_data("data".to_string(), "".to_string()).await;
-        assert!(result.is_err());
+    #[test]
+    fn test_empty_password_rejected() {
+        assert!(encrypt("data", "").is_err());
+        assert!(decrypt("data", "").is_err());
+    }
 
-        let result = decrypt_config_data("data".to_string(), "".to_string()).await;
-        assert!(result.is_err());
+    fn detect(encrypted: &str) -> EncryptedFormat {
+        detect_format(&BASE64.decode(encrypted).unwrap())
+    }
+
+    /// 按旧版实现生成 XOR 格式数据
+    fn legacy_encrypt(json: &str, password: &str) -> String {
+        let key = password.as_bytes();
+        let bytes: Vec<u8> = json
+            .bytes()
+            .enumerate()
+            .map(|(i, b)| b ^ key[i % key.len()])
+            .collect();
+        BASE64.encode(bytes)
+    }
+
+    #[test]
+    fn test_detect_and_decrypt_legacy_xor() {
+        let original = r#"{"version": "1.1.0", "backups": []}"#;
+        let legacy = legacy_encrypt(original, "old_password");
+
+        assert_eq!(detect(&legacy), EncryptedFormat::LegacyXor);
+        let (decrypted, format) = decrypt(&legacy, "old_password").expect("解密旧格式失败");
+        assert_eq!(decrypted, original);
+        assert_eq!(format, EncryptedFormat::LegacyXor);
+        assert!(format.needs_reencrypt());
+
+        let result = decrypt(&legacy, "wrong");
+        assert!(result.unwrap_err().contains("密码错误"));
+    }
+
+    #[test]
+    fn test_legacy_with_version_like_first_byte() {
+        // '{' ^ 'z' == 0x01，旧格式首字节与版本号相同
+        let original = format!(r#"{{"backups": [], "padding": "{}"}}"#, "x".repeat(64));
+        let legacy = legacy_encrypt(&original, "z");
+        assert_eq!(detect(&legacy), EncryptedFormat::V1);
+
+        let (decrypted, format) = decrypt(&legacy, "z").expect("回退到旧格式失败");
+        assert_eq!(decrypted, original);
+        assert_eq!(format, EncryptedFormat::LegacyXor);
+    }
+
+    #[test]
+    fn test_reencrypt_legacy_to_current() {
+        let original = r#"{"backups": []}"#;
+        let legacy = legacy_encrypt(original, "pw");
+
+        let (reencrypted, previous) =
+            reencrypt_with_options(&legacy, "pw", &EncryptOptions::default())
+                .expect("重新加密失败");
+        assert_eq!(previous, EncryptedFormat::LegacyXor);
+        assert_eq!(detect(&reencrypted), EncryptedFormat::V2);
+
+        let (decrypted, format) = decrypt(&reencrypted, "pw").unwrap();
+        assert_eq!(decrypted, original);
+        assert_eq!(format, EncryptedFormat::V2);
+        assert!(!format.needs_reencrypt());
+    }
+
+    #[test]
+    fn test_reencrypt_keeps_keyfile_and_associated_data() {
+        let original = encrypt_v2(
+            b"{}",
+            "pw",
+            &EncryptOptions {
+                kdf: FAST_KDF,
+                associated_data: b"manifest",
+                keyfile: Some(b"keyfile-content"),
+            },
+        )
+        .unwrap();
+
+        let options = EncryptOptions {
+            keyfile: Some(b"keyfile-content"),
+            ..Default::default()
+        };
+        let (reencrypted, previous) = reencrypt_with_options(&original, "pw", &options).unwrap();
+        assert_eq!(previous, EncryptedFormat::V2);
+
+        assert!(decrypt_with_options(&reencrypted, "pw", None).is_err());
+        let decrypted = decrypt_with_options(&reencrypted, "pw", Some(b"keyfile-content")).unwrap();
+        assert_eq!(decrypted.plaintext, "{}");
+        assert_eq!(decrypted.associated_data, b"manifest");
+    }
+
+    /// 测试用的低开销参数（低于加密下限，只能通过 `encrypt_v2` 直接使用）
+    const FAST_KDF: KdfParams = KdfParams {
+        m_cost: 8,
+        t_cost: 1,
+        p_cost: 1,
+    };
+
+    #[test]
+    fn test_v2_records_params_and_authenticates_associated_data() {
+        let manifest = br#"{"formatVersion": 1}"#;
+        let options = EncryptOptions {
+            kdf: FAST_KDF,
+            associated_data: manifest,
+            keyfile: None,
+        };
+        let encrypted = encrypt_v2(b"{}", "pw", &options).unwrap();
+
+        let data = BASE64.decode(&encrypted).unwrap();
+        let header = parse_v2_header(&data).unwrap();
+        assert_eq!(header.kdf, FAST_KDF);
+        assert_eq!(header.associated_data, manifest);
+
+        let decrypted = decrypt_with_options(&encrypted, "pw", None).unwrap();
+        assert_eq!(decrypted.format, EncryptedFormat::V2);
+        assert_eq!(decrypted.plaintext, "{}");
+        assert_eq!(decrypted.associated_data, manifest);
+
+        // 篡改明文存储的关联数据会导致认证失败
+        let mut tampered = data.clone();
+        let ad_pos = header.len - manifest.len();
+        tampered[ad_pos + 2] ^= 0x01;
+        assert!(decrypt_with_options(&BASE64.encode(tampered), "pw", None).is_err());
+    }
+
+    #[test]
+    fn test_v2_keyfile_second_factor() {
+        let options = EncryptOptions {
+            kdf: FAST_KDF,
+            associated_data: b"",
+            keyfile: Some(b"keyfile-content"),
+        };
+        let encrypted = encrypt_v2(b"{}", "pw", &options).unwrap();
+
+        let err = decrypt_with_options(&encrypted, "pw", None).unwrap_err();
+        assert!(err.contains("密钥文件"));
+        assert!(decrypt_with_options(&encrypted, "pw", Some(b"other")).is_err());
+        assert_eq!(
+            decrypt_with_options(&encrypted, "pw", Some(b"keyfile-content"))
+                .unwrap()
+                .plaintext,
+            "{}"
+        );
+    }
+
+    #[test]
+    fn test_encrypt_rejects_weak_params_but_decrypts_them() {
+        let options = EncryptOptions {
+            kdf: FAST_KDF,
+            ..Default::default()
+        };
+        let err = encrypt_with_options(b"{}", "pw", &options).unwrap_err();
+        assert!(err.contains("过低"));
+
+        // 已有文件中的低参数仍可解密
+        let encrypted = encrypt_v2(b"{}", "pw", &options).unwrap();
+        assert_eq!(
+            decrypt_with_options(&encrypted, "pw", None)
+                .unwrap()
+                .plaintext,
+            "{}"
+        );
+    }
+
+    #[test]
+    fn test_v2_rejects_excessive_params() {
+        let mut data = BASE64
+            .decode(
+                encrypt_v2(
+                    b"{}",
+                    "pw",
+                    &EncryptOptions {
+                        kdf: FAST_KDF,
+                        ..Default::default()
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        // m_cost 位于 [2..6]
+        data[2..6].copy_from_slice(&u32::MAX.to_le_bytes());
+        let err = decrypt_with_options(&BASE64.encode(data), "pw", None).unwrap_err();
+        assert!(err.contains("超出"));
+    }
+
+    #[test]
+    fn test_v1_still_decrypts() {
+        // 按 v1 布局构造数据
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let key = derive_key("pw", &salt, &KdfParams::default(), None).unwrap();
+        let ciphertext = ChaCha20Poly1305::new_from_slice(key.as_slice())
+            .unwrap()
+            .encrypt(Nonce::from_slice(&nonce), b"{\"v\": 1}".as_slice())
+            .unwrap();
+
+        let mut blob = vec![CRYPTO_VERSION_V1];
+        blob.extend_from_slice(&salt);
+        blob.extend_from_slice(&nonce);
+        blob.extend_from_slice(&ciphertext);
+        let encoded = BASE64.encode(blob);
+
+        assert_eq!(detect(&encoded), EncryptedFormat::V1);
+        let (plaintext, format) = decrypt(&encoded, "pw").unwrap();
+        assert_eq!(plaintext, "{\"v\": 1}");
+        assert_eq!(format, EncryptedFormat::V1);
+        assert!(format.needs_reencrypt());
     }
 }
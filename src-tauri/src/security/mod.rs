@@ -3,6 +3,8 @@
 //! 包含：
 //! - `credentials`: OAuth 凭据安全管理（系统凭据存储）
 //! - `crypto`: 账户导入导出加密（ChaCha20-Poly1305）
+//! - `recipients`: 按接收者公钥加密导出（age / X25519）
 
 pub mod credentials;
 pub mod crypto;
+pub mod recipients;
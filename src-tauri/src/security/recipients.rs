@@ -0,0 +1,162 @@
+//! 基于接收者公钥的导出加密（age / X25519）
+//!
+//! 与 `crypto` 模块的共享密码不同，这里按队友的 X25519 公钥（`age1...`）加密导出数据，
+//! 只有对应私钥的持有者才能解密，移交账户时无需再通过聊天传递密码。
+//!
+//! - 本机密钥对在首次使用时生成，私钥（`AGE-SECRET-KEY-1...`）保存在系统凭据存储中
+//! - 输出为 ASCII armor 格式的标准 age 文件，可用 `age` 命令行工具互通
+
+use age::armor::{ArmoredReader, ArmoredWriter, Format};
+use age::secrecy::ExposeSecret;
+use age::x25519;
+use std::io::{Read, Write};
+use std::str::FromStr;
+
+const KEYRING_SERVICE: &str = "antigravity-agent";
+const KEYRING_USERNAME: &str = "recipient_identity";
+
+/// age 文件头（二进制 / armor 两种形式）
+const AGE_BINARY_HEADER: &[u8] = b"age-encryption.org/v1";
+const AGE_ARMOR_HEADER: &[u8] = b"-----BEGIN AGE ENCRYPTED FILE-----";
+
+fn keyring_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_USERNAME)
+        .map_err(|e| format!("初始化系统凭据存储失败: {}", e))
+}
+
+/// 读取本机私钥，不存在时生成并保存到系统凭据存储
+fn load_or_create_identity() -> Result<x25519::Identity, String> {
+    let entry = keyring_entry()?;
+
+    match entry.get_password() {
+        Ok(raw) => x25519::Identity::from_str(raw.trim())
+            .map_err(|_| "系统凭据存储中的私钥已损坏".to_string()),
+        Err(keyring::Error::NoEntry) => {
+            let identity = x25519::Identity::generate();
+            entry
+                .set_password(identity.to_string().expose_secret())
+                .map_err(|e| format!("写入系统凭据存储失败: {}", e))?;
+            tracing::info!(target: "security::recipients", "已生成本机接收密钥对");
+            Ok(identity)
+        }
+        Err(e) => Err(format!("读取系统凭据存储失败: {}", e)),
+    }
+}
+
+/// 获取本机公钥（`age1...`），可分享给队友用于加密导出
+pub fn local_public_key() -> Result<String, String> {
+    Ok(load_or_create_identity()?.to_public().to_string())
+}
+
+/// 解析接收者公钥列表
+pub fn parse_recipients(recipients: &[String]) -> Result<Vec<x25519::Recipient>, String> {
+    if recipients.is_empty() {
+        return Err("接收者列表不能为空".to_string());
+    }
+
+    recipients
+        .iter()
+        .map(|r| {
+            x25519::Recipient::from_str(r.trim()).map_err(|e| format!("无效的公钥 {}: {}", r, e))
+        })
+        .collect()
+}
+
+/// 判断数据是否为 age 加密文件
+pub fn is_age_encrypted(data: &[u8]) -> bool {
+    let trimmed = data.trim_ascii_start();
+    trimmed.starts_with(AGE_BINARY_HEADER) || trimmed.starts_with(AGE_ARMOR_HEADER)
+}
+
+/// 加密给指定接收者，返回 armor 格式文本
+pub fn encrypt_for(recipients: &[x25519::Recipient], plaintext: &[u8]) -> Result<String, String> {
+    let encryptor =
+        age::Encryptor::with_recipients(recipients.iter().map(|r| r as &dyn age::Recipient))
+            .map_err(|e| format!("初始化加密器失败: {}", e))?;
+
+    let mut output = Vec::new();
+    let armor = ArmoredWriter::wrap_output(&mut output, Format::AsciiArmor)
+        .map_err(|e| format!("加密失败: {}", e))?;
+    let mut writer = encryptor
+        .wrap_output(armor)
+        .map_err(|e| format!("加密失败: {}", e))?;
+    writer
+        .write_all(plaintext)
+        .map_err(|e| format!("加密失败: {}", e))?;
+    writer
+        .finish()
+        .and_then(|armor| armor.finish())
+        .map_err(|e| format!("加密失败: {}", e))?;
+
+    String::from_utf8(output).map_err(|e| format!("加密失败: {}", e))
+}
+
+/// 使用指定私钥解密（支持 armor 和二进制格式）
+pub fn decrypt_with(identity: &x25519::Identity, ciphertext: &[u8]) -> Result<Vec<u8>, String> {
+    let decryptor = age::Decryptor::new(ArmoredReader::new(ciphertext))
+        .map_err(|e| format!("解析加密数据失败: {}", e))?;
+    if decryptor.is_scrypt() {
+        return Err("该文件使用密码加密，不是发给本机的".to_string());
+    }
+
+    let mut reader = decryptor
+        .decrypt(std::iter::once(identity as &dyn age::Identity))
+        .map_err(|_| "解密失败：该文件不是发给本机公钥的，或数据已损坏".to_string())?;
+
+    let mut plaintext = Vec::new();
+    reader
+        .read_to_end(&mut plaintext)
+        .map_err(|e| format!("解密失败: {}", e))?;
+    Ok(plaintext)
+}
+
+/// 加密给指定接收者；`include_self` 为 true 时同时加密给本机，便于导出者自己恢复
+pub fn encrypt_for_recipients(
+    plaintext: &[u8],
+    recipients: &[String],
+    include_self: bool,
+) -> Result<String, String> {
+    let mut parsed = parse_recipients(recipients)?;
+    if include_self {
+        parsed.push(load_or_create_identity()?.to_public());
+    }
+
+    encrypt_for(&parsed, plaintext)
+}
+
+/// 使用本机私钥解密发给本机的数据
+pub fn decrypt_for_self(ciphertext: &[u8]) -> Result<Vec<u8>, String> {
+    decrypt_with(&load_or_create_identity()?, ciphertext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_to_multiple_recipients() {
+        let alice = x25519::Identity::generate();
+        let bob = x25519::Identity::generate();
+        let eve = x25519::Identity::generate();
+        let plaintext = br#"{"email": "test@example.com"}"#;
+
+        let encrypted = encrypt_for(&[alice.to_public(), bob.to_public()], plaintext).unwrap();
+        assert!(is_age_encrypted(encrypted.as_bytes()));
+
+        assert_eq!(
+            decrypt_with(&alice, encrypted.as_bytes()).unwrap(),
+            plaintext
+        );
+        assert_eq!(decrypt_with(&bob, encrypted.as_bytes()).unwrap(), plaintext);
+        assert!(decrypt_with(&eve, encrypted.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_parse_recipients() {
+        let key = x25519::Identity::generate().to_public().to_string();
+        assert_eq!(parse_recipients(&[key]).unwrap().len(), 1);
+        assert!(parse_recipients(&[]).is_err());
+        assert!(parse_recipients(&["not-a-key".to_string()]).is_err());
+        assert!(!is_age_encrypted(b"{\"manifest\": {}}"));
+    }
+}
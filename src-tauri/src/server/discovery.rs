@@ -0,0 +1,436 @@
+//! 本地 HTTP 服务的端口选择与服务发现
+//!
+//! 端口优先级：环境变量 `ANTIGRAVITY_AGENT_PORT` > 设置中的 `server.port` > 默认 56789。
+//! 端口被占用且允许回退时，依次尝试其后的若干端口，最后交给系统分配空闲端口。
+//!
+//! 监听成功后在配置目录写入 `server.json`，VS Code 扩展和 CLI 通过它找到正在运行的实例：
+//!
+//! ```json
+//! {
+//!   "port": 56789,
+//!   "pid": 12345,
+//!   "url": "http://127.0.0.1:56789",
+//!   "socket": "/home/user/.antigravity-agent/agent.sock",
+//!   "tokenFingerprint": "sha256:3f0c...",
+//!   "version": "1.6.1",
+//!   "startedAt": "2025-01-01T00:00:00Z"
+//! }
+//! ```
+//!
+//! Unix 下默认还会监听配置目录中的 `agent.sock`（权限 0600），路由与 TCP 端口完全相同，
+//! 包括 `/ws`。同一台机器上的其他用户无法连接该套接字，CLI 和 VS Code 扩展应优先使用它。
+//!
+//! ```bash
+//! curl --unix-socket ~/.antigravity-agent/agent.sock http://localhost/api/v1/accounts
+//! ```
+//!
+//! 设置 `server.requireToken` 开启后，TCP 端口上的请求必须携带
+//! `Authorization: Bearer <server.token>`（Unix 域套接字和应用自身 webview 除外），
+//! 这样同一台机器上的其他用户只能看到端口，无法调用接口。
+//!
+//! 进程异常退出时该文件可能残留，读取方应先确认 `pid` 对应的进程仍然存在。
+//! 每个响应都带有 `X-Antigravity-Agent-Instance` 头（值同 `tokenFingerprint`），
+//! 客户端可据此确认连上的正是发现文件描述的实例，而不是恰好占用该端口的其他程序。
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::net::{Ipv4Addr, TcpListener};
+use std::path::{Path, PathBuf};
+
+use crate::app_settings::ServerSettings;
+
+/// 默认监听端口
+pub const DEFAULT_PORT: u16 = 56789;
+
+/// 覆盖监听端口的环境变量
+pub const PORT_ENV: &str = "ANTIGRAVITY_AGENT_PORT";
+
+/// 携带实例 token 指纹的响应头
+pub const INSTANCE_HEADER: &str = "x-antigravity-agent-instance";
+
+/// 回退时在首选端口之后尝试的端口数
+const FALLBACK_ATTEMPTS: u16 = 10;
+
+lazy_static::lazy_static! {
+    /// 当前实例实际监听的端口
+    static ref BOUND_PORT: RwLock<Option<u16>> = RwLock::new(None);
+    /// 当前实例的 token
+    static ref INSTANCE_TOKEN: RwLock<Option<String>> = RwLock::new(None);
+}
+
+/// 发现文件内容
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveryInfo {
+    pub port: u16,
+    pub pid: u32,
+    /// 服务根地址（不含 `/api`）
+    pub url: String,
+    /// Unix 域套接字路径，未启用时省略
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub socket: Option<String>,
+    /// 实例 token 的 SHA-256 指纹（token 本身只写入权限为 0600 的 `server.token`）
+    pub token_fingerprint: String,
+    pub version: String,
+    pub started_at: String,
+}
+
+/// 首选端口：环境变量优先，其次为设置
+fn preferred_port(settings: &ServerSettings) -> u16 {
+    if let Ok(value) = std::env::var(PORT_ENV) {
+        match value.trim().parse::<u16>() {
+            Ok(port) => return port,
+            Err(_) => tracing::warn!(
+                target: "server::discovery",
+                "环境变量 {} 的值无效: {}，使用设置中的端口",
+                PORT_ENV,
+                value
+            ),
+        }
+    }
+    settings.port
+}
+
+/// 按顺序尝试的端口列表，`0` 表示由系统分配
+fn candidate_ports(preferred: u16, fallback: bool) -> Vec<u16> {
+    let mut ports = vec![preferred];
+    if fallback && preferred != 0 {
+        ports.extend((1..=FALLBACK_ATTEMPTS).filter_map(|offset| preferred.checked_add(offset)));
+        ports.push(0);
+    }
+    ports
+}
+
+/// 按设置绑定 127.0.0.1 上的端口
+pub fn bind(settings: &ServerSettings) -> Result<TcpListener, String> {
+    let preferred = preferred_port(settings);
+    let mut last_error = None;
+
+    for port in candidate_ports(preferred, settings.port_fallback) {
+        match TcpListener::bind((Ipv4Addr::LOCALHOST, port)) {
+            Ok(listener) => {
+                if port != preferred {
+                    tracing::warn!(
+                        target: "server::discovery",
+                        "端口 {} 已被占用，改用端口 {}",
+                        preferred,
+                        listener.local_addr().map(|a| a.port()).unwrap_or(port)
+                    );
+                }
+                return Ok(listener);
+            }
+            Err(e) => {
+                tracing::debug!(target: "server::discovery", "绑定端口 {} 失败: {}", port, e);
+                last_error = Some(e);
+            }
+        }
+    }
+
+    Err(format!(
+        "无法绑定端口 {}: {}",
+        preferred,
+        last_error.map(|e| e.to_string()).unwrap_or_default()
+    ))
+}
+
+/// 绑定 Unix 域套接字，权限为 0600
+///
+/// 先绑定到临时路径并修改权限，再重命名到目标路径，其他用户没有机会在权限收紧前连接。
+/// 目标路径上的套接字若仍有进程在监听则报错，否则视为上次异常退出的残留并替换。
+#[cfg(unix)]
+pub fn bind_unix(path: &Path) -> Result<std::os::unix::net::UnixListener, String> {
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    if path.exists() && UnixStream::connect(path).is_ok() {
+        return Err(format!("{} 已被其他实例占用", path.display()));
+    }
+
+    let tmp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+    let _ = fs::remove_file(&tmp_path);
+    let listener = UnixListener::bind(&tmp_path)
+        .map_err(|e| format!("绑定 {} 失败: {}", tmp_path.display(), e))?;
+
+    let result = fs::set_permissions(&tmp_path, fs::Permissions::from_mode(0o600))
+        .and_then(|_| fs::rename(&tmp_path, path));
+    if let Err(e) = result {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(format!("绑定 {} 失败: {}", path.display(), e));
+    }
+
+    Ok(listener)
+}
+
+/// 当前实例实际监听的端口，服务未启动时为 `None`
+pub fn bound_port() -> Option<u16> {
+    *BOUND_PORT.read()
+}
+
+/// 记录实际端口，生成实例 token 并写入发现文件
+pub fn publish(port: u16, socket: Option<&Path>) -> Result<DiscoveryInfo, String> {
+    *BOUND_PORT.write() = Some(port);
+
+    let token = generate_token();
+    *INSTANCE_TOKEN.write() = Some(token.clone());
+    write_private(
+        &crate::directories::get_server_token_file(),
+        token.as_bytes(),
+    )?;
+
+    let info = DiscoveryInfo {
+        port,
+        pid: std::process::id(),
+        url: format!("http://127.0.0.1:{}", port),
+        socket: socket.map(|path| path.to_string_lossy().to_string()),
+        token_fingerprint: fingerprint(&token),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        started_at: chrono::Utc::now().to_rfc3339(),
+    };
+    write_discovery(&crate::directories::get_server_discovery_file(), &info)?;
+
+    tracing::info!(
+        target: "server::discovery",
+        port = port,
+        pid = info.pid,
+        "已写入服务发现文件"
+    );
+    Ok(info)
+}
+
+/// 服务停止时删除套接字和发现文件（发现文件仅在仍属于当前进程时删除）
+pub fn withdraw(socket: Option<&Path>) {
+    *BOUND_PORT.write() = None;
+
+    if let Some(socket) = socket {
+        let _ = fs::remove_file(socket);
+    }
+
+    let path = crate::directories::get_server_discovery_file();
+    if read_discovery(&path).is_some_and(|info| info.pid == std::process::id()) {
+        if let Err(e) = fs::remove_file(&path) {
+            tracing::warn!(target: "server::discovery", "删除服务发现文件失败: {}", e);
+        }
+    }
+}
+
+/// 校验请求携带的实例 token
+pub fn verify_token(candidate: &str) -> bool {
+    let guard = INSTANCE_TOKEN.read();
+    let Some(token) = guard.as_deref() else {
+        return false;
+    };
+    // 比较摘要，耗时与匹配的前缀长度无关
+    Sha256::digest(candidate.as_bytes()) == Sha256::digest(token.as_bytes())
+}
+
+/// 读取正在运行的实例写入的 token（用于向其转发命令）
+pub fn read_token() -> Option<String> {
+    let token = fs::read_to_string(crate::directories::get_server_token_file()).ok()?;
+    let token = token.trim();
+    (!token.is_empty()).then(|| token.to_string())
+}
+
+/// 读取发现文件，不存在或格式错误时返回 `None`
+pub fn read_discovery(path: &Path) -> Option<DiscoveryInfo> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_discovery(path: &Path, info: &DiscoveryInfo) -> Result<(), String> {
+    let json =
+        serde_json::to_string_pretty(info).map_err(|e| format!("序列化服务发现信息失败: {}", e))?;
+    write_private(path, json.as_bytes())
+}
+
+fn generate_token() -> String {
+    use rand::RngCore;
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn fingerprint(token: &str) -> String {
+    let digest = hex::encode(Sha256::digest(token.as_bytes()));
+    format!("sha256:{}", &digest[..16])
+}
+
+/// 临时文件路径：`<文件名>.<pid>.tmp`，不同目标文件、不同进程互不干扰
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.tmp", std::process::id()));
+    path.with_file_name(name)
+}
+
+/// 先写临时文件再重命名，读取方不会看到写了一半的内容；Unix 下权限为 0600
+fn write_private(path: &Path, contents: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+
+    // 残留的临时文件权限未知，删除后重新创建
+    let tmp_path = temp_path(path);
+    match fs::remove_file(&tmp_path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            return Err(format!("删除 {} 失败: {}", tmp_path.display(), e));
+        }
+        _ => {}
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let write_err = |e: std::io::Error| format!("写入 {} 失败: {}", tmp_path.display(), e);
+    let mut file = options.open(&tmp_path).map_err(write_err)?;
+    // mode 受 umask 影响，写入内容前显式设置
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))
+            .map_err(write_err)?;
+    }
+    file.write_all(contents)
+        .and_then(|_| file.sync_all())
+        .map_err(write_err)?;
+    drop(file);
+
+    fs::rename(&tmp_path, path).map_err(|e| format!("写入 {} 失败: {}", path.display(), e))
+}
+
+/// 本地服务根地址，供主窗口前端在启动时获取实际端口
+#[tauri::command]
+pub fn get_server_url() -> Result<String, String> {
+    bound_port()
+        .map(|port| format!("http://127.0.0.1:{}", port))
+        .ok_or_else(|| "本地服务尚未启动".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidate_ports() {
+        assert_eq!(candidate_ports(56789, false), vec![56789]);
+
+        let ports = candidate_ports(56789, true);
+        assert_eq!(ports.first(), Some(&56789));
+        assert_eq!(ports[1], 56790);
+        assert_eq!(ports.last(), Some(&0));
+        assert_eq!(ports.len(), FALLBACK_ATTEMPTS as usize + 2);
+
+        // 不越过端口上限
+        assert_eq!(candidate_ports(u16::MAX, true), vec![u16::MAX, 0]);
+    }
+
+    #[test]
+    fn test_bind_falls_back_when_port_taken() {
+        let taken = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = taken.local_addr().unwrap().port();
+
+        let strict = ServerSettings {
+            port,
+            port_fallback: false,
+            unix_socket: false,
+            ..ServerSettings::default()
+        };
+        assert!(bind(&strict).is_err());
+
+        let listener = bind(&ServerSettings {
+            port,
+            port_fallback: true,
+            unix_socket: false,
+            ..ServerSettings::default()
+        })
+        .unwrap();
+        assert_ne!(listener.local_addr().unwrap().port(), port);
+    }
+
+    #[test]
+    fn test_discovery_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("server.json");
+        let info = DiscoveryInfo {
+            port: 56790,
+            pid: 42,
+            url: "http://127.0.0.1:56790".to_string(),
+            socket: None,
+            token_fingerprint: fingerprint("token"),
+            version: "1.0.0".to_string(),
+            started_at: "2025-01-01T00:00:00+00:00".to_string(),
+        };
+
+        write_discovery(&path, &info).unwrap();
+        assert_eq!(read_discovery(&path), Some(info));
+        assert!(!temp_path(&path).exists());
+
+        let raw: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(raw["tokenFingerprint"]
+            .as_str()
+            .unwrap()
+            .starts_with("sha256:"));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_write_private_uses_per_file_temp() {
+        let dir = tempfile::tempdir().unwrap();
+        let json = dir.path().join("server.json");
+        let token = dir.path().join("server.token");
+        assert_ne!(temp_path(&json), temp_path(&token));
+
+        // 残留的临时文件被替换，权限不会沿用
+        fs::write(temp_path(&token), b"stale").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(temp_path(&token), fs::Permissions::from_mode(0o644)).unwrap();
+        }
+
+        write_private(&json, b"{}").unwrap();
+        write_private(&token, b"secret").unwrap();
+        assert_eq!(fs::read(&json).unwrap(), b"{}");
+        assert_eq!(fs::read(&token).unwrap(), b"secret");
+        assert!(!temp_path(&token).exists());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&token).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bind_unix_socket() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("agent.sock");
+
+        // 上次异常退出残留的文件会被替换
+        fs::write(&path, b"").unwrap();
+        let listener = bind_unix(&path).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        // 仍在监听时拒绝抢占
+        assert!(bind_unix(&path).is_err());
+        drop(listener);
+    }
+}
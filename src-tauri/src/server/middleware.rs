@@ -5,17 +5,24 @@ use std::rc::Rc;
 use std::task::{Context, Poll}; // Re-add Pin which is needed for the type annotation
 
 use actix_web::{
+    body::{EitherBody, MessageBody},
     dev::{self, Service, ServiceRequest, ServiceResponse, Transform},
     error::Error,
     error::PayloadError,
-    http::header,
+    http::header::{self, HeaderMap, HeaderName, HeaderValue},
+    middleware::Next,
     web::Bytes,
+    ResponseError,
 };
 use futures_util::future::LocalBoxFuture;
 use futures_util::stream::once;
 use futures_util::Stream; // Import Stream trait
 use serde_json::Value;
 
+/// 请求体中包含账户文件原文的旧接口，键名（如 `jetskiStateSync.agentManagerInitState`）
+/// 必须原样保留，不做转换
+const RAW_BODY_PATHS: &[&str] = &["/api/import_accounts", "/api/restore_backup_files"];
+
 // Middleware Factory
 pub struct CamelCaseToSnakeCase;
 
@@ -67,7 +74,10 @@ where
                 .map(|v| v.to_str().unwrap_or("").contains("application/json"))
                 .unwrap_or(false);
 
-            if is_json {
+            // v1 接口的请求体本身就是 camelCase DTO，不做转换
+            let keep_keys =
+                req.path().starts_with(super::v1::PREFIX) || RAW_BODY_PATHS.contains(&req.path());
+            if is_json && !keep_keys {
                 // Read body
                 let body = req.extract::<Bytes>().await?;
 
@@ -109,6 +119,74 @@ where
     }
 }
 
+/// 弃用标记响应头（RFC 9745）
+pub const DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+
+/// 旧接口的响应附带 `Deprecation: true` 和 `Link: <v1 路径>; rel="successor-version"`
+pub async fn mark_deprecated(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let successor = super::v1::successor_of(req.path());
+    let mut res = next.call(req).await?;
+
+    if let Some(successor) = successor {
+        let headers = res.headers_mut();
+        headers.insert(DEPRECATION, HeaderValue::from_static("true"));
+        if let Ok(link) =
+            HeaderValue::from_str(&format!("<{}>; rel=\"successor-version\"", successor))
+        {
+            headers.insert(header::LINK, link);
+        }
+    }
+    Ok(res)
+}
+
+/// 应用自身 webview 的 `Origin`（Tauri 主窗口、VS Code 扩展面板）
+const WEBVIEW_ORIGINS: &[&str] = &[
+    "tauri://localhost",
+    "http://tauri.localhost",
+    "https://tauri.localhost",
+    "vscode-webview://",
+];
+
+/// 请求头中的 `Authorization: Bearer <token>`，没有时为空字符串
+pub fn bearer_token(headers: &HeaderMap) -> &str {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default()
+}
+
+fn is_webview_origin(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ORIGIN)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|origin| WEBVIEW_ORIGINS.iter().any(|o| origin.starts_with(o)))
+}
+
+/// 启用 `server.requireToken` 时，TCP 连接上未携带实例 token 的请求返回 401
+///
+/// Unix 域套接字连接没有对端地址，访问已由文件权限限制，直接放行；
+/// 应用自身 webview 的请求按 `Origin` 放行。
+pub async fn require_instance_token(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let allowed = req.peer_addr().is_none()
+        || is_webview_origin(req.headers())
+        || super::discovery::verify_token(bearer_token(req.headers()));
+    if !allowed {
+        let res = super::v1::error::ApiError::unauthorized("缺少或携带了错误的实例 token")
+            .error_response();
+        return Ok(req.into_response(res).map_into_right_body());
+    }
+    next.call(req)
+        .await
+        .map(ServiceResponse::map_into_left_body)
+}
+
 /// Recursively transform keys from camelCase to snake_case
 fn transform_keys(value: &mut Value) {
     match value {
@@ -148,3 +226,54 @@ fn camel_to_snake(s: &str) -> String {
     }
     new_s
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::{call_service, init_service, TestRequest};
+    use actix_web::{http::StatusCode, web, App, HttpResponse};
+
+    #[actix_web::test]
+    async fn test_require_instance_token() {
+        let app = init_service(
+            App::new()
+                .wrap(actix_web::middleware::from_fn(require_instance_token))
+                .route("/ping", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let tcp_peer = "127.0.0.1:50000".parse().unwrap();
+
+        // TCP 连接未携带 token
+        let req = TestRequest::get()
+            .uri("/ping")
+            .peer_addr(tcp_peer)
+            .insert_header((header::AUTHORIZATION, "Bearer wrong"))
+            .to_request();
+        assert_eq!(
+            call_service(&app, req).await.status(),
+            StatusCode::UNAUTHORIZED
+        );
+
+        // 应用自身的 webview
+        let req = TestRequest::get()
+            .uri("/ping")
+            .peer_addr(tcp_peer)
+            .insert_header((header::ORIGIN, "tauri://localhost"))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+
+        let req = TestRequest::get()
+            .uri("/ping")
+            .peer_addr(tcp_peer)
+            .insert_header((header::ORIGIN, "http://localhost:3000"))
+            .to_request();
+        assert_eq!(
+            call_service(&app, req).await.status(),
+            StatusCode::UNAUTHORIZED
+        );
+
+        // Unix 域套接字连接没有对端地址
+        let req = TestRequest::get().uri("/ping").to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+    }
+}
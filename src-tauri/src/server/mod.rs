@@ -1,11 +1,15 @@
 use crate::AppState;
 use actix_cors::Cors;
 use actix_web::http::header;
-use actix_web::{get, post, web, App, HttpResponse, HttpServer, Responder};
+use actix_web::{get, patch, post, web, App, HttpResponse, HttpServer, Responder};
 use serde_json::json;
+use tauri::Manager;
 
 
+pub mod discovery;
 mod middleware;
+mod sse;
+pub mod v1;
 pub mod websocket;
 
 // =============================================================================
@@ -85,6 +89,22 @@ async fn clear_data() -> impl Responder {
     }
 }
 
+#[post("/api/undo_last_operation")]
+async fn undo_last_operation() -> impl Responder {
+    match crate::services::account::undo_last_operation().await {
+        Ok(msg) => HttpResponse::Ok().json(json!({ "success": true, "message": msg })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e })),
+    }
+}
+
+#[get("/api/list_state_snapshots")]
+async fn list_snapshots() -> impl Responder {
+    match crate::services::account::list_snapshots().await {
+        Ok(snapshots) => HttpResponse::Ok().json(snapshots),
+        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e })),
+    }
+}
+
 #[post("/api/sign_in_new_antigravity_account")]
 async fn sign_in_new() -> impl Responder {
     match crate::services::account::sign_in_new().await {
@@ -114,6 +134,29 @@ async fn get_metrics(
     }
 }
 
+#[derive(serde::Deserialize)]
+struct AllMetricsQuery {
+    #[serde(default)]
+    force: bool,
+}
+
+/// 批量获取所有账户指标（带缓存），单个账户失败时在对应条目中返回错误
+#[get("/api/get_all_account_metrics")]
+async fn get_all_metrics(
+    data: web::Data<AppState>,
+    query: web::Query<AllMetricsQuery>,
+) -> impl Responder {
+    let config_dir = {
+        let state = data.inner.lock();
+        state.config_dir.clone()
+    };
+
+    match crate::services::metrics_cache::get_all(&config_dir, query.force).await {
+        Ok(entries) => HttpResponse::Ok().json(entries),
+        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e }))
+    }
+}
+
 #[derive(serde::Deserialize)]
 struct TriggerRefreshRequest {
     email: String,
@@ -135,6 +178,34 @@ async fn refresh_quota(
     }
 }
 
+#[derive(serde::Deserialize)]
+struct AccountHealthQuery {
+    #[serde(default)]
+    refresh: bool,
+}
+
+/// 返回最近一次的 token 健康检查结果；`refresh=true` 或尚无记录时重新检查
+#[get("/api/account_health")]
+async fn account_health(
+    data: web::Data<AppState>,
+    query: web::Query<AccountHealthQuery>,
+) -> impl Responder {
+    let config_dir = {
+        let state = data.inner.lock();
+        state.config_dir.clone()
+    };
+
+    let recorded = crate::services::account_health::load_recorded(&config_dir);
+    if !query.refresh && !recorded.is_empty() {
+        return HttpResponse::Ok().json(recorded);
+    }
+
+    match crate::services::account_health::check_all(&config_dir).await {
+        Ok(results) => HttpResponse::Ok().json(results),
+        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e }))
+    }
+}
+
 // =============================================================================
 // Backup Service Endpoints
 // =============================================================================
@@ -152,22 +223,188 @@ async fn collect_backups(data: web::Data<AppState>) -> impl Responder {
     }
 }
 
+/// 兼容两种请求体：裸数组，或前端发送的 `{ accountFileData: [...] }`
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum RestoreBackupsRequest {
+    Files(Vec<crate::services::backup::AccountExportedData>),
+    Wrapped {
+        #[serde(rename = "accountFileData")]
+        account_file_data: Vec<crate::services::backup::AccountExportedData>,
+    },
+}
+
 #[post("/api/restore_backup_files")]
 async fn restore_backups(
     data: web::Data<AppState>,
-    req: web::Json<Vec<crate::services::backup::AccountExportedData>>,
+    req: web::Json<RestoreBackupsRequest>,
 ) -> impl Responder {
     let config_dir = {
         let state = data.inner.lock();
         state.config_dir.clone()
     };
 
-    match crate::services::backup::restore_files(&config_dir, req.into_inner()).await {
+    let files = match req.into_inner() {
+        RestoreBackupsRequest::Files(files) => files,
+        RestoreBackupsRequest::Wrapped { account_file_data } => account_file_data,
+    };
+
+    match crate::services::backup::restore_files(&config_dir, files).await {
         Ok(res) => HttpResponse::Ok().json(res),
         Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e }))
     }
 }
 
+/// 不经过 camelCase -> snake_case 转换（见 `middleware::RAW_BODY_PATHS`），字段名为 camelCase
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportAccountsRequest {
+    accounts: Vec<crate::services::backup::AccountExportedData>,
+    #[serde(default)]
+    strategy: crate::services::backup::ConflictStrategy,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[post("/api/import_accounts")]
+async fn import_accounts(
+    data: web::Data<AppState>,
+    req: web::Json<ImportAccountsRequest>,
+) -> impl Responder {
+    let config_dir = {
+        let state = data.inner.lock();
+        state.config_dir.clone()
+    };
+
+    let req = req.into_inner();
+    match crate::services::backup::import_accounts(&config_dir, req.accounts, req.strategy, req.dry_run).await {
+        Ok(report) => HttpResponse::Ok().json(report),
+        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e }))
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ExportBundleRequest {
+    /// 文件名，位于配置目录下的 `bundles/`
+    file_name: String,
+    #[serde(default)]
+    include_settings: bool,
+    /// 接收者公钥（age1...）
+    #[serde(default)]
+    recipients: Vec<String>,
+    /// 用密码加密（与 recipients 二选一）
+    #[serde(default)]
+    password: Option<String>,
+    /// 既没有接收者也没有密码时允许以明文导出 token
+    #[serde(default)]
+    allow_plaintext: bool,
+}
+
+#[post("/api/export_bundle")]
+async fn export_bundle(
+    data: web::Data<AppState>,
+    app: web::Data<tauri::AppHandle>,
+    req: web::Json<ExportBundleRequest>,
+) -> impl Responder {
+    let config_dir = {
+        let state = data.inner.lock();
+        state.config_dir.clone()
+    };
+
+    let settings = if req.include_settings {
+        match crate::services::settings::export_settings(&app) {
+            Ok(settings) => Some(settings),
+            Err(e) => return HttpResponse::InternalServerError().json(json!({ "error": e })),
+        }
+    } else {
+        None
+    };
+
+    let path = match crate::services::bundle::bundle_path(&req.file_name) {
+        Ok(path) => path,
+        Err(e) => return HttpResponse::BadRequest().json(json!({ "error": e })),
+    };
+    if req.recipients.is_empty() && req.password.is_none() && !req.allow_plaintext {
+        return HttpResponse::BadRequest().json(
+            json!({ "error": "未指定接收者公钥或密码时需设置 allowPlaintext 才能导出明文 token" }),
+        );
+    }
+
+    match crate::services::bundle::export_to_file(
+        &config_dir,
+        &path,
+        settings,
+        &req.recipients,
+        req.password.as_deref(),
+        req.allow_plaintext,
+    )
+    .await {
+        Ok(manifest) => HttpResponse::Ok().json(manifest),
+        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e }))
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ImportBundleRequest {
+    /// 文件名，位于配置目录下的 `bundles/`
+    file_name: String,
+    #[serde(default)]
+    strategy: crate::services::backup::ConflictStrategy,
+    #[serde(default)]
+    dry_run: bool,
+    #[serde(default)]
+    apply_settings: bool,
+    /// 密码加密的 bundle 所需的密码
+    #[serde(default)]
+    password: Option<String>,
+}
+
+#[post("/api/import_bundle")]
+async fn import_bundle(
+    data: web::Data<AppState>,
+    app: web::Data<tauri::AppHandle>,
+    req: web::Json<ImportBundleRequest>,
+) -> impl Responder {
+    let config_dir = {
+        let state = data.inner.lock();
+        state.config_dir.clone()
+    };
+
+    let path = match crate::services::bundle::bundle_path(&req.file_name) {
+        Ok(path) => path,
+        Err(e) => return HttpResponse::BadRequest().json(json!({ "error": e })),
+    };
+
+    let result = match crate::services::bundle::import_from_file(
+        &config_dir,
+        &path,
+        req.strategy,
+        req.dry_run,
+        req.password.as_deref(),
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(e) => return HttpResponse::InternalServerError().json(json!({ "error": e })),
+    };
+
+    let mut settings_applied = false;
+    if req.apply_settings && !req.dry_run {
+        if let Some(settings) = result.settings.clone() {
+            if let Err(e) = crate::services::settings::apply_imported_settings(&app, settings).await {
+                return HttpResponse::InternalServerError().json(json!({ "error": e }));
+            }
+            settings_applied = true;
+        }
+    }
+
+    HttpResponse::Ok().json(json!({
+        "manifest": result.manifest,
+        "report": result.report,
+        "settingsApplied": settings_applied
+    }))
+}
+
 #[derive(serde::Deserialize)]
 struct DeleteBackupRequest {
     name: String,
@@ -206,11 +443,6 @@ async fn clear_backups(data: web::Data<AppState>) -> impl Responder {
 // Settings Service Endpoints
 // =============================================================================
 
-#[derive(serde::Deserialize)]
-struct BoolStateRequest {
-    enabled: bool,
-}
-
 #[get("/api/get_all_settings")]
 async fn get_all_settings(app: web::Data<tauri::AppHandle>) -> impl Responder {
     match crate::services::settings::get_all(&app).await {
@@ -219,47 +451,24 @@ async fn get_all_settings(app: web::Data<tauri::AppHandle>) -> impl Responder {
     }
 }
 
-#[post("/api/save_system_tray_state")]
-async fn save_tray_state(
+/// 修改任意设置；设置已保存但未能立即生效时返回 200，并在 `warning` 中说明原因
+#[patch("/api/settings")]
+async fn patch_settings(
     app: web::Data<tauri::AppHandle>,
-    req: web::Json<BoolStateRequest>,
+    req: web::Json<serde_json::Value>,
 ) -> impl Responder {
-    match crate::services::settings::save_system_tray_state(&app, req.enabled).await {
-        Ok(val) => HttpResponse::Ok().json(json!({ "success": true, "value": val })),
-        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e }))
-    }
-}
+    use crate::services::settings::PatchError;
 
-#[post("/api/save_silent_start_state")]
-async fn save_silent_start(
-    app: web::Data<tauri::AppHandle>,
-    req: web::Json<BoolStateRequest>,
-) -> impl Responder {
-    match crate::services::settings::save_silent_start_state(&app, req.enabled).await {
-        Ok(val) => HttpResponse::Ok().json(json!({ "success": true, "value": val })),
-        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e }))
-    }
-}
-
-#[post("/api/save_private_mode_state")]
-async fn save_private_mode(
-    app: web::Data<tauri::AppHandle>,
-    req: web::Json<BoolStateRequest>,
-) -> impl Responder {
-    match crate::services::settings::save_private_mode_state(&app, req.enabled).await {
-        Ok(val) => HttpResponse::Ok().json(json!({ "success": true, "value": val })),
-        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e }))
-    }
-}
-
-#[post("/api/save_debug_mode_state")]
-async fn save_debug_mode(
-    app: web::Data<tauri::AppHandle>,
-    req: web::Json<BoolStateRequest>,
-) -> impl Responder {
-    match crate::services::settings::save_debug_mode_state(&app, req.enabled).await {
-        Ok(val) => HttpResponse::Ok().json(json!({ "success": true, "value": val })),
-        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e }))
+    match crate::services::settings::patch(&app, req.into_inner()) {
+        Ok(updated) => {
+            let mut body = json!(updated.settings);
+            if let Some(warning) = updated.warning {
+                body["warning"] = json!(warning);
+            }
+            HttpResponse::Ok().json(body)
+        }
+        Err(PatchError::Invalid(e)) => HttpResponse::BadRequest().json(json!({ "error": e })),
+        Err(PatchError::Failed(e)) => HttpResponse::InternalServerError().json(json!({ "error": e }))
     }
 }
 
@@ -271,22 +480,6 @@ async fn get_language(app: web::Data<tauri::AppHandle>) -> impl Responder {
     }
 }
 
-#[derive(serde::Deserialize)]
-struct SetLanguageRequest {
-    language: String,
-}
-
-#[post("/api/set_language")]
-async fn set_language(
-    app: web::Data<tauri::AppHandle>,
-    req: web::Json<SetLanguageRequest>,
-) -> impl Responder {
-    match crate::services::settings::set_language(&app, req.language.clone()).await {
-        Ok(_) => HttpResponse::Ok().json(json!({ "success": true })),
-        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e }))
-    }
-}
-
 // =============================================================================
 // Platform Service Endpoints
 // =============================================================================
@@ -358,13 +551,84 @@ async fn get_paths() -> impl Responder {
 
 #[derive(serde::Deserialize)]
 struct CryptoRequest {
+    #[serde(alias = "json_data", alias = "encrypted_data")]
     data: String, // json_data or encrypted_data
     password: String,
+    /// 密钥文件路径（第二因子，可选）
+    #[serde(default)]
+    keyfile_path: Option<String>,
+    /// 明文存储但参与认证的关联数据（加密 / 重新加密时使用）
+    #[serde(default)]
+    associated_data: Option<String>,
+    /// 自定义 Argon2 参数（加密 / 重新加密时使用）
+    #[serde(default)]
+    kdf: Option<crate::security::crypto::KdfParams>,
+}
+
+impl CryptoRequest {
+    fn read_keyfile(&self) -> Result<Option<Vec<u8>>, String> {
+        self.keyfile_path
+            .as_deref()
+            .map(|path| std::fs::read(path).map_err(|e| format!("读取密钥文件失败: {}", e)))
+            .transpose()
+    }
+}
+
+#[get("/api/get_recipient_public_key")]
+async fn get_recipient_public_key() -> impl Responder {
+    match crate::security::recipients::local_public_key() {
+        Ok(key) => HttpResponse::Ok().json(json!({ "publicKey": key })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e }))
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct RecipientEncryptRequest {
+    data: String,
+    recipients: Vec<String>,
+    #[serde(default = "default_true")]
+    include_self: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[post("/api/encrypt_for_recipients")]
+async fn encrypt_for_recipients(req: web::Json<RecipientEncryptRequest>) -> impl Responder {
+    match crate::security::recipients::encrypt_for_recipients(req.data.as_bytes(), &req.recipients, req.include_self) {
+        Ok(res) => HttpResponse::Ok().json(json!({ "result": res })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e }))
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct RecipientDecryptRequest {
+    data: String,
+}
+
+#[post("/api/decrypt_with_local_key")]
+async fn decrypt_with_local_key(req: web::Json<RecipientDecryptRequest>) -> impl Responder {
+    let result = crate::security::recipients::decrypt_for_self(req.data.as_bytes())
+        .and_then(|plain| String::from_utf8(plain).map_err(|_| "解密后的数据不是有效的 UTF-8 文本".to_string()));
+    match result {
+        Ok(res) => HttpResponse::Ok().json(json!({ "result": res })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e }))
+    }
 }
 
 #[post("/api/encrypt_config_data")]
 async fn encrypt_data(req: web::Json<CryptoRequest>) -> impl Responder {
-    match crate::security::crypto::encrypt_config_data(req.data.clone(), req.password.clone()).await {
+    let result = req.read_keyfile().and_then(|keyfile| {
+        let options = crate::security::crypto::EncryptOptions {
+            kdf: req.kdf.unwrap_or_default(),
+            associated_data: req.associated_data.as_deref().unwrap_or_default().as_bytes(),
+            keyfile: keyfile.as_deref(),
+        };
+        crate::security::crypto::encrypt_with_options(req.data.as_bytes(), &req.password, &options)
+    });
+
+    match result {
         Ok(res) => HttpResponse::Ok().json(json!({ "result": res })),
         Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e }))
     }
@@ -372,8 +636,37 @@ async fn encrypt_data(req: web::Json<CryptoRequest>) -> impl Responder {
 
 #[post("/api/decrypt_config_data")]
 async fn decrypt_data(req: web::Json<CryptoRequest>) -> impl Responder {
-    match crate::security::crypto::decrypt_config_data(req.data.clone(), req.password.clone()).await {
-        Ok(res) => HttpResponse::Ok().json(json!({ "result": res })),
+    let result = req.read_keyfile().and_then(|keyfile| {
+        crate::security::crypto::decrypt_with_options(&req.data, &req.password, keyfile.as_deref())
+    });
+
+    match result {
+        Ok(decrypted) => HttpResponse::Ok().json(json!({
+            "result": decrypted.plaintext,
+            "format": decrypted.format,
+            "associatedData": String::from_utf8_lossy(&decrypted.associated_data),
+            "needsReencrypt": decrypted.format.needs_reencrypt()
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e }))
+    }
+}
+
+#[post("/api/reencrypt_config_data")]
+async fn reencrypt_data(req: web::Json<CryptoRequest>) -> impl Responder {
+    let result = req.read_keyfile().and_then(|keyfile| {
+        let options = crate::security::crypto::EncryptOptions {
+            kdf: req.kdf.unwrap_or_default(),
+            associated_data: req.associated_data.as_deref().unwrap_or_default().as_bytes(),
+            keyfile: keyfile.as_deref(),
+        };
+        crate::security::crypto::reencrypt_with_options(&req.data, &req.password, &options)
+    });
+
+    match result {
+        Ok((res, previous_format)) => HttpResponse::Ok().json(json!({
+            "result": res,
+            "previousFormat": previous_format
+        })),
         Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e }))
     }
 }
@@ -384,7 +677,6 @@ async fn decrypt_data(req: web::Json<CryptoRequest>) -> impl Responder {
 
 #[derive(serde::Deserialize)]
 struct UpdateTrayRequest {
-    accounts: Vec<String>,
     labels: Option<crate::system_tray::TrayMenuLabels>,
 }
 
@@ -393,7 +685,7 @@ async fn update_tray(
     app: web::Data<tauri::AppHandle>,
     req: web::Json<UpdateTrayRequest>,
 ) -> impl Responder {
-    match crate::services::system::tray::update_menu(&app, req.accounts.clone(), req.labels.clone()).await {
+    match crate::services::system::tray::update_menu(&app, req.labels.clone()).await {
         Ok(msg) => HttpResponse::Ok().json(json!({ "success": true, "message": msg })),
         Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e }))
     }
@@ -496,7 +788,62 @@ async fn install_ext(req: web::Json<LaunchRequest>) -> impl Responder {
 // =============================================================================
 
 /// 启动 HTTP 服务器
+///
+/// 端口（以及 Unix 下的 `agent.sock`）在当前线程同步绑定，返回时 [`discovery::bound_port`] 已可用；
+/// 端口的选择规则、套接字权限和发现文件格式见 [`discovery`]。
 pub fn init(app_handle: tauri::AppHandle, state: AppState) {
+    let server_settings = app_handle
+        .state::<crate::app_settings::AppSettingsManager>()
+        .get_settings()
+        .server;
+
+    let listener = match discovery::bind(&server_settings) {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::error!("Failed to bind HTTP server: {}", e);
+            return;
+        }
+    };
+    let port = match listener.local_addr() {
+        Ok(addr) => addr.port(),
+        Err(e) => {
+            tracing::error!("Failed to read HTTP server address: {}", e);
+            return;
+        }
+    };
+    // Unix 域套接字与 TCP 端口共用同一个 App，绑定失败时只保留 TCP
+    #[cfg(unix)]
+    let unix_listener = if server_settings.unix_socket {
+        let path = crate::directories::get_server_socket_file();
+        match discovery::bind_unix(&path) {
+            Ok(listener) => Some((listener, path)),
+            Err(e) => {
+                tracing::warn!("Failed to bind Unix socket: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    #[cfg(unix)]
+    let socket_path = unix_listener.as_ref().map(|(_, path)| path.clone());
+    #[cfg(not(unix))]
+    let socket_path: Option<std::path::PathBuf> = None;
+
+    let require_token = server_settings.require_token;
+    if require_token {
+        tracing::info!("TCP 端口上的请求需携带实例 token");
+    }
+
+    let instance = match discovery::publish(port, socket_path.as_deref()) {
+        Ok(info) => info.token_fingerprint,
+        Err(e) => {
+            // 再次启动的进程找不到本实例，会因无法转发命令而报错退出
+            tracing::error!("Failed to write server discovery file: {}", e);
+            String::new()
+        }
+    };
+
     std::thread::spawn(move || {
         let sys = actix_web::rt::System::new();
 
@@ -510,16 +857,35 @@ pub fn init(app_handle: tauri::AppHandle, state: AppState) {
                             || origin.starts_with("http://127.0.0.1")
                             || origin.starts_with("http://localhost")
                     })
-                    .allowed_methods(vec!["GET", "POST"])
+                    .allowed_methods(vec!["GET", "POST", "PUT", "PATCH", "DELETE"])
                     .allowed_headers(vec![header::CONTENT_TYPE, header::AUTHORIZATION])
+                    .expose_headers(vec![
+                        header::LINK,
+                        middleware::DEPRECATION,
+                        header::HeaderName::from_static(discovery::INSTANCE_HEADER),
+                    ])
                     .max_age(3600);
 
                 App::new()
                     .wrap(cors)
-                    // 使用中间件统一处理 camelCase -> snake_case 参数名
+                    // 使用中间件统一处理 camelCase -> snake_case 参数名（不含 v1 接口）
                     .wrap(middleware::CamelCaseToSnakeCase)
+                    // 旧接口响应附带弃用标记
+                    .wrap(actix_web::middleware::from_fn(middleware::mark_deprecated))
+                    // TCP 端口上的请求校验实例 token（server.requireToken）
+                    .wrap(actix_web::middleware::Condition::new(
+                        require_token,
+                        actix_web::middleware::from_fn(middleware::require_instance_token),
+                    ))
+                    // 实例标识，客户端据此确认连上的是发现文件描述的实例
+                    .wrap(
+                        actix_web::middleware::DefaultHeaders::new()
+                            .add((discovery::INSTANCE_HEADER, instance.clone())),
+                    )
                     .app_data(web::Data::new(state.clone()))
                     .app_data(web::Data::new(app_handle.clone()))
+                    // v1 接口
+                    .configure(v1::configure)
                     // Account Service
                     .service(status)
                     .service(get_accounts)
@@ -528,22 +894,25 @@ pub fn init(app_handle: tauri::AppHandle, state: AppState) {
                     .service(restore_account)
                     .service(switch_account)
                     .service(clear_data)
+                    .service(undo_last_operation)
+                    .service(list_snapshots)
                     .service(sign_in_new)
                     .service(get_metrics)
+                    .service(get_all_metrics)
                     .service(refresh_quota)
+                    .service(account_health)
                     // Backup Service
                     .service(collect_backups)
                     .service(restore_backups)
+                    .service(import_accounts)
+                    .service(export_bundle)
+                    .service(import_bundle)
                     .service(delete_backup)
                     .service(clear_backups)
                     // Settings Service
                     .service(get_all_settings)
-                    .service(save_tray_state)
-                    .service(save_silent_start)
-                    .service(save_private_mode)
-                    .service(save_debug_mode)
+                    .service(patch_settings)
                     .service(get_language)
-                    .service(set_language)
                     // Platform Service
                     .service(get_platform_info)
                     .service(find_installations)
@@ -555,6 +924,10 @@ pub fn init(app_handle: tauri::AppHandle, state: AppState) {
                     // Crypto Service
                     .service(encrypt_data)
                     .service(decrypt_data)
+                    .service(reencrypt_data)
+                    .service(get_recipient_public_key)
+                    .service(encrypt_for_recipients)
+                    .service(decrypt_with_local_key)
                     // System Service
                     .service(update_tray)
                     .service(minimize_tray)
@@ -567,22 +940,35 @@ pub fn init(app_handle: tauri::AppHandle, state: AppState) {
                     .service(get_log_dir)
                     .service(open_log)
                     .service(install_ext)
+                    // 事件订阅（SSE），已由 /api/v1/events 取代
+                    .route("/api/events", web::get().to(sse::events_handler))
                     // WebSocket 路由
                     .route("/ws", web::get().to(websocket::ws_handler))
             })
-            .bind(("127.0.0.1", 56789));
+            .listen(listener);
+
+            #[cfg(unix)]
+            let server = match unix_listener {
+                Some((uds, _)) => server.and_then(|s| s.listen_uds(uds)),
+                None => server,
+            };
 
             match server {
                 Ok(s) => {
-                    tracing::info!("HTTP Server starting on http://127.0.0.1:56789");
+                    tracing::info!("HTTP Server starting on http://127.0.0.1:{}", port);
+                    if let Some(path) = &socket_path {
+                        tracing::info!("HTTP Server also listening on {}", path.display());
+                    }
                     if let Err(e) = s.run().await {
                         tracing::error!("HTTP Server error: {}", e);
                     }
                 }
                 Err(e) => {
-                    tracing::error!("Failed to bind HTTP server port 56789: {}", e);
+                    tracing::error!("Failed to start HTTP server on port {}: {}", port, e);
                 }
             }
+
+            discovery::withdraw(socket_path.as_deref());
         });
     });
 }
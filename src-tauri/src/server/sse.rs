@@ -0,0 +1,47 @@
+//! # Server-Sent Events
+//!
+//! `GET /api/events` 以 SSE 形式推送事件总线上的所有事件，便于脚本订阅：
+//!
+//! ```text
+//! curl -N http://127.0.0.1:56789/api/events
+//!
+//! event: account_switched
+//! data: {"email":"user@example.com"}
+//! ```
+//!
+//! 连接空闲时定期发送注释行保活；订阅者消费过慢时丢弃积压的旧事件。
+
+use actix_web::{web, HttpResponse};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+/// 空闲保活间隔
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// SSE 订阅处理函数
+pub async fn events_handler() -> HttpResponse {
+    let receiver = crate::events::subscribe();
+    tracing::info!(target: "server::sse", "新的 SSE 订阅");
+
+    let stream = futures_util::stream::unfold(receiver, |mut receiver| async move {
+        let chunk = loop {
+            tokio::select! {
+                result = receiver.recv() => match result {
+                    Ok(event) => break event.to_sse(),
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!(target: "server::sse", skipped = skipped, "SSE 订阅者消费过慢，已丢弃部分事件");
+                    }
+                    Err(RecvError::Closed) => return None,
+                },
+                _ = tokio::time::sleep(KEEP_ALIVE_INTERVAL) => break ": keep-alive\n\n".to_string(),
+            }
+        };
+        Some((Ok::<_, actix_web::Error>(web::Bytes::from(chunk)), receiver))
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(stream)
+}
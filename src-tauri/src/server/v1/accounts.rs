@@ -0,0 +1,217 @@
+//! `/api/v1/accounts`：账户、指标与 token 健康状态
+
+use actix_web::{get, post, web};
+use tauri::{AppHandle, Manager};
+use utoipa::OpenApi;
+
+use super::dto::{
+    AccountHealth, AccountMetrics, AccountMetricsEntry, AccountState, AlignmentReport, HealthQuery,
+    ImportAccountsRequest, ImportReport, MessageResponse, MetricsQuery, TriggerResult,
+};
+use super::{config_dir, ApiResult};
+use crate::services::{account, account_health, metrics_cache, quota_alignment};
+use crate::AppState;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    // 固定路径需在 `{email}` 路径之前注册
+    cfg.service(list_accounts)
+        .service(current_account)
+        .service(save_current_account)
+        .service(sign_in_new_account)
+        .service(list_metrics)
+        .service(list_health)
+        .service(quota_alignment_plan)
+        .service(run_quota_alignment)
+        .service(import_accounts)
+        .service(switch_account)
+        .service(restore_account)
+        .service(account_metrics)
+        .service(refresh_quota);
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    list_accounts,
+    current_account,
+    save_current_account,
+    sign_in_new_account,
+    list_metrics,
+    list_health,
+    quota_alignment_plan,
+    run_quota_alignment,
+    import_accounts,
+    switch_account,
+    restore_account,
+    account_metrics,
+    refresh_quota
+))]
+pub struct Api;
+
+/// 已保存的账户（按修改时间倒序）
+#[utoipa::path(tag = "accounts", responses((status = 200, body = Vec<AccountState>)))]
+#[get("/accounts")]
+async fn list_accounts(data: web::Data<AppState>) -> ApiResult<Vec<AccountState>> {
+    let accounts = account::get_all(&config_dir(&data)).await?;
+    Ok(web::Json(accounts.into_iter().map(Into::into).collect()))
+}
+
+/// Antigravity 当前登录的账户
+#[utoipa::path(tag = "accounts", responses((status = 200, body = AccountState)))]
+#[get("/accounts/current")]
+async fn current_account() -> ApiResult<AccountState> {
+    Ok(web::Json(account::get_current().await?.into()))
+}
+
+/// 将当前账户保存到账户目录
+#[utoipa::path(tag = "accounts", responses((status = 200, body = MessageResponse)))]
+#[post("/accounts/current/save")]
+async fn save_current_account() -> ApiResult<MessageResponse> {
+    Ok(web::Json(account::backup_current().await?.into()))
+}
+
+/// 清除登录状态并启动 Antigravity 登录新账户
+#[utoipa::path(tag = "accounts", responses((status = 200, body = MessageResponse)))]
+#[post("/accounts/sign-in")]
+async fn sign_in_new_account() -> ApiResult<MessageResponse> {
+    Ok(web::Json(account::sign_in_new().await?.into()))
+}
+
+/// 所有账户的指标（带缓存），单个账户失败时在对应条目中返回错误
+#[utoipa::path(
+    tag = "accounts",
+    params(MetricsQuery),
+    responses((status = 200, body = Vec<AccountMetricsEntry>))
+)]
+#[get("/accounts/metrics")]
+async fn list_metrics(
+    data: web::Data<AppState>,
+    query: web::Query<MetricsQuery>,
+) -> ApiResult<Vec<AccountMetricsEntry>> {
+    let entries = metrics_cache::get_all(&config_dir(&data), query.force).await?;
+    Ok(web::Json(entries.into_iter().map(Into::into).collect()))
+}
+
+/// 所有账户的 token 健康状态
+#[utoipa::path(
+    tag = "accounts",
+    params(HealthQuery),
+    responses((status = 200, body = Vec<AccountHealth>))
+)]
+#[get("/accounts/health")]
+async fn list_health(
+    data: web::Data<AppState>,
+    query: web::Query<HealthQuery>,
+) -> ApiResult<Vec<AccountHealth>> {
+    let config_dir = config_dir(&data);
+
+    let mut results = account_health::load_recorded(&config_dir);
+    if query.refresh || results.is_empty() {
+        results = account_health::check_all(&config_dir).await?;
+    }
+    Ok(web::Json(results.into_iter().map(Into::into).collect()))
+}
+
+/// 导入账户文件，按冲突策略处理已存在的账户
+#[utoipa::path(
+    tag = "accounts",
+    request_body = ImportAccountsRequest,
+    responses((status = 200, body = ImportReport))
+)]
+#[post("/accounts/import")]
+async fn import_accounts(
+    data: web::Data<AppState>,
+    req: web::Json<ImportAccountsRequest>,
+) -> ApiResult<ImportReport> {
+    let req = req.into_inner();
+    let report = crate::services::backup::import_accounts(
+        &config_dir(&data),
+        req.accounts.into_iter().map(Into::into).collect(),
+        req.strategy.into(),
+        req.dry_run,
+    )
+    .await?;
+    Ok(web::Json(report.into()))
+}
+
+/// 切换到指定账户（会重启 Antigravity）
+#[utoipa::path(
+    tag = "accounts",
+    params(("email" = String, Path, description = "账户邮箱")),
+    responses((status = 200, body = MessageResponse))
+)]
+#[post("/accounts/{email}/switch")]
+async fn switch_account(email: web::Path<String>) -> ApiResult<MessageResponse> {
+    Ok(web::Json(account::switch(email.into_inner()).await?.into()))
+}
+
+/// 将指定账户写回 Antigravity（不重启）
+#[utoipa::path(
+    tag = "accounts",
+    params(("email" = String, Path, description = "账户邮箱")),
+    responses((status = 200, body = MessageResponse))
+)]
+#[post("/accounts/{email}/restore")]
+async fn restore_account(email: web::Path<String>) -> ApiResult<MessageResponse> {
+    Ok(web::Json(
+        account::restore(email.into_inner()).await?.into(),
+    ))
+}
+
+/// 指定账户的配额指标
+#[utoipa::path(
+    tag = "accounts",
+    params(("email" = String, Path, description = "账户邮箱")),
+    responses((status = 200, body = AccountMetrics))
+)]
+#[get("/accounts/{email}/metrics")]
+async fn account_metrics(
+    data: web::Data<AppState>,
+    email: web::Path<String>,
+) -> ApiResult<AccountMetrics> {
+    let metrics = account::get_metrics(&config_dir(&data), email.into_inner()).await?;
+    Ok(web::Json(metrics.into()))
+}
+
+/// 对配额已满的模型发送最小请求，触发配额重置计时
+#[utoipa::path(
+    tag = "accounts",
+    params(("email" = String, Path, description = "账户邮箱")),
+    responses((status = 200, body = TriggerResult))
+)]
+#[post("/accounts/{email}/quota-refresh")]
+async fn refresh_quota(
+    data: web::Data<AppState>,
+    email: web::Path<String>,
+) -> ApiResult<TriggerResult> {
+    let result = account::trigger_quota_refresh(&config_dir(&data), email.into_inner()).await?;
+    Ok(web::Json(result.into()))
+}
+
+/// 按配额对齐设置为所有账户生成配额窗口触发计划（不发送请求）
+#[utoipa::path(tag = "accounts", responses((status = 200, body = AlignmentReport)))]
+#[get("/accounts/quota-alignment")]
+async fn quota_alignment_plan(
+    data: web::Data<AppState>,
+    app: web::Data<AppHandle>,
+) -> ApiResult<AlignmentReport> {
+    alignment(&data, &app, true).await
+}
+
+/// 立即为所有账户发送到期的配额窗口触发，返回计划与执行结果
+#[utoipa::path(tag = "accounts", responses((status = 200, body = AlignmentReport)))]
+#[post("/accounts/quota-alignment/run")]
+async fn run_quota_alignment(
+    data: web::Data<AppState>,
+    app: web::Data<AppHandle>,
+) -> ApiResult<AlignmentReport> {
+    alignment(&data, &app, false).await
+}
+
+async fn alignment(data: &AppState, app: &AppHandle, dry_run: bool) -> ApiResult<AlignmentReport> {
+    let settings = app
+        .state::<crate::app_settings::AppSettingsManager>()
+        .get_settings()
+        .quota_alignment;
+    let report = quota_alignment::run(&config_dir(data), &settings, dry_run).await?;
+    Ok(web::Json(report.into()))
+}
@@ -0,0 +1,137 @@
+//! `/api/v1/antigravity` 与 `/api/v1/platform`：Antigravity 进程、安装路径与状态快照
+
+use actix_web::{get, post, put, web};
+use utoipa::OpenApi;
+
+use super::dto::{
+    CustomExecutable, Detection, MessageResponse, PathRequest, PlatformInfo, RunningStatus,
+    Snapshot, ValidationResult,
+};
+use super::ApiResult;
+use crate::services::{account, platform};
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(platform_info)
+        .service(status)
+        .service(clear_data)
+        .service(list_snapshots)
+        .service(undo_last_operation)
+        .service(detect_installation)
+        .service(find_installations)
+        .service(detect_executable)
+        .service(custom_executable)
+        .service(save_executable)
+        .service(validate_executable);
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    platform_info,
+    status,
+    clear_data,
+    list_snapshots,
+    undo_last_operation,
+    detect_installation,
+    find_installations,
+    detect_executable,
+    custom_executable,
+    save_executable,
+    validate_executable
+))]
+pub struct Api;
+
+/// 操作系统与 Antigravity 数据目录信息
+#[utoipa::path(tag = "antigravity", responses((status = 200, body = PlatformInfo)))]
+#[get("/platform")]
+async fn platform_info() -> ApiResult<PlatformInfo> {
+    Ok(web::Json(platform::get_platform_info().await?.into()))
+}
+
+/// Antigravity 是否正在运行
+#[utoipa::path(tag = "antigravity", responses((status = 200, body = RunningStatus)))]
+#[get("/antigravity/status")]
+async fn status() -> ApiResult<RunningStatus> {
+    Ok(web::Json(RunningStatus {
+        running: account::is_running(),
+    }))
+}
+
+/// 清除 Antigravity 中的登录数据（操作前自动创建快照）
+#[utoipa::path(tag = "antigravity", responses((status = 200, body = MessageResponse)))]
+#[post("/antigravity/clear")]
+async fn clear_data() -> ApiResult<MessageResponse> {
+    Ok(web::Json(account::clear_all_data().await?.into()))
+}
+
+/// 状态数据库快照（最新的在前）
+#[utoipa::path(tag = "antigravity", responses((status = 200, body = Vec<Snapshot>)))]
+#[get("/antigravity/snapshots")]
+async fn list_snapshots() -> ApiResult<Vec<Snapshot>> {
+    let snapshots = account::list_snapshots().await?;
+    Ok(web::Json(snapshots.into_iter().map(Into::into).collect()))
+}
+
+/// 从最近的快照恢复状态数据库
+#[utoipa::path(tag = "antigravity", responses((status = 200, body = MessageResponse)))]
+#[post("/antigravity/snapshots/undo")]
+async fn undo_last_operation() -> ApiResult<MessageResponse> {
+    Ok(web::Json(account::undo_last_operation().await?.into()))
+}
+
+/// 检测 Antigravity 数据目录
+#[utoipa::path(tag = "antigravity", responses((status = 200, body = Detection)))]
+#[get("/antigravity/installation")]
+async fn detect_installation() -> ApiResult<Detection> {
+    Ok(web::Json(
+        platform::detect_antigravity_installation().await?.into(),
+    ))
+}
+
+/// 查找所有 Antigravity 安装位置
+#[utoipa::path(tag = "antigravity", responses((status = 200, body = Vec<String>)))]
+#[get("/antigravity/installations")]
+async fn find_installations() -> ApiResult<Vec<String>> {
+    Ok(web::Json(platform::find_antigravity_installations().await?))
+}
+
+/// 当前使用的 Antigravity 可执行文件（优先使用自定义路径）
+#[utoipa::path(tag = "antigravity", responses((status = 200, body = Detection)))]
+#[get("/antigravity/executable")]
+async fn detect_executable() -> ApiResult<Detection> {
+    Ok(web::Json(
+        platform::detect_antigravity_executable().await?.into(),
+    ))
+}
+
+/// 用户设置的自定义可执行文件路径（未设置时为 null）
+#[utoipa::path(tag = "antigravity", responses((status = 200, body = CustomExecutable)))]
+#[get("/antigravity/executable/custom")]
+async fn custom_executable() -> ApiResult<CustomExecutable> {
+    Ok(web::Json(CustomExecutable {
+        path: platform::get_custom_executable().await?,
+    }))
+}
+
+/// 设置自定义的 Antigravity 可执行文件路径
+#[utoipa::path(
+    tag = "antigravity",
+    request_body = PathRequest,
+    responses((status = 200, body = MessageResponse))
+)]
+#[put("/antigravity/executable")]
+async fn save_executable(req: web::Json<PathRequest>) -> ApiResult<MessageResponse> {
+    let msg = platform::save_antigravity_executable(req.into_inner().path).await?;
+    Ok(web::Json(msg.into()))
+}
+
+/// 校验可执行文件路径
+#[utoipa::path(
+    tag = "antigravity",
+    request_body = PathRequest,
+    responses((status = 200, body = ValidationResult))
+)]
+#[post("/antigravity/executable/validate")]
+async fn validate_executable(req: web::Json<PathRequest>) -> ApiResult<ValidationResult> {
+    let valid = platform::validate_antigravity_executable(req.into_inner().path).await?;
+    Ok(web::Json(ValidationResult { valid }))
+}
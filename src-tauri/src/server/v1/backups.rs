@@ -0,0 +1,159 @@
+//! `/api/v1/backups` 与 `/api/v1/bundles`：账户文件备份、恢复与导入导出
+
+use actix_web::{delete, get, post, web};
+use utoipa::OpenApi;
+
+use super::dto::{
+    AccountFile, BundleImportResponse, BundleManifest, ExportBundleRequest, ImportBundleRequest,
+    MessageResponse, RestoreBackupsRequest, RestoreResult,
+};
+use super::error::ApiError;
+use super::{config_dir, ApiResult};
+use crate::services::{backup, bundle, settings};
+use crate::AppState;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(list_backups)
+        .service(restore_backups)
+        .service(clear_backups)
+        .service(delete_backup)
+        .service(export_bundle)
+        .service(import_bundle);
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    list_backups,
+    restore_backups,
+    clear_backups,
+    delete_backup,
+    export_bundle,
+    import_bundle
+))]
+pub struct Api;
+
+/// 所有账户文件的完整内容
+#[utoipa::path(tag = "backups", responses((status = 200, body = Vec<AccountFile>)))]
+#[get("/backups")]
+async fn list_backups(data: web::Data<AppState>) -> ApiResult<Vec<AccountFile>> {
+    let files = backup::collect_contents(&config_dir(&data)).await?;
+    Ok(web::Json(files.into_iter().map(Into::into).collect()))
+}
+
+/// 恢复账户文件（覆盖同名账户）
+#[utoipa::path(
+    tag = "backups",
+    request_body = RestoreBackupsRequest,
+    responses((status = 200, body = RestoreResult))
+)]
+#[post("/backups/restore")]
+async fn restore_backups(
+    data: web::Data<AppState>,
+    req: web::Json<RestoreBackupsRequest>,
+) -> ApiResult<RestoreResult> {
+    let files = req.into_inner().files.into_iter().map(Into::into).collect();
+    let result = backup::restore_files(&config_dir(&data), files).await?;
+    Ok(web::Json(result.into()))
+}
+
+/// 删除所有账户文件
+#[utoipa::path(tag = "backups", responses((status = 200, body = MessageResponse)))]
+#[delete("/backups")]
+async fn clear_backups(data: web::Data<AppState>) -> ApiResult<MessageResponse> {
+    Ok(web::Json(
+        backup::clear_all(&config_dir(&data)).await?.into(),
+    ))
+}
+
+/// 删除指定账户文件
+#[utoipa::path(
+    tag = "backups",
+    params(("name" = String, Path, description = "账户名（文件名，不含 .json）")),
+    responses((status = 200, body = MessageResponse))
+)]
+#[delete("/backups/{name}")]
+async fn delete_backup(
+    data: web::Data<AppState>,
+    name: web::Path<String>,
+) -> ApiResult<MessageResponse> {
+    let msg = backup::delete(&config_dir(&data), name.into_inner()).await?;
+    Ok(web::Json(msg.into()))
+}
+
+/// 导出所有账户（以及可选的设置）到 `bundles/` 下的 bundle 文件
+///
+/// bundle 包含所有账户的 token：既未指定接收者也未提供密码时必须设置 `allowPlaintext`，
+/// 否则返回 400。
+#[utoipa::path(
+    tag = "backups",
+    request_body = ExportBundleRequest,
+    responses((status = 200, body = BundleManifest))
+)]
+#[post("/bundles/export")]
+async fn export_bundle(
+    data: web::Data<AppState>,
+    app: web::Data<tauri::AppHandle>,
+    req: web::Json<ExportBundleRequest>,
+) -> ApiResult<BundleManifest> {
+    let settings = if req.include_settings {
+        Some(settings::export_settings(&app)?)
+    } else {
+        None
+    };
+
+    let path = bundle::bundle_path(&req.file_name).map_err(ApiError::invalid_request)?;
+    if req.recipients.is_empty() && req.password.is_none() && !req.allow_plaintext {
+        return Err(ApiError::invalid_request(
+            "未指定接收者公钥或密码时需设置 allowPlaintext 才能导出明文 token",
+        ));
+    }
+
+    let manifest = bundle::export_to_file(
+        &config_dir(&data),
+        &path,
+        settings,
+        &req.recipients,
+        req.password.as_deref(),
+        req.allow_plaintext,
+    )
+    .await?;
+    Ok(web::Json(manifest.into()))
+}
+
+/// 从 `bundles/` 下的 bundle 文件导入账户，可选同时应用其中的界面偏好设置
+#[utoipa::path(
+    tag = "backups",
+    request_body = ImportBundleRequest,
+    responses((status = 200, body = BundleImportResponse))
+)]
+#[post("/bundles/import")]
+async fn import_bundle(
+    data: web::Data<AppState>,
+    app: web::Data<tauri::AppHandle>,
+    req: web::Json<ImportBundleRequest>,
+) -> ApiResult<BundleImportResponse> {
+    let req = req.into_inner();
+    let path = bundle::bundle_path(&req.file_name).map_err(ApiError::invalid_request)?;
+    let result = bundle::import_from_file(
+        &config_dir(&data),
+        &path,
+        req.strategy.into(),
+        req.dry_run,
+        req.password.as_deref(),
+    )
+    .await?;
+
+    let mut settings_applied = false;
+    if req.apply_settings && !req.dry_run {
+        if let Some(imported) = result.settings {
+            settings::apply_imported_settings(&app, imported).await?;
+            settings_applied = true;
+        }
+    }
+
+    Ok(web::Json(BundleImportResponse {
+        manifest: result.manifest.into(),
+        report: result.report.into(),
+        settings_applied,
+    }))
+}
@@ -0,0 +1,142 @@
+//! `/api/v1/crypto`：导出数据的密码加密与公钥加密
+
+use actix_web::{get, post, web};
+use utoipa::OpenApi;
+
+use super::dto::{
+    CryptoResult, DecryptRequest, DecryptResponse, EncryptRequest, PublicKeyResponse,
+    RecipientDecryptRequest, RecipientEncryptRequest, ReencryptRequest, ReencryptResponse,
+};
+use super::ApiResult;
+use crate::security::{crypto, recipients};
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(encrypt)
+        .service(decrypt)
+        .service(reencrypt)
+        .service(public_key)
+        .service(encrypt_for_recipients)
+        .service(decrypt_with_local_key);
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    encrypt,
+    decrypt,
+    reencrypt,
+    public_key,
+    encrypt_for_recipients,
+    decrypt_with_local_key
+))]
+pub struct Api;
+
+fn read_keyfile(path: Option<&str>) -> Result<Option<Vec<u8>>, String> {
+    path.map(|path| std::fs::read(path).map_err(|e| format!("读取密钥文件失败: {}", e)))
+        .transpose()
+}
+
+/// 用密码（和可选的密钥文件）加密
+#[utoipa::path(
+    tag = "crypto",
+    request_body = EncryptRequest,
+    responses((status = 200, body = CryptoResult))
+)]
+#[post("/crypto/encrypt")]
+async fn encrypt(req: web::Json<EncryptRequest>) -> ApiResult<CryptoResult> {
+    let keyfile = read_keyfile(req.keyfile_path.as_deref())?;
+    let options = crypto::EncryptOptions {
+        kdf: req.kdf.map(Into::into).unwrap_or_default(),
+        associated_data: req
+            .associated_data
+            .as_deref()
+            .unwrap_or_default()
+            .as_bytes(),
+        keyfile: keyfile.as_deref(),
+    };
+    let result = crypto::encrypt_with_options(req.data.as_bytes(), &req.password, &options)?;
+    Ok(web::Json(CryptoResult { result }))
+}
+
+/// 解密（自动识别格式）
+#[utoipa::path(
+    tag = "crypto",
+    request_body = DecryptRequest,
+    responses((status = 200, body = DecryptResponse))
+)]
+#[post("/crypto/decrypt")]
+async fn decrypt(req: web::Json<DecryptRequest>) -> ApiResult<DecryptResponse> {
+    let keyfile = read_keyfile(req.keyfile_path.as_deref())?;
+    let decrypted = crypto::decrypt_with_options(&req.data, &req.password, keyfile.as_deref())?;
+    Ok(web::Json(DecryptResponse {
+        result: decrypted.plaintext,
+        needs_reencrypt: decrypted.format.needs_reencrypt(),
+        format: decrypted.format.into(),
+        associated_data: String::from_utf8_lossy(&decrypted.associated_data).to_string(),
+    }))
+}
+
+/// 解密后按当前格式重新加密
+#[utoipa::path(
+    tag = "crypto",
+    request_body = ReencryptRequest,
+    responses((status = 200, body = ReencryptResponse))
+)]
+#[post("/crypto/reencrypt")]
+async fn reencrypt(req: web::Json<ReencryptRequest>) -> ApiResult<ReencryptResponse> {
+    let keyfile = read_keyfile(req.keyfile_path.as_deref())?;
+    let options = crypto::EncryptOptions {
+        kdf: req.kdf.map(Into::into).unwrap_or_default(),
+        associated_data: req
+            .associated_data
+            .as_deref()
+            .unwrap_or_default()
+            .as_bytes(),
+        keyfile: keyfile.as_deref(),
+    };
+    let (result, previous_format) =
+        crypto::reencrypt_with_options(&req.data, &req.password, &options)?;
+    Ok(web::Json(ReencryptResponse {
+        result,
+        previous_format: previous_format.into(),
+    }))
+}
+
+/// 本机 age 公钥
+#[utoipa::path(tag = "crypto", responses((status = 200, body = PublicKeyResponse)))]
+#[get("/crypto/public-key")]
+async fn public_key() -> ApiResult<PublicKeyResponse> {
+    Ok(web::Json(PublicKeyResponse {
+        public_key: recipients::local_public_key()?,
+    }))
+}
+
+/// 按接收者公钥加密
+#[utoipa::path(
+    tag = "crypto",
+    request_body = RecipientEncryptRequest,
+    responses((status = 200, body = CryptoResult))
+)]
+#[post("/crypto/recipients/encrypt")]
+async fn encrypt_for_recipients(
+    req: web::Json<RecipientEncryptRequest>,
+) -> ApiResult<CryptoResult> {
+    let result =
+        recipients::encrypt_for_recipients(req.data.as_bytes(), &req.recipients, req.include_self)?;
+    Ok(web::Json(CryptoResult { result }))
+}
+
+/// 用本机私钥解密
+#[utoipa::path(
+    tag = "crypto",
+    request_body = RecipientDecryptRequest,
+    responses((status = 200, body = CryptoResult))
+)]
+#[post("/crypto/recipients/decrypt")]
+async fn decrypt_with_local_key(
+    req: web::Json<RecipientDecryptRequest>,
+) -> ApiResult<CryptoResult> {
+    let plain = recipients::decrypt_for_self(req.data.as_bytes())?;
+    let result =
+        String::from_utf8(plain).map_err(|_| "解密后的数据不是有效的 UTF-8 文本".to_string())?;
+    Ok(web::Json(CryptoResult { result }))
+}
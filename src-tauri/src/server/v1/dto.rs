@@ -0,0 +1,1587 @@
+//! v1 接口的请求 / 响应类型
+//!
+//! 所有字段统一为 camelCase，与服务层类型（部分为 snake_case）通过 `From` 转换，
+//! 服务层序列化格式变化不会影响 v1 接口。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::services::{
+    account, account_health, backup, bundle, launch, metrics_cache, platform, quota_alignment,
+    scheduler,
+};
+
+// =============================================================================
+// 通用
+// =============================================================================
+
+/// 操作结果说明
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MessageResponse {
+    pub message: String,
+}
+
+impl From<String> for MessageResponse {
+    fn from(message: String) -> Self {
+        Self { message }
+    }
+}
+
+/// 文件路径
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PathRequest {
+    pub path: String,
+}
+
+/// 运行状态
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RunningStatus {
+    pub running: bool,
+}
+
+/// 将 JSON 对象的键递归转换为 camelCase（用于直接透传的 proto 解码结果）
+pub fn camelize_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (snake_to_camel(&key), camelize_keys(value)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(camelize_keys).collect()),
+        other => other,
+    }
+}
+
+fn snake_to_camel(key: &str) -> String {
+    let mut result = String::with_capacity(key.len());
+    let mut upper = false;
+    for c in key.chars() {
+        if c == '_' && !result.is_empty() {
+            upper = true;
+        } else if upper {
+            result.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+// =============================================================================
+// 账户
+// =============================================================================
+
+/// 账户状态（Antigravity `SessionResponse` 的解码结果，键为 camelCase）
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(transparent)]
+#[schema(value_type = Object)]
+pub struct AccountState(pub Value);
+
+impl From<Value> for AccountState {
+    fn from(value: Value) -> Self {
+        Self(camelize_keys(value))
+    }
+}
+
+/// 单个模型的配额
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Quota {
+    pub model_name: String,
+    /// 剩余配额百分比（0-100）
+    pub percentage: f64,
+    pub reset_text: String,
+    /// 解析后的重置时间（RFC 3339），`resetText` 为空或无法解析时为空
+    pub reset_at: Option<String>,
+}
+
+/// 账户指标
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountMetrics {
+    pub email: String,
+    pub user_id: String,
+    pub avatar_url: String,
+    pub quotas: Vec<Quota>,
+}
+
+impl From<account::AccountMetrics> for AccountMetrics {
+    fn from(metrics: account::AccountMetrics) -> Self {
+        Self {
+            email: metrics.email,
+            user_id: metrics.user_id,
+            avatar_url: metrics.avatar_url,
+            quotas: metrics
+                .quotas
+                .into_iter()
+                .map(|q| Quota {
+                    reset_at: q.reset_at().map(|time| time.to_rfc3339()),
+                    model_name: q.model_name,
+                    percentage: q.percentage,
+                    reset_text: q.reset_text,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 批量指标中单个账户的结果
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountMetricsEntry {
+    pub email: String,
+    pub metrics: Option<AccountMetrics>,
+    pub error: Option<String>,
+    /// 是否来自缓存
+    pub cached: bool,
+    /// 指标拉取时间（RFC 3339）
+    pub fetched_at: Option<String>,
+}
+
+impl From<metrics_cache::AccountMetricsEntry> for AccountMetricsEntry {
+    fn from(entry: metrics_cache::AccountMetricsEntry) -> Self {
+        Self {
+            email: entry.email,
+            metrics: entry.metrics.map(Into::into),
+            error: entry.error,
+            cached: entry.cached,
+            fetched_at: entry.fetched_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MetricsQuery {
+    /// 忽略缓存重新拉取
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// 配额刷新结果
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TriggerResult {
+    pub email: String,
+    pub triggered_models: Vec<String>,
+    pub failed_models: Vec<String>,
+    pub skipped_models: Vec<String>,
+    pub skipped_details: Vec<String>,
+    pub success: bool,
+    pub message: String,
+}
+
+impl From<account::TriggerResult> for TriggerResult {
+    fn from(result: account::TriggerResult) -> Self {
+        Self {
+            email: result.email,
+            triggered_models: result.triggered_models,
+            failed_models: result.failed_models,
+            skipped_models: result.skipped_models,
+            skipped_details: result.skipped_details,
+            success: result.success,
+            message: result.message,
+        }
+    }
+}
+
+/// 计划中的一次配额窗口触发
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedTrigger {
+    pub email: String,
+    pub model: String,
+    /// 计划触发时间（RFC 3339）
+    pub at: String,
+    /// 是否已到期（执行时会触发）
+    pub due: bool,
+    /// 当前窗口的重置时间（RFC 3339），配额已满时为空
+    pub reset_at: Option<String>,
+}
+
+impl From<quota_alignment::PlannedTrigger> for PlannedTrigger {
+    fn from(trigger: quota_alignment::PlannedTrigger) -> Self {
+        Self {
+            email: trigger.email,
+            model: trigger.model,
+            at: trigger.at,
+            due: trigger.due,
+            reset_at: trigger.reset_at,
+        }
+    }
+}
+
+/// 已执行的配额窗口触发
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutedTrigger {
+    pub email: String,
+    pub model: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+impl From<quota_alignment::ExecutedTrigger> for ExecutedTrigger {
+    fn from(trigger: quota_alignment::ExecutedTrigger) -> Self {
+        Self {
+            email: trigger.email,
+            model: trigger.model,
+            success: trigger.success,
+            error: trigger.error,
+        }
+    }
+}
+
+/// 无法获取配额的账户
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AlignmentFailure {
+    pub email: String,
+    pub error: String,
+}
+
+/// 配额窗口对齐报告
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AlignmentReport {
+    /// 生成时间（RFC 3339）
+    pub generated_at: String,
+    /// 为 true 时只生成了计划，没有发送触发请求
+    pub dry_run: bool,
+    pub planned: Vec<PlannedTrigger>,
+    pub executed: Vec<ExecutedTrigger>,
+    pub failed_accounts: Vec<AlignmentFailure>,
+}
+
+impl From<quota_alignment::AlignmentReport> for AlignmentReport {
+    fn from(report: quota_alignment::AlignmentReport) -> Self {
+        Self {
+            generated_at: report.generated_at,
+            dry_run: report.dry_run,
+            planned: report.planned.into_iter().map(Into::into).collect(),
+            executed: report.executed.into_iter().map(Into::into).collect(),
+            failed_accounts: report
+                .failed_accounts
+                .into_iter()
+                .map(|failure| AlignmentFailure {
+                    email: failure.email,
+                    error: failure.error,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 账户 token 状态
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum HealthStatus {
+    Valid,
+    Refreshed,
+    Revoked,
+    NetworkError,
+    MissingToken,
+}
+
+impl From<account_health::HealthStatus> for HealthStatus {
+    fn from(status: account_health::HealthStatus) -> Self {
+        use account_health::HealthStatus as S;
+        match status {
+            S::Valid => Self::Valid,
+            S::Refreshed => Self::Refreshed,
+            S::Revoked => Self::Revoked,
+            S::NetworkError => Self::NetworkError,
+            S::MissingToken => Self::MissingToken,
+        }
+    }
+}
+
+/// 账户 token 健康检查结果
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountHealth {
+    pub email: String,
+    pub status: HealthStatus,
+    /// 检查时间（RFC 3339）
+    pub checked_at: String,
+    pub error: Option<String>,
+}
+
+impl From<account_health::AccountHealth> for AccountHealth {
+    fn from(health: account_health::AccountHealth) -> Self {
+        Self {
+            email: health.email,
+            status: health.status.into(),
+            checked_at: health.checked_at,
+            error: health.error,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct HealthQuery {
+    /// 重新检查（默认返回最近一次的结果）
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+// =============================================================================
+// 备份与导入
+// =============================================================================
+
+/// 账户文件
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AccountFile {
+    pub filename: String,
+    /// 账户文件内容
+    #[schema(value_type = Object)]
+    pub content: Value,
+    /// 修改时间（毫秒）
+    pub timestamp: u64,
+}
+
+impl From<backup::AccountExportedData> for AccountFile {
+    fn from(data: backup::AccountExportedData) -> Self {
+        Self {
+            filename: data.filename,
+            content: data.content,
+            timestamp: data.timestamp,
+        }
+    }
+}
+
+impl From<AccountFile> for backup::AccountExportedData {
+    fn from(file: AccountFile) -> Self {
+        Self {
+            filename: file.filename,
+            content: file.content,
+            timestamp: file.timestamp,
+        }
+    }
+}
+
+/// 恢复账户文件
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RestoreBackupsRequest {
+    pub files: Vec<AccountFile>,
+}
+
+/// 恢复失败的文件
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FailedFile {
+    pub filename: String,
+    pub error: String,
+}
+
+/// 恢复结果
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreResult {
+    pub restored_count: u32,
+    pub failed: Vec<FailedFile>,
+}
+
+impl From<backup::RestoreResult> for RestoreResult {
+    fn from(result: backup::RestoreResult) -> Self {
+        Self {
+            restored_count: result.restored_count,
+            failed: result
+                .failed
+                .into_iter()
+                .map(|f| FailedFile {
+                    filename: f.filename,
+                    error: f.error,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 导入冲突处理策略（目标账户文件已存在时）
+#[derive(Debug, Clone, Copy, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictStrategy {
+    /// 保留本地文件
+    Skip,
+    /// 用导入内容覆盖
+    #[default]
+    Overwrite,
+    /// 保留 token 较新的一份
+    KeepNewer,
+    /// 以新文件名另存
+    Rename,
+}
+
+impl From<ConflictStrategy> for backup::ConflictStrategy {
+    fn from(strategy: ConflictStrategy) -> Self {
+        match strategy {
+            ConflictStrategy::Skip => Self::Skip,
+            ConflictStrategy::Overwrite => Self::Overwrite,
+            ConflictStrategy::KeepNewer => Self::KeepNewer,
+            ConflictStrategy::Rename => Self::Rename,
+        }
+    }
+}
+
+/// 导入账户文件
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportAccountsRequest {
+    pub accounts: Vec<AccountFile>,
+    #[serde(default)]
+    pub strategy: ConflictStrategy,
+    /// 只返回导入计划，不写入文件
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// 单个导入条目的处理结果
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportAction {
+    Create,
+    Overwrite,
+    Rename,
+    Skip,
+    Invalid,
+    Failed,
+}
+
+impl From<backup::ImportAction> for ImportAction {
+    fn from(action: backup::ImportAction) -> Self {
+        use backup::ImportAction as A;
+        match action {
+            A::Create => Self::Create,
+            A::Overwrite => Self::Overwrite,
+            A::Rename => Self::Rename,
+            A::Skip => Self::Skip,
+            A::Invalid => Self::Invalid,
+            A::Failed => Self::Failed,
+        }
+    }
+}
+
+/// 导入条目
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportItem {
+    /// 导入数据中声明的文件名
+    pub filename: String,
+    pub email: Option<String>,
+    /// 实际写入的文件名
+    pub target_filename: Option<String>,
+    pub action: ImportAction,
+    /// 跳过 / 拒绝 / 失败的原因
+    pub reason: Option<String>,
+}
+
+/// 导入报告（dry-run 时为预览）
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub created: u32,
+    pub overwritten: u32,
+    pub renamed: u32,
+    pub skipped: u32,
+    pub invalid: u32,
+    pub failed: u32,
+    pub items: Vec<ImportItem>,
+}
+
+impl From<backup::ImportReport> for ImportReport {
+    fn from(report: backup::ImportReport) -> Self {
+        Self {
+            dry_run: report.dry_run,
+            created: report.created,
+            overwritten: report.overwritten,
+            renamed: report.renamed,
+            skipped: report.skipped,
+            invalid: report.invalid,
+            failed: report.failed,
+            items: report
+                .items
+                .into_iter()
+                .map(|item| ImportItem {
+                    filename: item.filename,
+                    email: item.email,
+                    target_filename: item.target_filename,
+                    action: item.action.into(),
+                    reason: item.reason,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 导出 bundle
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportBundleRequest {
+    /// 导出文件名，写入配置目录下的 `bundles/`
+    pub file_name: String,
+    #[serde(default)]
+    pub include_settings: bool,
+    /// 接收者公钥（age1...）
+    #[serde(default)]
+    pub recipients: Vec<String>,
+    /// 用密码加密（与 `recipients` 二选一）
+    #[serde(default)]
+    pub password: Option<String>,
+    /// 既没有接收者也没有密码时允许以明文导出 token
+    #[serde(default)]
+    pub allow_plaintext: bool,
+}
+
+/// 导入 bundle
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportBundleRequest {
+    /// bundle 文件名，从配置目录下的 `bundles/` 读取
+    pub file_name: String,
+    #[serde(default)]
+    pub strategy: ConflictStrategy,
+    #[serde(default)]
+    pub dry_run: bool,
+    /// 同时应用 bundle 中的设置
+    #[serde(default)]
+    pub apply_settings: bool,
+    /// 密码加密的 bundle 所需的密码
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+/// bundle 清单中的账户
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ManifestAccount {
+    pub filename: String,
+    pub email: Option<String>,
+    /// 账户内容的 sha256（hex）
+    pub sha256: String,
+}
+
+/// bundle 清单
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleManifest {
+    pub format: String,
+    pub format_version: u32,
+    pub min_reader_version: u32,
+    /// 生成该 bundle 的 Agent 版本
+    pub agent_version: String,
+    /// 创建时间（RFC 3339）
+    pub created_at: String,
+    pub accounts: Vec<ManifestAccount>,
+    /// 设置内容的 sha256，未包含设置时为空
+    pub settings_sha256: Option<String>,
+}
+
+impl From<bundle::BundleManifest> for BundleManifest {
+    fn from(manifest: bundle::BundleManifest) -> Self {
+        Self {
+            format: manifest.format,
+            format_version: manifest.format_version,
+            min_reader_version: manifest.min_reader_version,
+            agent_version: manifest.agent_version,
+            created_at: manifest.created_at,
+            accounts: manifest
+                .accounts
+                .into_iter()
+                .map(|a| ManifestAccount {
+                    filename: a.filename,
+                    email: a.email,
+                    sha256: a.sha256,
+                })
+                .collect(),
+            settings_sha256: manifest.settings_sha256,
+        }
+    }
+}
+
+/// 导入 bundle 的结果
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleImportResponse {
+    pub manifest: BundleManifest,
+    pub report: ImportReport,
+    pub settings_applied: bool,
+}
+
+// =============================================================================
+// Antigravity 与平台
+// =============================================================================
+
+/// 状态快照
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub id: String,
+    /// 触发快照的操作
+    pub operation: String,
+    /// 创建时间（RFC 3339）
+    pub created_at: String,
+    pub source_path: String,
+    pub has_backup_db: bool,
+}
+
+impl From<crate::antigravity::snapshot::SnapshotInfo> for Snapshot {
+    fn from(info: crate::antigravity::snapshot::SnapshotInfo) -> Self {
+        Self {
+            id: info.id,
+            operation: info.operation,
+            created_at: info.created_at,
+            source_path: info.source_path,
+            has_backup_db: info.has_backup_db,
+        }
+    }
+}
+
+/// 安装目录 / 可执行文件检测结果
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Detection {
+    pub found: bool,
+    pub path: Option<String>,
+    /// 是否为用户自定义的路径
+    pub is_custom_path: bool,
+}
+
+impl From<platform::Detection> for Detection {
+    fn from(detection: platform::Detection) -> Self {
+        Self {
+            found: detection.found,
+            path: detection.path,
+            is_custom_path: detection.is_custom_path,
+        }
+    }
+}
+
+/// 用户自定义的可执行文件路径
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CustomExecutable {
+    /// 未设置时为 null；路径可能已失效，不做校验
+    pub path: Option<String>,
+}
+
+/// 路径校验结果
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ValidationResult {
+    pub valid: bool,
+}
+
+/// 平台信息
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PlatformInfo {
+    pub os: String,
+    pub arch: String,
+    pub family: String,
+    pub antigravity_available: bool,
+    pub antigravity_paths: Vec<String>,
+    pub config_dir: Option<String>,
+    pub data_dir: Option<String>,
+    pub home_dir: Option<String>,
+}
+
+impl From<platform::PlatformInfo> for PlatformInfo {
+    fn from(info: platform::PlatformInfo) -> Self {
+        Self {
+            os: info.os,
+            arch: info.arch,
+            family: info.family,
+            antigravity_available: info.antigravity_available,
+            antigravity_paths: info.antigravity_paths,
+            config_dir: info.config_dir,
+            data_dir: info.data_dir,
+            home_dir: info.home_dir,
+        }
+    }
+}
+
+// =============================================================================
+// 设置
+// =============================================================================
+
+/// Google 接口地址
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiEndpoints {
+    pub cloud_code_base_url: String,
+    pub oauth_token_url: String,
+    pub userinfo_url: String,
+}
+
+/// 网络设置
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkSettings {
+    /// 代理地址（http / https / socks5 / socks5h），为空时不使用代理
+    pub proxy_url: Option<String>,
+    /// 不走代理的主机列表（逗号分隔）
+    pub no_proxy: Option<String>,
+    /// 额外信任的根证书（PEM 文件路径）
+    pub ca_bundle_path: Option<String>,
+    pub connect_timeout_secs: u64,
+    pub request_timeout_secs: u64,
+    pub user_agent: Option<String>,
+    pub endpoints: ApiEndpoints,
+}
+
+impl From<crate::app_settings::NetworkSettings> for NetworkSettings {
+    fn from(network: crate::app_settings::NetworkSettings) -> Self {
+        Self {
+            proxy_url: network.proxy_url,
+            no_proxy: network.no_proxy,
+            ca_bundle_path: network.ca_bundle_path,
+            connect_timeout_secs: network.connect_timeout_secs,
+            request_timeout_secs: network.request_timeout_secs,
+            user_agent: network.user_agent,
+            endpoints: ApiEndpoints {
+                cloud_code_base_url: network.endpoints.cloud_code_base_url,
+                oauth_token_url: network.endpoints.oauth_token_url,
+                userinfo_url: network.endpoints.userinfo_url,
+            },
+        }
+    }
+}
+
+impl From<NetworkSettings> for crate::app_settings::NetworkSettings {
+    fn from(network: NetworkSettings) -> Self {
+        Self {
+            proxy_url: network.proxy_url,
+            no_proxy: network.no_proxy,
+            ca_bundle_path: network.ca_bundle_path,
+            connect_timeout_secs: network.connect_timeout_secs,
+            request_timeout_secs: network.request_timeout_secs,
+            user_agent: network.user_agent,
+            endpoints: crate::services::google_api::ApiEndpoints {
+                cloud_code_base_url: network.endpoints.cloud_code_base_url,
+                oauth_token_url: network.endpoints.oauth_token_url,
+                userinfo_url: network.endpoints.userinfo_url,
+            },
+        }
+    }
+}
+
+/// 本地 HTTP 服务设置（重启后生效）
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerSettings {
+    /// 监听端口（环境变量 `ANTIGRAVITY_AGENT_PORT` 优先）
+    pub port: u16,
+    /// 端口被占用时是否自动改用其他空闲端口
+    pub port_fallback: bool,
+    /// 是否同时监听 Unix 域套接字（仅 Unix）
+    pub unix_socket: bool,
+    /// TCP 端口上的请求是否必须携带实例 token（套接字与应用自身 webview 除外）
+    #[serde(default)]
+    pub require_token: bool,
+}
+
+impl From<crate::app_settings::ServerSettings> for ServerSettings {
+    fn from(server: crate::app_settings::ServerSettings) -> Self {
+        Self {
+            port: server.port,
+            port_fallback: server.port_fallback,
+            unix_socket: server.unix_socket,
+            require_token: server.require_token,
+        }
+    }
+}
+
+impl From<ServerSettings> for crate::app_settings::ServerSettings {
+    fn from(server: ServerSettings) -> Self {
+        Self {
+            port: server.port,
+            port_fallback: server.port_fallback,
+            unix_socket: server.unix_socket,
+            require_token: server.require_token,
+        }
+    }
+}
+
+/// 通知规则类型
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationRuleType {
+    /// 剩余配额低于阈值
+    QuotaLow,
+    /// 配额已重置
+    QuotaReset,
+    /// token 已被吊销
+    TokenRevoked,
+    /// 切换账户成功
+    SwitchSucceeded,
+    /// 切换账户失败
+    SwitchFailed,
+    /// 保存了新账户
+    AccountCaptured,
+}
+
+/// 通知规则
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationRule {
+    #[serde(rename = "type")]
+    pub rule_type: NotificationRuleType,
+    pub enabled: bool,
+    /// 配额阈值（百分比，0-100），`quota_low` 必填
+    pub threshold: Option<f64>,
+    /// 限定模型名称（不区分大小写），为空时适用于所有模型；仅 `quota_low` / `quota_reset` 使用
+    pub model: Option<String>,
+}
+
+impl From<crate::services::notification::NotificationRule> for NotificationRule {
+    fn from(rule: crate::services::notification::NotificationRule) -> Self {
+        use crate::services::notification::NotificationTrigger as Trigger;
+        let (rule_type, threshold, model) = match rule.trigger {
+            Trigger::QuotaLow { threshold, model } => {
+                (NotificationRuleType::QuotaLow, Some(threshold), model)
+            }
+            Trigger::QuotaReset { model } => (NotificationRuleType::QuotaReset, None, model),
+            Trigger::TokenRevoked => (NotificationRuleType::TokenRevoked, None, None),
+            Trigger::SwitchSucceeded => (NotificationRuleType::SwitchSucceeded, None, None),
+            Trigger::SwitchFailed => (NotificationRuleType::SwitchFailed, None, None),
+            Trigger::AccountCaptured => (NotificationRuleType::AccountCaptured, None, None),
+        };
+        Self {
+            rule_type,
+            enabled: rule.enabled,
+            threshold,
+            model,
+        }
+    }
+}
+
+impl TryFrom<NotificationRule> for crate::services::notification::NotificationRule {
+    type Error = String;
+
+    fn try_from(rule: NotificationRule) -> Result<Self, String> {
+        use crate::services::notification::NotificationTrigger as Trigger;
+        let model = rule.model.filter(|model| !model.trim().is_empty());
+        let trigger = match rule.rule_type {
+            NotificationRuleType::QuotaLow => Trigger::QuotaLow {
+                threshold: rule
+                    .threshold
+                    .ok_or_else(|| "quota_low 规则缺少 threshold".to_string())?,
+                model,
+            },
+            NotificationRuleType::QuotaReset => Trigger::QuotaReset { model },
+            NotificationRuleType::TokenRevoked => Trigger::TokenRevoked,
+            NotificationRuleType::SwitchSucceeded => Trigger::SwitchSucceeded,
+            NotificationRuleType::SwitchFailed => Trigger::SwitchFailed,
+            NotificationRuleType::AccountCaptured => Trigger::AccountCaptured,
+        };
+        let rule = Self {
+            enabled: rule.enabled,
+            trigger,
+        };
+        rule.validate()?;
+        Ok(rule)
+    }
+}
+
+/// 桌面通知设置
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationSettings {
+    pub enabled: bool,
+    /// 同一规则对同一账户 / 模型重复通知的最短间隔（秒）
+    pub min_interval_secs: u64,
+    /// 每小时最多发送的通知数
+    pub max_per_hour: u32,
+    pub rules: Vec<NotificationRule>,
+}
+
+impl From<crate::app_settings::NotificationSettings> for NotificationSettings {
+    fn from(notifications: crate::app_settings::NotificationSettings) -> Self {
+        Self {
+            enabled: notifications.enabled,
+            min_interval_secs: notifications.min_interval_secs,
+            max_per_hour: notifications.max_per_hour,
+            rules: notifications.rules.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl TryFrom<NotificationSettings> for crate::app_settings::NotificationSettings {
+    type Error = String;
+
+    fn try_from(notifications: NotificationSettings) -> Result<Self, String> {
+        Ok(Self {
+            enabled: notifications.enabled,
+            min_interval_secs: notifications.min_interval_secs,
+            max_per_hour: notifications.max_per_hour,
+            rules: notifications
+                .rules
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// 定时任务类型
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobType {
+    /// 刷新所有账户的配额指标
+    RefreshMetrics,
+    /// 检查所有账户的 token 健康状态
+    CheckTokenHealth,
+    /// 保存当前登录的账户
+    BackupCurrent,
+    /// 为所有账户触发配额刷新
+    TriggerQuotaRefresh,
+    /// 清理过期的 state.vscdb 快照
+    PruneSnapshots,
+    /// 触发到期的配额窗口（见配额对齐设置）
+    AlignQuotaWindows,
+}
+
+/// 错过计划时间时的处理方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MissedRunPolicy {
+    /// 跳过，等待下一次计划时间
+    Skip,
+    /// 立即补跑一次
+    RunOnce,
+}
+
+impl From<scheduler::MissedRunPolicy> for MissedRunPolicy {
+    fn from(policy: scheduler::MissedRunPolicy) -> Self {
+        match policy {
+            scheduler::MissedRunPolicy::Skip => Self::Skip,
+            scheduler::MissedRunPolicy::RunOnce => Self::RunOnce,
+        }
+    }
+}
+
+impl From<MissedRunPolicy> for scheduler::MissedRunPolicy {
+    fn from(policy: MissedRunPolicy) -> Self {
+        match policy {
+            MissedRunPolicy::Skip => Self::Skip,
+            MissedRunPolicy::RunOnce => Self::RunOnce,
+        }
+    }
+}
+
+/// 定时任务
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledJob {
+    /// 任务 ID，在所有任务中唯一
+    pub id: String,
+    #[serde(rename = "type")]
+    pub job_type: JobType,
+    pub enabled: bool,
+    /// 五段式 cron 表达式（分 时 日 月 周），或 `@hourly` / `@daily` / `@weekly` / `@monthly`
+    pub schedule: String,
+    pub missed_run: MissedRunPolicy,
+    /// 快照保留天数，`prune_snapshots` 必填
+    pub keep_days: Option<u32>,
+}
+
+impl From<scheduler::ScheduledJob> for ScheduledJob {
+    fn from(job: scheduler::ScheduledJob) -> Self {
+        use scheduler::JobKind;
+        let (job_type, keep_days) = match job.job {
+            JobKind::RefreshMetrics => (JobType::RefreshMetrics, None),
+            JobKind::CheckTokenHealth => (JobType::CheckTokenHealth, None),
+            JobKind::BackupCurrent => (JobType::BackupCurrent, None),
+            JobKind::TriggerQuotaRefresh => (JobType::TriggerQuotaRefresh, None),
+            JobKind::PruneSnapshots { keep_days } => (JobType::PruneSnapshots, Some(keep_days)),
+            JobKind::AlignQuotaWindows => (JobType::AlignQuotaWindows, None),
+        };
+        Self {
+            id: job.id,
+            job_type,
+            enabled: job.enabled,
+            schedule: job.schedule,
+            missed_run: job.missed_run.into(),
+            keep_days,
+        }
+    }
+}
+
+impl TryFrom<ScheduledJob> for scheduler::ScheduledJob {
+    type Error = String;
+
+    fn try_from(job: ScheduledJob) -> Result<Self, String> {
+        use scheduler::JobKind;
+        let kind = match job.job_type {
+            JobType::RefreshMetrics => JobKind::RefreshMetrics,
+            JobType::CheckTokenHealth => JobKind::CheckTokenHealth,
+            JobType::BackupCurrent => JobKind::BackupCurrent,
+            JobType::TriggerQuotaRefresh => JobKind::TriggerQuotaRefresh,
+            JobType::PruneSnapshots => JobKind::PruneSnapshots {
+                keep_days: job
+                    .keep_days
+                    .ok_or_else(|| "prune_snapshots 任务缺少 keepDays".to_string())?,
+            },
+            JobType::AlignQuotaWindows => JobKind::AlignQuotaWindows,
+        };
+        Ok(Self {
+            id: job.id,
+            enabled: job.enabled,
+            schedule: job.schedule,
+            missed_run: job.missed_run.into(),
+            job: kind,
+        })
+    }
+}
+
+/// 定时任务设置
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SchedulerSettings {
+    pub enabled: bool,
+    pub jobs: Vec<ScheduledJob>,
+}
+
+impl From<crate::app_settings::SchedulerSettings> for SchedulerSettings {
+    fn from(scheduler: crate::app_settings::SchedulerSettings) -> Self {
+        Self {
+            enabled: scheduler.enabled,
+            jobs: scheduler.jobs.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl TryFrom<SchedulerSettings> for crate::app_settings::SchedulerSettings {
+    type Error = String;
+
+    fn try_from(scheduler: SchedulerSettings) -> Result<Self, String> {
+        let jobs = scheduler
+            .jobs
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<_>, _>>()?;
+        scheduler::validate_jobs(&jobs)?;
+        Ok(Self {
+            enabled: scheduler.enabled,
+            jobs,
+        })
+    }
+}
+
+/// 配额重置窗口对齐设置
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaAlignmentSettings {
+    /// 参与对齐的模型（显示名称，如 `Claude`）
+    pub models: Vec<String>,
+    /// 配额窗口时长（分钟）
+    pub window_minutes: u32,
+    /// 窗口开启的锚点时刻（`HH:MM`，本地时间），账户按邮箱排序后轮流使用
+    pub anchors: Vec<String>,
+    /// 为 true 时只使用第一个锚点，把窗口时长平均错开分配给所有账户
+    pub stagger: bool,
+}
+
+impl From<crate::app_settings::QuotaAlignmentSettings> for QuotaAlignmentSettings {
+    fn from(settings: crate::app_settings::QuotaAlignmentSettings) -> Self {
+        Self {
+            models: settings.models,
+            window_minutes: settings.window_minutes,
+            anchors: settings.anchors,
+            stagger: settings.stagger,
+        }
+    }
+}
+
+impl From<QuotaAlignmentSettings> for crate::app_settings::QuotaAlignmentSettings {
+    fn from(settings: QuotaAlignmentSettings) -> Self {
+        Self {
+            models: settings.models,
+            window_minutes: settings.window_minutes,
+            anchors: settings.anchors,
+            stagger: settings.stagger,
+        }
+    }
+}
+
+/// 应用设置
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Settings {
+    pub system_tray_enabled: bool,
+    pub silent_start_enabled: bool,
+    pub debug_mode: bool,
+    pub private_mode: bool,
+    pub language: String,
+    pub network: NetworkSettings,
+    pub server: ServerSettings,
+    pub notifications: NotificationSettings,
+    pub scheduler: SchedulerSettings,
+    pub quota_alignment: QuotaAlignmentSettings,
+}
+
+impl From<crate::app_settings::AppSettings> for Settings {
+    fn from(settings: crate::app_settings::AppSettings) -> Self {
+        Self {
+            system_tray_enabled: settings.system_tray_enabled,
+            silent_start_enabled: settings.silent_start_enabled,
+            debug_mode: settings.debug_mode,
+            private_mode: settings.private_mode,
+            language: settings.language,
+            network: settings.network.into(),
+            server: settings.server.into(),
+            notifications: settings.notifications.into(),
+            scheduler: settings.scheduler.into(),
+            quota_alignment: settings.quota_alignment.into(),
+        }
+    }
+}
+
+impl TryFrom<Settings> for crate::app_settings::AppSettings {
+    type Error = String;
+
+    fn try_from(settings: Settings) -> Result<Self, String> {
+        Ok(Self {
+            system_tray_enabled: settings.system_tray_enabled,
+            silent_start_enabled: settings.silent_start_enabled,
+            debug_mode: settings.debug_mode,
+            private_mode: settings.private_mode,
+            language: settings.language,
+            network: settings.network.into(),
+            server: settings.server.into(),
+            notifications: settings.notifications.try_into()?,
+            scheduler: settings.scheduler.try_into()?,
+            quota_alignment: settings.quota_alignment.into(),
+            ..Default::default()
+        })
+    }
+}
+
+/// 修改后的设置
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsUpdated {
+    #[serde(flatten)]
+    pub settings: Settings,
+    /// 设置已保存、但未能立即生效时的原因；此时无需重试
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+/// 部分设置：[`Settings`] 的任意字段子集，对象逐字段合并，其他值（包括数组）整体替换
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(value_type = Object)]
+pub struct SettingsPatch(pub Value);
+
+// =============================================================================
+// 加密
+// =============================================================================
+
+/// Argon2id 参数
+#[derive(Debug, Clone, Copy, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct KdfParams {
+    /// 内存开销（KB）
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+impl From<KdfParams> for crate::security::crypto::KdfParams {
+    fn from(params: KdfParams) -> Self {
+        Self {
+            m_cost: params.m_cost,
+            t_cost: params.t_cost,
+            p_cost: params.p_cost,
+        }
+    }
+}
+
+/// 密码加密
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptRequest {
+    /// 明文
+    pub data: String,
+    pub password: String,
+    /// 密钥文件路径（第二因子）
+    #[serde(default)]
+    pub keyfile_path: Option<String>,
+    /// 明文存储但参与认证的关联数据
+    #[serde(default)]
+    pub associated_data: Option<String>,
+    /// 自定义 Argon2 参数
+    #[serde(default)]
+    pub kdf: Option<KdfParams>,
+}
+
+/// 密码解密
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DecryptRequest {
+    /// 密文
+    pub data: String,
+    pub password: String,
+    #[serde(default)]
+    pub keyfile_path: Option<String>,
+}
+
+/// 按当前格式重新加密
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReencryptRequest {
+    pub data: String,
+    pub password: String,
+    /// 密钥文件路径（解密原数据和加密新数据都会使用）
+    #[serde(default)]
+    pub keyfile_path: Option<String>,
+    /// 新的关联数据，不传则沿用原数据中的关联数据
+    #[serde(default)]
+    pub associated_data: Option<String>,
+    /// 自定义 Argon2 参数
+    #[serde(default)]
+    pub kdf: Option<KdfParams>,
+}
+
+/// 加密数据格式
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum EncryptedFormat {
+    V2,
+    V1,
+    LegacyXor,
+}
+
+impl From<crate::security::crypto::EncryptedFormat> for EncryptedFormat {
+    fn from(format: crate::security::crypto::EncryptedFormat) -> Self {
+        use crate::security::crypto::EncryptedFormat as F;
+        match format {
+            F::V2 => Self::V2,
+            F::V1 => Self::V1,
+            F::LegacyXor => Self::LegacyXor,
+        }
+    }
+}
+
+/// 加密 / 解密结果
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CryptoResult {
+    pub result: String,
+}
+
+/// 解密结果
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DecryptResponse {
+    pub result: String,
+    pub format: EncryptedFormat,
+    pub associated_data: String,
+    /// 是否为应当重新加密的旧格式
+    pub needs_reencrypt: bool,
+}
+
+/// 重新加密结果
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReencryptResponse {
+    pub result: String,
+    pub previous_format: EncryptedFormat,
+}
+
+/// 本机公钥
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyResponse {
+    /// age 公钥（age1...）
+    pub public_key: String,
+}
+
+/// 按接收者公钥加密
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RecipientEncryptRequest {
+    pub data: String,
+    /// 接收者公钥（age1...）
+    pub recipients: Vec<String>,
+    /// 同时加密给本机
+    #[serde(default = "default_true")]
+    pub include_self: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 用本机私钥解密
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RecipientDecryptRequest {
+    pub data: String,
+}
+
+// =============================================================================
+// 系统
+// =============================================================================
+
+/// 托盘菜单文案
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TrayMenuLabels {
+    pub show_main: String,
+    pub quit: String,
+    pub save_current: Option<String>,
+    pub sign_in_new: Option<String>,
+    pub refresh_quotas: Option<String>,
+    /// 没有订阅信息的账户所在分组
+    pub other_accounts: Option<String>,
+}
+
+/// 更新托盘菜单（账户列表由后端读取，旧客户端传入的 `accounts` 会被忽略）
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TrayMenuRequest {
+    #[serde(default)]
+    pub labels: Option<TrayMenuLabels>,
+}
+
+impl From<TrayMenuLabels> for crate::system_tray::TrayMenuLabels {
+    fn from(labels: TrayMenuLabels) -> Self {
+        let defaults = Self::default();
+        Self {
+            show_main: labels.show_main,
+            quit: labels.quit,
+            save_current: labels.save_current.unwrap_or(defaults.save_current),
+            sign_in_new: labels.sign_in_new.unwrap_or(defaults.sign_in_new),
+            refresh_quotas: labels.refresh_quotas.unwrap_or(defaults.refresh_quotas),
+            other_accounts: labels.other_accounts.unwrap_or(defaults.other_accounts),
+        }
+    }
+}
+
+/// 写入文本文件
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct WriteFileRequest {
+    pub path: String,
+    pub content: String,
+}
+
+/// 前端日志
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FrontendLogRequest {
+    /// `info` / `warn` / `error` / `debug`
+    #[serde(default)]
+    pub level: Option<String>,
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub module: Option<String>,
+    /// 附加信息（JSON 字符串）
+    #[serde(default)]
+    pub details: Option<String>,
+}
+
+/// 日志目录
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LogDirectory {
+    pub path: String,
+}
+
+/// 下载并安装扩展
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct InstallExtensionRequest {
+    /// VSIX 下载地址
+    pub url: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_camelize_keys() {
+        let value = serde_json::json!({
+            "has_access_token": true,
+            "models": [{ "model_names": ["a"], "field_5": 1 }],
+            "_private": null
+        });
+        assert_eq!(
+            camelize_keys(value),
+            serde_json::json!({
+                "hasAccessToken": true,
+                "models": [{ "modelNames": ["a"], "field5": 1 }],
+                "_private": null
+            })
+        );
+    }
+
+    #[test]
+    fn test_settings_patch_round_trip() {
+        use crate::app_settings::{merge_json, AppSettings};
+
+        let original = AppSettings::default();
+        let mut document = serde_json::to_value(Settings::from(original.clone())).unwrap();
+        merge_json(&mut document, serde_json::json!({ "debugMode": true }));
+        let patched: AppSettings = serde_json::from_value::<Settings>(document.clone())
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert!(patched.debug_mode);
+        assert_eq!(
+            serde_json::to_value(AppSettings {
+                debug_mode: false,
+                ..patched
+            })
+            .unwrap(),
+            serde_json::to_value(original).unwrap()
+        );
+
+        merge_json(&mut document, serde_json::json!({ "debug_mode": true }));
+        assert!(serde_json::from_value::<Settings>(document).is_err());
+    }
+}
+
+// =============================================================================
+// 定时任务
+// =============================================================================
+
+/// 触发方式
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RunTrigger {
+    /// 按计划时间执行
+    Scheduled,
+    /// 错过计划时间后补跑
+    CatchUp,
+    /// 手动执行
+    Manual,
+}
+
+impl From<scheduler::RunTrigger> for RunTrigger {
+    fn from(trigger: scheduler::RunTrigger) -> Self {
+        match trigger {
+            scheduler::RunTrigger::Scheduled => Self::Scheduled,
+            scheduler::RunTrigger::CatchUp => Self::CatchUp,
+            scheduler::RunTrigger::Manual => Self::Manual,
+        }
+    }
+}
+
+/// 运行结果
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Succeeded,
+    Failed,
+    /// 错过计划时间且策略为跳过
+    Skipped,
+}
+
+impl From<scheduler::RunStatus> for RunStatus {
+    fn from(status: scheduler::RunStatus) -> Self {
+        match status {
+            scheduler::RunStatus::Succeeded => Self::Succeeded,
+            scheduler::RunStatus::Failed => Self::Failed,
+            scheduler::RunStatus::Skipped => Self::Skipped,
+        }
+    }
+}
+
+/// 定时任务运行记录
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RunRecord {
+    pub job_id: String,
+    pub trigger: RunTrigger,
+    pub status: RunStatus,
+    /// 开始时间（RFC 3339）
+    pub started_at: String,
+    pub duration_ms: u64,
+    /// 执行结果或错误信息
+    pub message: String,
+}
+
+impl From<scheduler::RunRecord> for RunRecord {
+    fn from(record: scheduler::RunRecord) -> Self {
+        Self {
+            job_id: record.job_id,
+            trigger: record.trigger.into(),
+            status: record.status.into(),
+            started_at: record.started_at,
+            duration_ms: record.duration_ms,
+            message: record.message,
+        }
+    }
+}
+
+/// 定时任务的计划与最近一次运行情况
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct JobStatus {
+    pub job: ScheduledJob,
+    /// 下一次计划时间（RFC 3339），任务未启用或表达式无效时为空
+    pub next_run: Option<String>,
+    pub last_run: Option<RunRecord>,
+    /// 是否正在执行
+    pub running: bool,
+}
+
+impl From<scheduler::JobStatus> for JobStatus {
+    fn from(status: scheduler::JobStatus) -> Self {
+        Self {
+            job: status.job.into(),
+            next_run: status.next_run,
+            last_run: status.last_run.map(Into::into),
+            running: status.running,
+        }
+    }
+}
+
+/// 定时任务状态
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SchedulerStatus {
+    /// 调度总开关
+    pub enabled: bool,
+    pub jobs: Vec<JobStatus>,
+    /// 最近的运行记录（最新的在前）
+    pub history: Vec<RunRecord>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SchedulerQuery {
+    /// 返回的运行记录条数（默认 50）
+    #[serde(default = "default_history_limit")]
+    pub limit: usize,
+}
+
+fn default_history_limit() -> usize {
+    50
+}
+
+// =============================================================================
+// 实例
+// =============================================================================
+
+/// 转发给正在运行的实例的启动命令
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum InstanceCommand {
+    /// 显示主窗口
+    Show,
+    /// 切换到指定账户
+    Switch { email: String },
+    /// 保存当前登录的账户
+    SaveCurrent,
+    /// 打开 `antigravity-agent://` 深链接，会修改账户的操作需用户在弹窗中确认
+    OpenUrl { url: String },
+}
+
+impl From<InstanceCommand> for launch::LaunchCommand {
+    fn from(command: InstanceCommand) -> Self {
+        match command {
+            InstanceCommand::Show => Self::Show,
+            InstanceCommand::Switch { email } => Self::Switch { email },
+            InstanceCommand::SaveCurrent => Self::SaveCurrent,
+            InstanceCommand::OpenUrl { url } => Self::OpenUrl { url },
+        }
+    }
+}
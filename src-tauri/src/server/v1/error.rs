@@ -0,0 +1,114 @@
+//! v1 接口的错误响应
+//!
+//! 所有失败响应的格式一致：
+//!
+//! ```json
+//! { "error": { "code": "invalid_request", "message": "..." } }
+//! ```
+
+use actix_web::http::StatusCode;
+use actix_web::{HttpRequest, HttpResponse, ResponseError};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// 错误响应
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorBody {
+    pub error: ErrorDetail,
+}
+
+/// 错误详情
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorDetail {
+    /// 机器可读的错误码：`invalid_request` / `unauthorized` / `not_found` / `internal_error`
+    pub code: String,
+    /// 错误说明
+    pub message: String,
+}
+
+/// v1 接口错误
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+impl ApiError {
+    /// 请求参数无效
+    pub fn invalid_request(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            code: "invalid_request",
+            message: message.into(),
+        }
+    }
+
+    /// 缺少或携带了错误的实例 token
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::UNAUTHORIZED,
+            code: "unauthorized",
+            message: message.into(),
+        }
+    }
+
+    /// 资源不存在
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            code: "not_found",
+            message: message.into(),
+        }
+    }
+
+    /// 服务端处理失败
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            code: "internal_error",
+            message: message.into(),
+        }
+    }
+}
+
+/// 服务层错误（`Result<_, String>`）默认视为服务端处理失败
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        Self::internal(message)
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status).json(ErrorBody {
+            error: ErrorDetail {
+                code: self.code.to_string(),
+                message: self.message.clone(),
+            },
+        })
+    }
+}
+
+/// 请求体 / 查询参数 / 路径参数解析失败时返回统一的错误格式
+pub fn invalid_request_handler<E: std::fmt::Display>(
+    err: E,
+    _req: &HttpRequest,
+) -> actix_web::Error {
+    ApiError::invalid_request(err.to_string()).into()
+}
+
+/// v1 下未匹配的路径
+pub async fn not_found(req: HttpRequest) -> HttpResponse {
+    ApiError::not_found(format!("未知的接口: {} {}", req.method(), req.path())).error_response()
+}
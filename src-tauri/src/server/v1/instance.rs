@@ -0,0 +1,45 @@
+//! `/api/v1/instance`：单实例命令转发
+//!
+//! 再次启动应用时，新进程把命令行参数转发到这里由当前实例执行（见 [`crate::services::launch`]）。
+//! 请求需携带 `Authorization: Bearer <token>`，token 位于配置目录中权限为 0600 的 `server.token`。
+
+use actix_web::{post, web, HttpRequest};
+use tauri::AppHandle;
+use utoipa::OpenApi;
+
+use super::dto::{InstanceCommand, MessageResponse};
+use super::error::ApiError;
+use super::ApiResult;
+use crate::server::{discovery, middleware};
+use crate::services::launch;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(run_command);
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(run_command))]
+pub struct Api;
+
+/// 执行另一个进程转发的启动命令
+#[utoipa::path(
+    tag = "system",
+    request_body = InstanceCommand,
+    responses(
+        (status = 200, body = MessageResponse),
+        (status = 401, description = "缺少或携带了错误的实例 token", body = super::error::ErrorBody)
+    )
+)]
+#[post("/instance/commands")]
+async fn run_command(
+    http_req: HttpRequest,
+    app: web::Data<AppHandle>,
+    req: web::Json<InstanceCommand>,
+) -> ApiResult<MessageResponse> {
+    if !discovery::verify_token(middleware::bearer_token(http_req.headers())) {
+        return Err(ApiError::unauthorized("实例 token 无效"));
+    }
+
+    let msg = launch::execute(&app, req.into_inner().into()).await?;
+    Ok(web::Json(msg.into()))
+}
@@ -0,0 +1,273 @@
+//! # REST API v1
+//!
+//! `/api/v1` 下按资源组织的接口：
+//!
+//! - 请求与响应均为 camelCase 的类型化 DTO（见 [`dto`]），不经过旧接口的 camelCase -> snake_case 转换
+//! - 失败时统一返回 `{"error": {"code": "...", "message": "..."}}`（见 [`error`]）
+//! - OpenAPI 文档由上述类型生成：`GET /api/v1/openapi.json`
+//!
+//! 旧的 `/api/<command>` 路径作为弃用别名继续可用，响应格式不变，但会附带
+//! `Deprecation: true` 和指向新路径的 `Link` 响应头，对应关系见 [`LEGACY_ALIASES`]。
+
+use actix_web::{get, web, HttpResponse};
+use std::path::PathBuf;
+use utoipa::openapi::{ContentBuilder, Ref, ResponseBuilder};
+use utoipa::OpenApi;
+
+use crate::AppState;
+use error::{ApiError, ErrorBody};
+
+mod accounts;
+mod antigravity;
+mod backups;
+mod crypto;
+pub mod dto;
+pub mod error;
+mod instance;
+mod scheduler;
+mod settings;
+mod system;
+
+/// v1 接口路径前缀
+pub const PREFIX: &str = "/api/v1";
+
+/// v1 接口的返回类型
+pub type ApiResult<T> = Result<web::Json<T>, ApiError>;
+
+/// 旧接口路径与对应的 v1 路径
+#[rustfmt::skip]
+pub const LEGACY_ALIASES: &[(&str, &str)] = &[
+    ("/api/is_antigravity_running", "/api/v1/antigravity/status"),
+    ("/api/get_antigravity_accounts", "/api/v1/accounts"),
+    ("/api/get_current_antigravity_account_info", "/api/v1/accounts/current"),
+    ("/api/save_antigravity_current_account", "/api/v1/accounts/current/save"),
+    ("/api/restore_antigravity_account", "/api/v1/accounts/{email}/restore"),
+    ("/api/switch_to_antigravity_account", "/api/v1/accounts/{email}/switch"),
+    ("/api/clear_all_antigravity_data", "/api/v1/antigravity/clear"),
+    ("/api/undo_last_operation", "/api/v1/antigravity/snapshots/undo"),
+    ("/api/list_state_snapshots", "/api/v1/antigravity/snapshots"),
+    ("/api/sign_in_new_antigravity_account", "/api/v1/accounts/sign-in"),
+    ("/api/get_account_metrics", "/api/v1/accounts/{email}/metrics"),
+    ("/api/get_all_account_metrics", "/api/v1/accounts/metrics"),
+    ("/api/trigger_quota_refresh", "/api/v1/accounts/{email}/quota-refresh"),
+    ("/api/account_health", "/api/v1/accounts/health"),
+    ("/api/collect_account_contents", "/api/v1/backups"),
+    ("/api/restore_backup_files", "/api/v1/backups/restore"),
+    ("/api/import_accounts", "/api/v1/accounts/import"),
+    ("/api/export_bundle", "/api/v1/bundles/export"),
+    ("/api/import_bundle", "/api/v1/bundles/import"),
+    ("/api/delete_backup", "/api/v1/backups/{name}"),
+    ("/api/clear_all_backups", "/api/v1/backups"),
+    ("/api/get_all_settings", "/api/v1/settings"),
+    ("/api/get_language", "/api/v1/settings"),
+    ("/api/get_platform_info", "/api/v1/platform"),
+    ("/api/find_antigravity_installations", "/api/v1/antigravity/installations"),
+    ("/api/validate_antigravity_executable", "/api/v1/antigravity/executable/validate"),
+    ("/api/detect_antigravity_installation", "/api/v1/antigravity/installation"),
+    ("/api/detect_antigravity_executable", "/api/v1/antigravity/executable"),
+    ("/api/save_antigravity_executable", "/api/v1/antigravity/executable"),
+    ("/api/get_current_paths", "/api/v1/antigravity/executable/custom"),
+    ("/api/encrypt_config_data", "/api/v1/crypto/encrypt"),
+    ("/api/decrypt_config_data", "/api/v1/crypto/decrypt"),
+    ("/api/reencrypt_config_data", "/api/v1/crypto/reencrypt"),
+    ("/api/get_recipient_public_key", "/api/v1/crypto/public-key"),
+    ("/api/encrypt_for_recipients", "/api/v1/crypto/recipients/encrypt"),
+    ("/api/decrypt_with_local_key", "/api/v1/crypto/recipients/decrypt"),
+    ("/api/update_tray_menu_command", "/api/v1/tray/menu"),
+    ("/api/minimize_to_tray", "/api/v1/tray/minimize"),
+    ("/api/restore_from_tray", "/api/v1/tray/restore"),
+    ("/api/is_database_monitoring_running", "/api/v1/db-monitor"),
+    ("/api/start_database_monitoring", "/api/v1/db-monitor/start"),
+    ("/api/stop_database_monitoring", "/api/v1/db-monitor/stop"),
+    ("/api/write_text_file", "/api/v1/files"),
+    ("/api/write_frontend_log", "/api/v1/logs"),
+    ("/api/get_log_directory_path", "/api/v1/logs/directory"),
+    ("/api/open_log_directory", "/api/v1/logs/directory/open"),
+    ("/api/launch_and_install_extension", "/api/v1/extension/install"),
+    ("/api/events", "/api/v1/events"),
+];
+
+/// 旧接口对应的 v1 路径
+pub fn successor_of(legacy_path: &str) -> Option<&'static str> {
+    LEGACY_ALIASES
+        .iter()
+        .find(|(legacy, _)| *legacy == legacy_path)
+        .map(|(_, successor)| *successor)
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Antigravity Agent API",
+        description = "Antigravity Agent 本地 HTTP 接口（仅监听 127.0.0.1）"
+    ),
+    servers((url = "/api/v1")),
+    paths(openapi_json, events),
+    components(schemas(ErrorBody)),
+    tags(
+        (name = "accounts", description = "已保存的账户、配额指标与 token 健康状态"),
+        (name = "antigravity", description = "Antigravity 进程、安装路径与状态快照"),
+        (name = "backups", description = "账户文件备份、恢复与 bundle 导入导出"),
+        (name = "settings", description = "应用设置"),
+        (name = "scheduler", description = "定时任务状态与手动执行"),
+        (name = "crypto", description = "导出数据加密"),
+        (name = "system", description = "托盘、数据库监控、日志、扩展安装与单实例命令转发"),
+        (name = "meta", description = "接口文档与事件订阅")
+    )
+)]
+struct ApiDoc;
+
+/// 生成完整的 OpenAPI 文档
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    let mut doc = ApiDoc::openapi();
+    for api in [
+        accounts::Api::openapi(),
+        antigravity::Api::openapi(),
+        backups::Api::openapi(),
+        settings::Api::openapi(),
+        scheduler::Api::openapi(),
+        crypto::Api::openapi(),
+        system::Api::openapi(),
+        instance::Api::openapi(),
+    ] {
+        doc.merge(api);
+    }
+    add_error_responses(&mut doc);
+    doc
+}
+
+/// 为所有接口补充统一的错误响应
+fn add_error_responses(doc: &mut utoipa::openapi::OpenApi) {
+    let error_response = |description: &str| {
+        ResponseBuilder::new()
+            .description(description)
+            .content(
+                "application/json",
+                ContentBuilder::new()
+                    .schema(Some(Ref::from_schema_name("ErrorBody")))
+                    .build(),
+            )
+            .build()
+    };
+
+    for item in doc.paths.paths.values_mut() {
+        let operations = [
+            &mut item.get,
+            &mut item.put,
+            &mut item.post,
+            &mut item.delete,
+            &mut item.patch,
+        ];
+        for operation in operations.into_iter().flatten() {
+            let responses = &mut operation.responses.responses;
+            responses
+                .entry("400".to_string())
+                .or_insert_with(|| error_response("请求参数无效").into());
+            responses
+                .entry("500".to_string())
+                .or_insert_with(|| error_response("处理失败").into());
+        }
+    }
+}
+
+/// 注册 v1 接口
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope(PREFIX)
+            .app_data(web::JsonConfig::default().error_handler(error::invalid_request_handler))
+            .app_data(web::QueryConfig::default().error_handler(error::invalid_request_handler))
+            .app_data(web::PathConfig::default().error_handler(error::invalid_request_handler))
+            .service(openapi_json)
+            .service(events)
+            .configure(accounts::configure)
+            .configure(antigravity::configure)
+            .configure(backups::configure)
+            .configure(settings::configure)
+            .configure(scheduler::configure)
+            .configure(crypto::configure)
+            .configure(system::configure)
+            .configure(instance::configure)
+            .default_service(web::to(error::not_found)),
+    );
+}
+
+/// 读取应用配置目录
+fn config_dir(data: &AppState) -> PathBuf {
+    let state = data.inner.lock();
+    state.config_dir.clone()
+}
+
+/// OpenAPI 文档
+#[utoipa::path(
+    tag = "meta",
+    responses((status = 200, description = "OpenAPI 3.1 文档", content_type = "application/json"))
+)]
+#[get("/openapi.json")]
+async fn openapi_json() -> HttpResponse {
+    HttpResponse::Ok().json(openapi())
+}
+
+/// 以 Server-Sent Events 推送事件总线上的事件（`event` 为事件名，`data` 为 JSON）
+#[utoipa::path(
+    tag = "meta",
+    responses((status = 200, description = "事件流", content_type = "text/event-stream", body = String))
+)]
+#[get("/events")]
+async fn events() -> HttpResponse {
+    super::sse::events_handler().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::{
+        call_and_read_body_json, call_service, init_service, read_body_json, TestRequest,
+    };
+    use actix_web::App;
+
+    #[test]
+    fn test_legacy_aliases_point_to_documented_paths() {
+        let doc = openapi();
+        for (legacy, successor) in LEGACY_ALIASES {
+            let path = successor.strip_prefix(PREFIX).unwrap();
+            assert!(
+                doc.paths.paths.contains_key(path),
+                "{} -> {} 不在 OpenAPI 文档中",
+                legacy,
+                successor
+            );
+        }
+        assert_eq!(
+            successor_of("/api/get_all_settings"),
+            Some("/api/v1/settings")
+        );
+        assert_eq!(successor_of("/api/v1/settings"), None);
+    }
+
+    #[actix_web::test]
+    async fn test_openapi_and_error_envelope() {
+        let app = init_service(App::new().configure(configure)).await;
+
+        let req = TestRequest::get().uri("/api/v1/openapi.json").to_request();
+        let doc: serde_json::Value = call_and_read_body_json(&app, req).await;
+        assert!(doc["paths"]["/accounts/{email}/switch"]["post"].is_object());
+        assert!(doc["components"]["schemas"]["AccountMetrics"].is_object());
+
+        let req = TestRequest::get().uri("/api/v1/unknown").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), actix_web::http::StatusCode::NOT_FOUND);
+        let body: serde_json::Value = read_body_json(res).await;
+        assert_eq!(body["error"]["code"], "not_found");
+
+        // 请求体解析失败同样返回统一的错误格式
+        let req = TestRequest::post()
+            .uri("/api/v1/crypto/recipients/decrypt")
+            .insert_header(("Content-Type", "application/json"))
+            .set_payload("{")
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), actix_web::http::StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = read_body_json(res).await;
+        assert_eq!(body["error"]["code"], "invalid_request");
+    }
+}
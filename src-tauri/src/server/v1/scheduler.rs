@@ -0,0 +1,59 @@
+//! `/api/v1/scheduler`：定时任务状态与手动执行
+//!
+//! 任务配置通过 `PATCH /api/v1/settings` 的 `scheduler` 字段修改。
+
+use actix_web::{get, post, web};
+use tauri::{AppHandle, Manager};
+use utoipa::OpenApi;
+
+use super::dto::{RunRecord, SchedulerQuery, SchedulerStatus};
+use super::error::ApiError;
+use super::ApiResult;
+use crate::services::scheduler;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_status).service(run_job);
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(get_status, run_job))]
+pub struct Api;
+
+/// 所有任务的下一次计划时间、最近一次运行结果以及运行记录
+#[utoipa::path(
+    tag = "scheduler",
+    params(SchedulerQuery),
+    responses((status = 200, body = SchedulerStatus))
+)]
+#[get("/scheduler")]
+async fn get_status(
+    app: web::Data<AppHandle>,
+    query: web::Query<SchedulerQuery>,
+) -> ApiResult<SchedulerStatus> {
+    let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+    Ok(web::Json(SchedulerStatus {
+        enabled: settings_manager.get_settings().scheduler.enabled,
+        jobs: scheduler::job_statuses(&app)
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+        history: scheduler::history(query.limit)
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+    }))
+}
+
+/// 立即执行指定任务并返回运行记录（不影响计划时间）
+#[utoipa::path(
+    tag = "scheduler",
+    params(("id" = String, Path, description = "任务 ID")),
+    responses((status = 200, body = RunRecord))
+)]
+#[post("/scheduler/jobs/{id}/run")]
+async fn run_job(app: web::Data<AppHandle>, id: web::Path<String>) -> ApiResult<RunRecord> {
+    let job = scheduler::find_job(&app, &id)
+        .ok_or_else(|| ApiError::not_found(format!("任务不存在: {}", id)))?;
+    let record = scheduler::run_now(&app, &job).await?;
+    Ok(web::Json(record.into()))
+}
@@ -0,0 +1,75 @@
+//! `/api/v1/settings`：应用设置
+//!
+//! `PATCH /settings` 可一次修改任意字段，返回修改后的完整设置；设置已保存但未能立即生效时
+//! 仍返回 200，并在 `warning` 中说明原因。
+
+use actix_web::{get, patch, web};
+use tauri::{AppHandle, Manager};
+use utoipa::OpenApi;
+
+use super::dto::{Settings, SettingsPatch, SettingsUpdated};
+use super::error::ApiError;
+use super::ApiResult;
+use crate::app_settings::{AppSettings, AppSettingsManager};
+use crate::services::settings;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_settings).service(patch_settings);
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(get_settings, patch_settings))]
+pub struct Api;
+
+fn current(app: &AppHandle) -> web::Json<Settings> {
+    let settings_manager = app.state::<AppSettingsManager>();
+    web::Json(settings_manager.get_settings().into())
+}
+
+/// 当前设置
+#[utoipa::path(tag = "settings", responses((status = 200, body = Settings)))]
+#[get("/settings")]
+async fn get_settings(app: web::Data<AppHandle>) -> ApiResult<Settings> {
+    Ok(current(&app))
+}
+
+/// 修改任意设置
+///
+/// 请求体为设置的任意部分，与当前设置合并后整体校验，校验通过才会保存；托盘、Debug 模式
+/// （日志级别）、网络设置的变化立即生效，并广播 `settings_changed` 事件。未能立即生效时
+/// 设置仍已保存，响应中带 `warning`。
+#[utoipa::path(
+    tag = "settings",
+    request_body = SettingsPatch,
+    responses((status = 200, body = SettingsUpdated))
+)]
+#[patch("/settings")]
+async fn patch_settings(
+    app: web::Data<AppHandle>,
+    req: web::Json<SettingsPatch>,
+) -> ApiResult<SettingsUpdated> {
+    let patch = req.into_inner().0;
+    if !patch.is_object() {
+        return Err(ApiError::invalid_request("请求体应为 JSON 对象"));
+    }
+
+    // 在设置事务内与当前设置合并，避免并发修改被覆盖
+    let updated = settings::update_with(&app, |current| {
+        let mut document = serde_json::to_value(Settings::from(current.clone()))
+            .map_err(|e| format!("序列化设置失败: {}", e))?;
+        crate::app_settings::merge_json(&mut document, patch);
+
+        let patched: Settings = serde_json::from_value(document)
+            .map_err(|e| ApiError::invalid_request(format!("设置无效: {}", e)))?;
+        let patched: AppSettings = patched.try_into().map_err(ApiError::invalid_request)?;
+        let patched = settings::validate(patched).map_err(ApiError::invalid_request)?;
+        Ok::<_, ApiError>(AppSettings {
+            schema_version: current.schema_version,
+            ..patched
+        })
+    })?;
+    Ok(web::Json(SettingsUpdated {
+        settings: updated.settings.into(),
+        warning: updated.warning,
+    }))
+}
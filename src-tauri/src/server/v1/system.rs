@@ -0,0 +1,158 @@
+//! `/api/v1` 系统接口：托盘、数据库监控、日志与扩展安装
+
+use actix_web::{get, post, put, web, HttpResponse};
+use tauri::AppHandle;
+use utoipa::OpenApi;
+
+use super::dto::{
+    FrontendLogRequest, InstallExtensionRequest, LogDirectory, MessageResponse, RunningStatus,
+    TrayMenuRequest, WriteFileRequest,
+};
+use super::error::ApiError;
+use super::ApiResult;
+use crate::services::system::{db_monitor, extension, logging, tray};
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(update_tray_menu)
+        .service(minimize_to_tray)
+        .service(restore_from_tray)
+        .service(db_monitor_status)
+        .service(start_db_monitor)
+        .service(stop_db_monitor)
+        .service(write_frontend_log)
+        .service(log_directory)
+        .service(open_log_directory)
+        .service(write_text_file)
+        .service(install_extension);
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    update_tray_menu,
+    minimize_to_tray,
+    restore_from_tray,
+    db_monitor_status,
+    start_db_monitor,
+    stop_db_monitor,
+    write_frontend_log,
+    log_directory,
+    open_log_directory,
+    write_text_file,
+    install_extension
+))]
+pub struct Api;
+
+/// 更新托盘菜单文案并重建菜单
+#[utoipa::path(
+    tag = "system",
+    request_body = TrayMenuRequest,
+    responses((status = 200, body = MessageResponse))
+)]
+#[put("/tray/menu")]
+async fn update_tray_menu(
+    app: web::Data<AppHandle>,
+    req: web::Json<TrayMenuRequest>,
+) -> ApiResult<MessageResponse> {
+    let req = req.into_inner();
+    let msg = tray::update_menu(&app, req.labels.map(Into::into)).await?;
+    Ok(web::Json(msg.into()))
+}
+
+/// 隐藏主窗口到托盘
+#[utoipa::path(tag = "system", responses((status = 200, body = MessageResponse)))]
+#[post("/tray/minimize")]
+async fn minimize_to_tray(app: web::Data<AppHandle>) -> ApiResult<MessageResponse> {
+    Ok(web::Json(tray::minimize(&app).await?.into()))
+}
+
+/// 从托盘恢复主窗口
+#[utoipa::path(tag = "system", responses((status = 200, body = MessageResponse)))]
+#[post("/tray/restore")]
+async fn restore_from_tray(app: web::Data<AppHandle>) -> ApiResult<MessageResponse> {
+    Ok(web::Json(tray::restore(&app).await?.into()))
+}
+
+/// 数据库监控是否运行
+#[utoipa::path(tag = "system", responses((status = 200, body = RunningStatus)))]
+#[get("/db-monitor")]
+async fn db_monitor_status(app: web::Data<AppHandle>) -> ApiResult<RunningStatus> {
+    Ok(web::Json(RunningStatus {
+        running: db_monitor::is_running(&app).await?,
+    }))
+}
+
+/// 启动数据库监控
+#[utoipa::path(tag = "system", responses((status = 200, body = MessageResponse)))]
+#[post("/db-monitor/start")]
+async fn start_db_monitor(app: web::Data<AppHandle>) -> ApiResult<MessageResponse> {
+    Ok(web::Json(db_monitor::start(&app).await?.into()))
+}
+
+/// 停止数据库监控
+#[utoipa::path(tag = "system", responses((status = 200, body = MessageResponse)))]
+#[post("/db-monitor/stop")]
+async fn stop_db_monitor(app: web::Data<AppHandle>) -> ApiResult<MessageResponse> {
+    Ok(web::Json(db_monitor::stop(&app).await?.into()))
+}
+
+/// 写入一条前端日志
+#[utoipa::path(
+    tag = "system",
+    request_body = FrontendLogRequest,
+    responses((status = 204, description = "已写入"))
+)]
+#[post("/logs")]
+async fn write_frontend_log(req: web::Json<FrontendLogRequest>) -> Result<HttpResponse, ApiError> {
+    let entry =
+        serde_json::to_value(req.into_inner()).map_err(|e| ApiError::internal(e.to_string()))?;
+    logging::write_frontend_log(entry).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// 日志目录
+#[utoipa::path(tag = "system", responses((status = 200, body = LogDirectory)))]
+#[get("/logs/directory")]
+async fn log_directory() -> ApiResult<LogDirectory> {
+    Ok(web::Json(LogDirectory {
+        path: logging::get_directory_path().await?,
+    }))
+}
+
+/// 在文件管理器中打开日志目录
+#[utoipa::path(tag = "system", responses((status = 204, description = "已打开")))]
+#[post("/logs/directory/open")]
+async fn open_log_directory() -> Result<HttpResponse, ApiError> {
+    logging::open_directory().await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// 写入文本文件（用于前端保存导出内容）
+#[utoipa::path(
+    tag = "system",
+    request_body = WriteFileRequest,
+    responses((status = 200, body = MessageResponse))
+)]
+#[post("/files")]
+async fn write_text_file(req: web::Json<WriteFileRequest>) -> ApiResult<MessageResponse> {
+    let req = req.into_inner();
+    Ok(web::Json(
+        logging::write_text_file(req.path, req.content)
+            .await?
+            .into(),
+    ))
+}
+
+/// 下载 VSIX 并安装到 Antigravity
+#[utoipa::path(
+    tag = "system",
+    request_body = InstallExtensionRequest,
+    responses((status = 200, body = MessageResponse))
+)]
+#[post("/extension/install")]
+async fn install_extension(req: web::Json<InstallExtensionRequest>) -> ApiResult<MessageResponse> {
+    Ok(web::Json(
+        extension::launch_and_install(req.into_inner().url)
+            .await?
+            .into(),
+    ))
+}
@@ -123,7 +123,7 @@ pub struct RpcResponse {
 /// {"type": "rpc_response", "id": "xxx", "result": null}
 ///
 /// // 事件通知
-/// {"type": "event", "name": "account_changed", "data": {"email": "..."}}
+/// {"type": "event", "name": "account_switched", "data": {"email": "..."}}
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -514,11 +514,12 @@ pub fn call_all_extensions(method: &str, params: Value) {
 /// # 示例
 ///
 /// ```rust,ignore
-/// broadcast_event("account_changed", serde_json::json!({
+/// broadcast_event("account_switched", serde_json::json!({
 ///     "email": "user@example.com"
 /// }));
 /// ```
-#[allow(dead_code)]
+///
+/// 业务代码应通过 `crate::events::publish` 发布事件，由事件总线统一转发到这里。
 pub fn broadcast_event(name: &str, data: Value) {
     CONNECTION_MANAGER.broadcast(WsMessage::Event {
         name: name.to_string(),
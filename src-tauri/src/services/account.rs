@@ -1,7 +1,9 @@
 use crate::antigravity::account::decode_jetski_state_proto;
 use base64::Engine;
 use prost::Message;
-use rusqlite::{Connection, OptionalExtension};
+use crate::antigravity::snapshot;
+use crate::antigravity::state_db::StateDb;
+use crate::constants::database;
 use serde_json::{from_str, Value};
 use std::fs;
 
@@ -81,6 +83,94 @@ pub async fn get_all(config_dir: &std::path::Path) -> Result<Vec<Value>, String>
     }
 }
 
+/// 已保存的账户文件
+pub(crate) struct AccountFile {
+    /// 文件中记录的邮箱（无法解码时退回文件名）
+    pub email: String,
+    pub path: std::path::PathBuf,
+}
+
+/// 读取账户文件中记录的邮箱
+fn read_account_email(path: &std::path::Path) -> Result<String, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("读取账户文件失败: {}", e))?;
+    let json: Value = from_str(&content).map_err(|e| format!("解析账户文件失败: {}", e))?;
+    let state = json
+        .get(database::AGENT_STATE)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| format!("缺少 {} 字段", database::AGENT_STATE))?;
+    crate::antigravity::account::decode_session_response(state)?
+        .context
+        .map(|c| c.email.trim().to_string())
+        .filter(|email| !email.is_empty())
+        .ok_or_else(|| "账户数据中未找到邮箱".to_string())
+}
+
+/// 列出所有已保存的账户文件，每个邮箱一个（按邮箱排序）
+///
+/// 邮箱取自文件中解码出的 proto：文件名不一定是邮箱（例如导入时 rename 策略另存的
+/// `{email}_2.json`）。同一邮箱有多个文件时优先 `{email}.json`。
+pub(crate) fn list_account_files(config_dir: &std::path::Path) -> Result<Vec<AccountFile>, String> {
+    let antigravity_dir = config_dir.join("antigravity-accounts");
+    if !antigravity_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths: Vec<std::path::PathBuf> = fs::read_dir(&antigravity_dir)
+        .map_err(|e| format!("读取账户目录失败: {}", e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    let mut files: std::collections::BTreeMap<String, AccountFile> =
+        std::collections::BTreeMap::new();
+    for path in paths {
+        let Some(stem) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let email = read_account_email(&path).unwrap_or_else(|e| {
+            tracing::warn!(target: "account", file = %path.display(), error = %e, "无法读取账户文件中的邮箱，使用文件名");
+            stem.clone()
+        });
+        // 已有 `{email}.json` 时不被其他文件替换
+        let keep_existing = files.get(&email).is_some_and(|existing| {
+            stem != email || existing.path.file_stem() == Some(std::ffi::OsStr::new(&email))
+        });
+        if !keep_existing {
+            files.insert(email.clone(), AccountFile { email, path });
+        }
+    }
+
+    Ok(files.into_values().collect())
+}
+
+/// 列出所有已保存账户的邮箱（按字母排序）
+pub(crate) fn list_account_emails(config_dir: &std::path::Path) -> Result<Vec<String>, String> {
+    Ok(list_account_files(config_dir)?
+        .into_iter()
+        .map(|file| file.email)
+        .collect())
+}
+
+/// 邮箱对应的账户文件
+pub(crate) fn account_file_path(
+    config_dir: &std::path::Path,
+    email: &str,
+) -> Result<std::path::PathBuf, String> {
+    let path = config_dir
+        .join("antigravity-accounts")
+        .join(format!("{}.json", email));
+    if validate_account_name(email).is_ok() && path.exists() {
+        return Ok(path);
+    }
+    list_account_files(config_dir)?
+        .into_iter()
+        .find(|file| file.email == email)
+        .map(|file| file.path)
+        .ok_or_else(|| format!("账户文件不存在: {}", path.display()))
+}
+
 /// 获取当前 Antigravity 账户信息
 pub async fn get_current() -> Result<Value, String> {
     tracing::info!("开始获取当前 Antigravity 信息");
@@ -88,38 +178,12 @@ pub async fn get_current() -> Result<Value, String> {
     let start_time = std::time::Instant::now();
 
     let result = async {
-        // 尝试获取 Antigravity 状态数据库路径
-        let app_data = match crate::platform::get_antigravity_db_path() {
-            Some(path) => path,
-            None => {
-                // 如果主路径不存在，尝试其他可能的位置
-                let possible_paths = crate::platform::get_all_antigravity_db_paths();
-                if possible_paths.is_empty() {
-                    return Err("未找到Antigravity安装位置".to_string());
-                }
-                possible_paths[0].clone()
-            }
-        };
-
-        if !app_data.exists() {
-            return Err(format!(
-                "Antigravity 状态数据库文件不存在: {}",
-                app_data.display()
-            ));
-        }
-
-        // 连接到 SQLite 数据库并获取认证信息
-        let conn = Connection::open(&app_data)
-            .map_err(|e| format!("连接数据库失败 ({}): {}", app_data.display(), e))?;
+        // 只读打开 Antigravity 状态数据库
+        let db = StateDb::open_default_readonly()?;
 
         // jetski 状态（可选）
-        let jetski_state: Option<String> = conn
-            .query_row(
-                "SELECT value FROM ItemTable WHERE key = 'jetskiStateSync.agentManagerInitState'",
-                [],
-                |row| row.get(0),
-            )
-            .optional()
+        let jetski_state = db
+            .get(database::AGENT_STATE)
             .map_err(|e| format!("查询 jetskiStateSync.agentManagerInitState 失败: {}", e))?;
 
         let state_str = jetski_state
@@ -160,50 +224,19 @@ pub async fn backup_current() -> Result<String, String> {
     let start_time = std::time::Instant::now();
 
     let result = async {
-        // 尝试获取 Antigravity 状态数据库路径
-        let app_data = match crate::platform::get_antigravity_db_path() {
-            Some(path) => path,
-            None => {
-                // 如果主路径不存在，尝试其他可能的位置
-                let possible_paths = crate::platform::get_all_antigravity_db_paths();
-                if possible_paths.is_empty() {
-                    return Err("未找到Antigravity安装位置".to_string());
-                }
-                possible_paths[0].clone()
-            }
-        };
-
-        if !app_data.exists() {
-            return Err(format!(
-                "Antigravity 状态数据库文件不存在: {}",
-                app_data.display()
-            ));
-        }
-
-        // 连接到 SQLite 数据库并获取认证信息
-        let conn = Connection::open(&app_data)
-            .map_err(|e| format!("连接数据库失败 ({}): {}", app_data.display(), e))?;
+        // 只读打开 Antigravity 状态数据库
+        let db = StateDb::open_default_readonly()?;
 
         // jetski 状态（必需）
-        let jetski_state: String = conn
-            .query_row(
-                "SELECT value FROM ItemTable WHERE key = 'jetskiStateSync.agentManagerInitState'",
-                [],
-                |row| row.get(0),
-            )
-            .optional()
+        let jetski_state: String = db
+            .get(database::AGENT_STATE)
             .map_err(|e| format!("查询 jetskiStateSync.agentManagerInitState 失败: {}", e))?
             .ok_or_else(|| "未找到 jetskiStateSync.agentManagerInitState".to_string())?;
 
-        // 认证状态 (可选)
-        let auth_status: Option<String> = conn
-            .query_row(
-                "SELECT value FROM ItemTable WHERE key = 'antigravityAuthStatus'",
-                [],
-                |row| row.get(0),
-            )
-            .optional()
-            .unwrap_or(None);
+        // 认证状态（可选，但数据库被锁定或读取失败时不能当作不存在）
+        let auth_status: Option<String> = db
+            .get(database::AUTH_STATUS)
+            .map_err(|e| format!("查询 antigravityAuthStatus 失败: {}", e))?;
 
         // 从 jetski proto 解码邮箱（仅用于文件名）
         let bytes = base64::engine::general_purpose::STANDARD
@@ -231,6 +264,7 @@ pub async fn backup_current() -> Result<String, String> {
         }
 
         let account_file = accounts_dir.join(format!("{email}.json"));
+        let created = !account_file.exists();
         let mut content_map = serde_json::Map::new();
         content_map.insert(
             "jetskiStateSync.agentManagerInitState".to_string(),
@@ -256,19 +290,20 @@ pub async fn backup_current() -> Result<String, String> {
             account_file.display()
         );
         tracing::info!(file = %account_file.display(), "✅ 保存 jetski 状态完成");
-        Ok(message)
+        Ok((message, email.to_string(), created))
     }
     .await;
 
     let duration = start_time.elapsed();
 
     match result {
-        Ok(message) => {
+        Ok((message, email, created)) => {
             tracing::info!(
                 duration_ms = duration.as_millis(),
                 result_message = %message,
                 "账户保存操作完成"
             );
+            crate::events::publish(crate::events::AppEvent::AccountSaved { email, created });
             Ok(message)
         }
         Err(e) => {
@@ -284,6 +319,7 @@ pub async fn backup_current() -> Result<String, String> {
 
 /// 清除所有 Antigravity 数据
 pub async fn clear_all_data() -> Result<String, String> {
+    snapshot::take("clear_all_data")?;
     crate::antigravity::cleanup::clear_all_antigravity_data().await
 }
 
@@ -291,6 +327,14 @@ pub async fn clear_all_data() -> Result<String, String> {
 pub async fn restore(account_name: String) -> Result<String, String> {
     tracing::debug!(target: "account::restore", account_name = %account_name, "调用 restore_antigravity_account");
 
+    validate_account_name(&account_name)?;
+
+    snapshot::take("restore")?;
+    restore_account_file(&account_name).await
+}
+
+/// 校验账户名，防止路径穿越
+pub(crate) fn validate_account_name(account_name: &str) -> Result<(), String> {
     if account_name.is_empty()
         || account_name.len() > 255
         || account_name.contains('/')
@@ -303,6 +347,11 @@ pub async fn restore(account_name: String) -> Result<String, String> {
         return Err("非法账户名".to_string());
     }
 
+    Ok(())
+}
+
+/// 将账户文件写回 Antigravity 数据库（不创建快照，供 switch 内部使用）
+async fn restore_account_file(account_name: &str) -> Result<String, String> {
     // 1. 构建备份文件路径
     let accounts_dir = crate::directories::get_accounts_directory();
     let account_file = accounts_dir.join(format!("{account_name}.json"));
@@ -311,6 +360,27 @@ pub async fn restore(account_name: String) -> Result<String, String> {
     crate::antigravity::restore::save_antigravity_account_to_file(account_file).await
 }
 
+/// 撤销上一次破坏性操作：用最近的快照覆盖 state.vscdb
+///
+/// 有扩展连接时同时广播 reloadWindow，让 Antigravity 重新加载恢复后的账户。
+pub async fn undo_last_operation() -> Result<String, String> {
+    let info = snapshot::restore_latest()?;
+
+    if crate::server::websocket::has_extension_connections() {
+        crate::server::websocket::call_all_extensions("reloadWindow", serde_json::json!({}));
+    }
+
+    Ok(format!(
+        "已撤销操作 {}（快照时间 {}）",
+        info.operation, info.created_at
+    ))
+}
+
+/// 列出 state.vscdb 快照
+pub async fn list_snapshots() -> Result<Vec<snapshot::SnapshotInfo>, String> {
+    snapshot::list()
+}
+
 /// 切换到 Antigravity 账户
 ///
 /// 三分支逻辑：
@@ -318,6 +388,23 @@ pub async fn restore(account_name: String) -> Result<String, String> {
 /// 2. 无扩展 + Antigravity 运行中 → 提示安装扩展
 /// 3. 无扩展 + Antigravity 未运行 → 恢复数据 + 启动进程
 pub async fn switch(account_name: String) -> Result<String, String> {
+    let result = perform_switch(account_name.clone()).await;
+    match &result {
+        Ok(_) => crate::events::publish(crate::events::AppEvent::AccountSwitched {
+            email: account_name,
+        }),
+        Err(e) => crate::events::publish(crate::events::AppEvent::AccountSwitchFailed {
+            email: account_name,
+            error: e.clone(),
+        }),
+    }
+    result
+}
+
+/// 按场景执行账户切换
+async fn perform_switch(account_name: String) -> Result<String, String> {
+        validate_account_name(&account_name)?;
+
         // 检查条件
         let has_extension = crate::server::websocket::has_extension_connections();
         let is_running = crate::platform::is_antigravity_running();
@@ -335,12 +422,15 @@ pub async fn switch(account_name: String) -> Result<String, String> {
                 let client_count = crate::server::websocket::extension_client_count();
                 tracing::info!(target: "account::switch::scenario1", client_count = client_count, "使用扩展模式切换");
 
+                // 0. 快照（Antigravity 仍在运行，快照通过 SQLite 在线备份读取一致的数据）
+                snapshot::take("switch")?;
+
                 // 1. 清除原来的数据库
-                clear_all_data().await?;
+                crate::antigravity::cleanup::clear_all_antigravity_data().await?;
                 tracing::debug!(target: "account::switch::step1", "Antigravity 数据库清除完成");
 
                 // 2. 恢复指定账户到 Antigravity 数据库
-                restore(account_name.clone()).await?;
+                restore_account_file(&account_name).await?;
                 tracing::debug!(target: "account::switch::step2", "账户数据恢复完成");
 
                 // 3. 等待数据库操作完成
@@ -389,10 +479,13 @@ pub async fn switch(account_name: String) -> Result<String, String> {
 
                 tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
 
-                clear_all_data().await?;
+                // 进程已退出，WAL 已写回数据库后再快照
+                snapshot::take("switch")?;
+
+                crate::antigravity::cleanup::clear_all_antigravity_data().await?;
                 tracing::debug!(target: "account::switch::step1", "Antigravity 数据库清除完成");
 
-                restore(account_name.clone()).await?;
+                restore_account_file(&account_name).await?;
                 tracing::debug!(target: "account::switch::step2", "账户数据恢复完成");
 
                 tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
@@ -441,12 +534,14 @@ pub async fn switch(account_name: String) -> Result<String, String> {
 
                 tracing::info!(target: "account::switch::scenario3", "Antigravity 未运行，使用进程启动模式");
 
+                snapshot::take("switch")?;
+
                 // 1. 清除原来的数据库
-                clear_all_data().await?;
+                crate::antigravity::cleanup::clear_all_antigravity_data().await?;
                 tracing::debug!(target: "account::switch::step1", "Antigravity 数据库清除完成");
 
                 // 2. 恢复指定账户到 Antigravity 数据库
-                restore(account_name.clone()).await?;
+                restore_account_file(&account_name).await?;
                 tracing::debug!(target: "account::switch::step2", "账户数据恢复完成");
 
                 // 3. 等待数据库操作完成
@@ -496,8 +591,10 @@ pub async fn sign_in_new() -> Result<String, String> {
         }
     };
 
-    // 3. 清除数据
-    let _ = clear_all_data().await;
+    // 3. 清除数据（清除前会先拍快照，失败时不继续，避免在旧账户仍登录时报告成功）
+    clear_all_data()
+        .await
+        .map_err(|e| format!("清除账户数据失败: {}", e))?;
 
     // 4. 重启
     tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
@@ -510,6 +607,9 @@ pub async fn sign_in_new() -> Result<String, String> {
     Ok(format!("{} -> 备份: {:?} -> 重启: {}", kill_result, backup_msg, start_msg))
 }
 
+/// 剩余配额达到该比例视为已满（窗口尚未开始计时 / 已重置）
+pub const FULL_QUOTA: f64 = 0.9999;
+
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
 pub struct QuotaItem {
     pub model_name: String,
@@ -517,6 +617,41 @@ pub struct QuotaItem {
     pub reset_text: String,
 }
 
+impl QuotaItem {
+    /// 配额是否已满（窗口尚未开始计时）
+    pub fn is_full(&self) -> bool {
+        self.percentage >= FULL_QUOTA
+    }
+
+    /// 解析 `resetTime`（RFC 3339），为空或格式无效时返回 `None`
+    pub fn reset_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::parse_from_rfc3339(self.reset_text.trim())
+            .ok()
+            .map(|time| time.with_timezone(&chrono::Utc))
+    }
+}
+
+/// 展示配额的模型：(模型 key, 显示名称)
+const QUOTA_MODELS: &[(&str, &str)] = &[
+    ("gemini-3-pro-high", "Gemini Pro"),
+    ("gemini-3-flash", "Gemini Flash"),
+    ("gemini-3-pro-image", "Gemini Image"),
+    ("claude-opus-4-5-thinking", "Claude"),
+];
+
+/// 显示名称对应的模型 key（用于发送触发请求）
+pub(crate) fn model_key(model_name: &str) -> Option<&'static str> {
+    QUOTA_MODELS
+        .iter()
+        .find(|(_, name)| *name == model_name)
+        .map(|(key, _)| *key)
+}
+
+/// 所有展示配额的模型的显示名称
+pub(crate) fn quota_model_names() -> impl Iterator<Item = &'static str> {
+    QUOTA_MODELS.iter().map(|(_, name)| *name)
+}
+
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
 pub struct AccountMetrics {
     pub email: String,
@@ -539,49 +674,80 @@ pub struct TriggerResult {
 pub async fn get_metrics(
     config_dir: &std::path::Path,
     email: String,
+) -> Result<AccountMetrics, String> {
+    let client = crate::services::google_api::default_client();
+    get_metrics_with(&client, config_dir, email).await
+}
+
+/// 使用指定的 Google API 客户端获取账户指标
+pub async fn get_metrics_with(
+    client: &dyn crate::services::google_api::CloudCodeClient,
+    config_dir: &std::path::Path,
+    email: String,
 ) -> Result<AccountMetrics, String> {
     use crate::services::google_api;
-    
+
     // 1. Load Account & Token
     let (email, proto_bytes) = google_api::load_account(config_dir, &email).await?;
-    let token_info = google_api::get_valid_token(&email, &proto_bytes).await?;
-
-    // 2. Fetch Models
-    let project = google_api::fetch_code_assist_project(&token_info.access_token).await
-        .map_err(|e| format!("获取项目 ID 失败: {}", e))?;
+    let token_info = google_api::get_valid_token(client, &email, &proto_bytes).await?;
+
+    // 2. Fetch Models（project id 优先使用缓存）
+    let cache = &crate::services::metrics_cache::METRICS_CACHE;
+    let project = match cache.project(&email) {
+        Some(project) => project,
+        None => {
+            let project = client.fetch_code_assist_project(&token_info.access_token).await
+                .map_err(|e| format!("获取项目 ID 失败: {}", e))?;
+            cache.store_project(&email, &project);
+            project
+        }
+    };
 
-    let models_json = google_api::fetch_available_models(&token_info.access_token, &project).await
-        .map_err(|e| format!("获取模型列表失败: {}", e))?;
+    let models_json = match client.fetch_available_models(&token_info.access_token, &project).await {
+        Ok(json) => json,
+        Err(e) => {
+            // project id 可能已失效，下次重新获取
+            cache.invalidate_project(&email);
+            return Err(format!("获取模型列表失败: {}", e));
+        }
+    };
 
     // 3. Parse Quotas
     let quotas = parse_quotas(&models_json);
 
-    Ok(AccountMetrics {
+    let metrics = AccountMetrics {
         email,
         user_id: token_info.user_id,
         avatar_url: token_info.avatar_url,
         quotas,
-    })
+    };
+    cache.store_metrics(&metrics);
+    crate::events::publish(crate::events::AppEvent::QuotaUpdated {
+        metrics: metrics.clone(),
+    });
+
+    Ok(metrics)
 }
 
 pub async fn trigger_quota_refresh(
     config_dir: &std::path::Path,
     email: String,
 ) -> Result<TriggerResult, String> {
-    use crate::services::google_api;
+    use crate::services::google_api::{self, CloudCodeClient};
     use tracing::{info, error};
 
     info!("🚀 Check Quota & Trigger Refresh for: {}", email);
+    let client = google_api::default_client();
 
     // 1. Load Account & Token
     let (email_str, proto_bytes) = google_api::load_account(config_dir, &email).await?;
-    let token_info = match google_api::get_valid_token(&email, &proto_bytes).await {
+    let token_info = match google_api::get_valid_token(&client, &email, &proto_bytes).await {
         Ok(t) => t,
         Err(e) => return Err(format!("Auth failed: {}", e)),
     };
 
     // 2. Get Project ID
-    let project = match google_api::fetch_code_assist_project(&token_info.access_token).await {
+    let project = match client.fetch_code_assist_project(&token_info.access_token).await {
         Ok(p) => p,
         Err(e) => {
             return Ok(TriggerResult {
@@ -597,7 +763,8 @@ pub async fn trigger_quota_refresh(
     };
 
     // 3. Get Available Models & Quotas
-    let models_json = google_api::fetch_available_models(&token_info.access_token, &project).await?;
+    let models_json = client.fetch_available_models(&token_info.access_token, &project).await
+        .map_err(|e| format!("获取模型列表失败: {}", e))?;
     let quotas = parse_quotas(&models_json);
 
     let mut triggered = Vec::new();
@@ -606,21 +773,12 @@ pub async fn trigger_quota_refresh(
     let mut skipped_details = Vec::new();
 
     for item in quotas {
-        if item.percentage > 0.9999 {
-            // Find key? We need key for trigger.
-            // Simplified: we used display name for key mapping in parse_quotas.
-            // We need to reverse map or pass key.
-            // Let's assume we can map back for now or improve parse_quotas later.
-            // For now, let's look up key from name.
-            let key = match item.model_name.as_str() {
-                "Gemini Pro" => "gemini-3-pro-high",
-                "Gemini Flash" => "gemini-3-flash",
-                "Gemini Image" => "gemini-3-pro-image",
-                "Claude" => "claude-opus-4-5-thinking",
-                _ => continue,
+        if item.is_full() {
+            let Some(key) = model_key(&item.model_name) else {
+                continue;
             };
 
-            match trigger_minimal_query(&token_info.access_token, &project, key).await {
+            match client.trigger_minimal_query(&token_info.access_token, &project, key).await {
                 Ok(_) => triggered.push(item.model_name.clone()),
                 Err(e) => {
                     error!("Trigger failed for {}: {}", item.model_name, e);
@@ -644,19 +802,12 @@ pub async fn trigger_quota_refresh(
     })
 }
 
-fn parse_quotas(models_json: &serde_json::Value) -> Vec<QuotaItem> {
+pub(crate) fn parse_quotas(models_json: &serde_json::Value) -> Vec<QuotaItem> {
     let mut items = Vec::new();
     let models_map = models_json.get("models").and_then(|v| v.as_object());
 
     if let Some(map) = models_map {
-        let targets = vec![
-            ("gemini-3-pro-high", "Gemini Pro"),
-            ("gemini-3-flash", "Gemini Flash"),
-            ("gemini-3-pro-image", "Gemini Image"),
-            ("claude-opus-4-5-thinking", "Claude"),
-        ];
-
-        for (key, name) in targets {
+        for &(key, name) in QUOTA_MODELS {
             if let Some(model_data) = map.get(key) {
                 if let Some(quota_info) = model_data.get("quotaInfo") {
                     let percentage = quota_info
@@ -681,53 +832,6 @@ fn parse_quotas(models_json: &serde_json::Value) -> Vec<QuotaItem> {
     items
 }
 
-async fn trigger_minimal_query(
-    access_token: &str,
-    project: &str,
-    model_key: &str,
-) -> Result<(), String> {
-    use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, USER_AGENT};
-    
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(10))
-        .build()
-        .map_err(|e| e.to_string())?;
-
-    let url = format!("{}/v1internal:generateContent", crate::services::google_api::CLOUD_CODE_BASE_URL);
-
-    let body = serde_json::json!({
-        "project": project,
-        "model": model_key,
-        "request": {
-            "contents": [
-                {
-                    "role": "user",
-                    "parts": [{ "text": format!("Hi [Ref: {}]", chrono::Utc::now().to_rfc3339()) }]
-                }
-            ],
-            "generationConfig": {
-                "maxOutputTokens": 10
-            }
-        }
-    });
-
-    let res = client
-        .post(&url)
-        .header(AUTHORIZATION, format!("Bearer {}", access_token))
-        .header(CONTENT_TYPE, "application/json")
-        .header(USER_AGENT, "antigravity/windows/amd64")
-        .json(&body)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    if !res.status().is_success() {
-        return Err(format!("API Error {}", res.status()));
-    }
-
-    Ok(())
-}
-
 /// 检查是否运行中
 pub fn is_running() -> bool {
     crate::platform::is_antigravity_running()
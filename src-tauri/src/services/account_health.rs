@@ -0,0 +1,219 @@
+//! 账户 token 健康检查
+//!
+//! 并行检查 `antigravity-accounts` 下每个账户的 token：先用 access token 请求 userinfo，
+//! 失败时尝试用 refresh token 刷新，据此判断账户状态，避免切换后才发现 token 已被吊销。
+//! 检查结果写入 `account_health.json`，供界面展示最近一次状态。
+
+use futures_util::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use super::google_api::{self, ApiError};
+use crate::constants::database;
+
+/// 同时检查的账户数量上限
+const MAX_CONCURRENT_CHECKS: usize = 4;
+
+const HEALTH_FILE: &str = "account_health.json";
+
+/// 账户 token 状态
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    /// access token 有效
+    Valid,
+    /// access token 已过期，但 refresh token 可以刷新
+    Refreshed,
+    /// refresh token 已被吊销或失效，需要重新登录
+    Revoked,
+    /// 无法确认（网络、超时或本地配置问题）
+    NetworkError,
+    /// 账户文件中没有可用的 token
+    MissingToken,
+}
+
+/// 单个账户的检查结果
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountHealth {
+    pub email: String,
+    pub status: HealthStatus,
+    /// 检查时间（RFC 3339）
+    pub checked_at: String,
+    pub error: Option<String>,
+}
+
+/// 读取最近一次的检查结果
+pub fn load_recorded(config_dir: &Path) -> Vec<AccountHealth> {
+    fs::read_to_string(config_dir.join(HEALTH_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str::<BTreeMap<String, AccountHealth>>(&content).ok())
+        .map(|records| records.into_values().collect())
+        .unwrap_or_default()
+}
+
+/// 并行检查所有已保存账户并记录结果
+pub async fn check_all(config_dir: &Path) -> Result<Vec<AccountHealth>, String> {
+    let accounts = super::account::list_account_files(config_dir)?;
+
+    tracing::info!(
+        target: "account::health",
+        account_count = accounts.len(),
+        "开始检查账户 token 状态"
+    );
+    let start_time = std::time::Instant::now();
+
+    let mut results: Vec<AccountHealth> = stream::iter(accounts)
+        .map(|account| async move {
+            let (status, error) = check_account(&account.path, &account.email).await;
+            AccountHealth {
+                email: account.email,
+                status,
+                checked_at: chrono::Local::now().to_rfc3339(),
+                error,
+            }
+        })
+        .buffer_unordered(MAX_CONCURRENT_CHECKS)
+        .collect()
+        .await;
+    results.sort_by(|a, b| a.email.cmp(&b.email));
+
+    tracing::info!(
+        target: "account::health",
+        duration_ms = start_time.elapsed().as_millis(),
+        revoked = results.iter().filter(|r| r.status == HealthStatus::Revoked).count(),
+        "账户 token 状态检查完成"
+    );
+
+    // 只通知新出现的吊销，避免每次检查都重复提醒
+    let previously_revoked: Vec<String> = load_recorded(config_dir)
+        .into_iter()
+        .filter(|r| r.status == HealthStatus::Revoked)
+        .map(|r| r.email)
+        .collect();
+    record(config_dir, &results)?;
+    for result in &results {
+        if result.status == HealthStatus::Revoked && !previously_revoked.contains(&result.email) {
+            crate::events::publish(crate::events::AppEvent::TokenRevoked {
+                email: result.email.clone(),
+            });
+        }
+    }
+    Ok(results)
+}
+
+/// 写入检查结果（整体覆盖，已删除的账户不再保留）
+fn record(config_dir: &Path, results: &[AccountHealth]) -> Result<(), String> {
+    let records: BTreeMap<&str, &AccountHealth> =
+        results.iter().map(|r| (r.email.as_str(), r)).collect();
+    let json = serde_json::to_string_pretty(&records)
+        .map_err(|e| format!("序列化健康检查结果失败: {}", e))?;
+    fs::write(config_dir.join(HEALTH_FILE), json)
+        .map_err(|e| format!("写入健康检查结果失败: {}", e))
+}
+
+/// 读取账户文件中的 token
+fn read_tokens(path: &Path) -> Result<(String, String), String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("读取账户文件失败: {}", e))?;
+    let json: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| format!("解析账户文件失败: {}", e))?;
+    let state = json
+        .get(database::AGENT_STATE)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| format!("缺少 {} 字段", database::AGENT_STATE))?;
+
+    let auth = crate::antigravity::account::decode_session_response(state)?
+        .auth
+        .ok_or_else(|| "账户数据中没有 AuthInfo".to_string())?;
+    Ok((auth.access_token, auth.refresh_token))
+}
+
+async fn check_account(path: &Path, email: &str) -> (HealthStatus, Option<String>) {
+    let (access_token, refresh_token) = match read_tokens(path) {
+        Ok(tokens) => tokens,
+        Err(e) => return (HealthStatus::MissingToken, Some(e)),
+    };
+
+    if access_token.is_empty() && refresh_token.is_empty() {
+        return (HealthStatus::MissingToken, None);
+    }
+
+    if !access_token.is_empty() {
+        match google_api::try_fetch_user_info(&access_token).await {
+            Ok(_) => return (HealthStatus::Valid, None),
+            Err(ApiError::Unauthorized) => {
+                tracing::debug!(target: "account::health", email = %email, "access token 无效，尝试刷新");
+            }
+            // 网络、限流或服务端故障时无法判断 token 状态，不再尝试刷新
+            Err(e) => return (HealthStatus::NetworkError, Some(e.to_string())),
+        }
+    }
+
+    if refresh_token.is_empty() {
+        return (
+            HealthStatus::MissingToken,
+            Some("access token 已失效且没有 refresh token".to_string()),
+        );
+    }
+
+    match google_api::try_refresh_access_token(&refresh_token).await {
+        Ok(_) => (HealthStatus::Refreshed, None),
+        Err(e) => (classify_refresh_error(&e), Some(e.to_string())),
+    }
+}
+
+/// 刷新失败时的状态：400/401（invalid_grant 等）表示 refresh token 已失效
+fn classify_refresh_error(error: &ApiError) -> HealthStatus {
+    match error {
+        ApiError::Unauthorized => HealthStatus::Revoked,
+        ApiError::Status(status) if status.as_u16() == 400 => HealthStatus::Revoked,
+        _ => HealthStatus::NetworkError,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_refresh_error() {
+        assert_eq!(
+            classify_refresh_error(&ApiError::Status(reqwest::StatusCode::BAD_REQUEST)),
+            HealthStatus::Revoked
+        );
+        assert_eq!(
+            classify_refresh_error(&ApiError::Status(reqwest::StatusCode::SERVICE_UNAVAILABLE)),
+            HealthStatus::NetworkError
+        );
+        assert_eq!(
+            classify_refresh_error(&ApiError::Network("timeout".to_string())),
+            HealthStatus::NetworkError
+        );
+        assert_eq!(
+            classify_refresh_error(&ApiError::Unauthorized),
+            HealthStatus::Revoked
+        );
+        assert_eq!(
+            classify_refresh_error(&ApiError::RateLimited { retry_after: None }),
+            HealthStatus::NetworkError
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_token_is_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        let accounts = dir.path().join("antigravity-accounts");
+        fs::create_dir_all(&accounts).unwrap();
+        fs::write(accounts.join("a@example.com.json"), r#"{"foo": "bar"}"#).unwrap();
+
+        let results = check_all(dir.path()).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].status, HealthStatus::MissingToken);
+
+        let recorded = load_recorded(dir.path());
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].email, "a@example.com");
+    }
+}
@@ -1,30 +1,33 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 use std::fs;
 use std::time::SystemTime;
 
+use crate::constants::database;
+
 /// 备份数据收集结构
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AccountExportedData {
-    filename: String,
+    pub(crate) filename: String,
     #[serde(rename = "content")]
-    content: Value,
+    pub(crate) content: Value,
     #[serde(rename = "timestamp")]
-    timestamp: u64,
+    pub(crate) timestamp: u64,
 }
 
 /// 恢复结果
 #[derive(Serialize, Deserialize, Debug)]
 pub struct RestoreResult {
     #[serde(rename = "restoredCount")]
-    restored_count: u32,
-    failed: Vec<FailedAccountExportedData>,
+    pub(crate) restored_count: u32,
+    pub(crate) failed: Vec<FailedAccountExportedData>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct FailedAccountExportedData {
-    filename: String,
-    error: String,
+    pub(crate) filename: String,
+    pub(crate) error: String,
 }
 
 /// 收集所有账户文件的完整内容, 用于导出
@@ -84,46 +87,318 @@ pub async fn collect_contents(
 }
 
 /// 恢复备份文件到本地
+///
+/// 旧接口：等价于 `ConflictStrategy::Overwrite` 的导入，但同样经过校验。
 pub async fn restore_files(
     config_dir: &std::path::Path,
     account_file_data: Vec<AccountExportedData>,
 ) -> Result<RestoreResult, String> {
+    let report = import_accounts(
+        config_dir,
+        account_file_data,
+        ConflictStrategy::Overwrite,
+        false,
+    )
+    .await?;
+
     let mut results = RestoreResult {
         restored_count: 0,
         failed: Vec::new(),
     };
 
-    // 获取目标目录
-    let antigravity_dir = config_dir.join("antigravity-accounts");
+    for item in report.items {
+        match item.action {
+            ImportAction::Create | ImportAction::Overwrite | ImportAction::Rename => {
+                results.restored_count += 1;
+            }
+            ImportAction::Skip => {}
+            ImportAction::Invalid | ImportAction::Failed => {
+                results.failed.push(FailedAccountExportedData {
+                    filename: item.filename,
+                    error: item.reason.unwrap_or_default(),
+                });
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+/// 导入冲突处理策略（目标账户文件已存在时）
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    /// 保留本地文件，跳过导入
+    Skip,
+    /// 用导入内容覆盖本地文件
+    #[default]
+    Overwrite,
+    /// 比较 token 创建时间，保留较新的一份
+    KeepNewer,
+    /// 以新文件名另存（{email}_2.json ...），文件中的邮箱不变
+    Rename,
+}
+
+/// 单个导入条目的处理结果
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportAction {
+    /// 新建账户文件
+    Create,
+    /// 覆盖已有账户文件
+    Overwrite,
+    /// 以新文件名另存
+    Rename,
+    /// 因冲突策略跳过
+    Skip,
+    /// 内容无效，拒绝导入
+    Invalid,
+    /// 写入失败
+    Failed,
+}
+
+/// 导入计划 / 结果条目
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportItem {
+    /// 导入数据中声明的文件名
+    pub filename: String,
+    /// 从 proto 中解析出的邮箱
+    pub email: Option<String>,
+    /// 实际写入的文件名
+    pub target_filename: Option<String>,
+    pub action: ImportAction,
+    /// 跳过 / 拒绝 / 失败的原因
+    pub reason: Option<String>,
+}
+
+/// 导入报告（dry-run 时为预览）
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub created: u32,
+    pub overwritten: u32,
+    pub renamed: u32,
+    pub skipped: u32,
+    pub invalid: u32,
+    pub failed: u32,
+    pub items: Vec<ImportItem>,
+}
+
+impl ImportReport {
+    fn push(&mut self, item: ImportItem) {
+        match item.action {
+            ImportAction::Create => self.created += 1,
+            ImportAction::Overwrite => self.overwritten += 1,
+            ImportAction::Rename => self.renamed += 1,
+            ImportAction::Skip => self.skipped += 1,
+            ImportAction::Invalid => self.invalid += 1,
+            ImportAction::Failed => self.failed += 1,
+        }
+        self.items.push(item);
+    }
+}
+
+/// 经过校验的待导入账户
+struct ValidatedAccount {
+    email: String,
+    /// token 创建时间（秒），用于 keep_newer 策略
+    token_created_at: i64,
+    /// 只包含已知字段的规范化内容
+    content: Value,
+}
 
-    // 确保目录存在
-    if let Err(e) = fs::create_dir_all(&antigravity_dir) {
-        return Err(format!("创建目录失败: {}", e));
+/// 校验导入数据中的文件名，拒绝任何可能造成路径穿越的名字
+pub(crate) fn validate_import_filename(filename: &str) -> Result<(), String> {
+    let path = std::path::Path::new(filename);
+    let is_plain_name = path.components().count() == 1
+        && matches!(
+            path.components().next(),
+            Some(std::path::Component::Normal(_))
+        );
+
+    if filename.is_empty()
+        || filename.len() > 255
+        || !is_plain_name
+        || filename.contains(['/', '\\', ':', '\0'])
+        || filename.starts_with('.')
+    {
+        return Err(format!("非法文件名: {:?}", filename));
     }
 
-    // 遍历每个备份
-    for account_file in account_file_data {
-        let file_path = antigravity_dir.join(&account_file.filename);
+    Ok(())
+}
+
+/// 解码 jetskiStateSync proto，校验账户内容并提取邮箱
+fn validate_account_content(content: &Value) -> Result<ValidatedAccount, String> {
+    let state = content
+        .get(database::AGENT_STATE)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| format!("缺少 {} 字段", database::AGENT_STATE))?;
 
-        match fs::write(
-            &file_path,
-            serde_json::to_string_pretty(&account_file.content).unwrap_or_default(),
-        )
-        .map_err(|e| format!("写入文件失败: {}", e))
+    let msg = crate::antigravity::account::decode_session_response(state)?;
+
+    let email = msg
+        .context
+        .as_ref()
+        .map(|c| c.email.trim().to_string())
+        .filter(|e| !e.is_empty())
+        .ok_or_else(|| "账户数据中未找到邮箱".to_string())?;
+    crate::services::account::validate_account_name(&email)
+        .map_err(|_| format!("账户邮箱包含非法字符: {:?}", email))?;
+
+    let auth = msg
+        .auth
+        .as_ref()
+        .filter(|a| !a.access_token.is_empty() || !a.refresh_token.is_empty())
+        .ok_or_else(|| "账户数据中缺少认证 token".to_string())?;
+    let token_created_at = auth.created_at.as_ref().map(|t| t.seconds).unwrap_or(0);
+
+    let mut normalized = serde_json::Map::new();
+    normalized.insert(
+        database::AGENT_STATE.to_string(),
+        Value::String(state.trim().to_string()),
+    );
+    if let Some(status) = content.get(database::AUTH_STATUS).and_then(|v| v.as_str()) {
+        normalized.insert(
+            database::AUTH_STATUS.to_string(),
+            Value::String(status.to_string()),
+        );
+    }
+
+    Ok(ValidatedAccount {
+        email,
+        token_created_at,
+        content: Value::Object(normalized),
+    })
+}
+
+/// 读取已存在账户文件中的 token 创建时间（无法解析时视为 0）
+fn existing_token_created_at(path: &std::path::Path) -> i64 {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .and_then(|json| validate_account_content(&json).ok())
+        .map(|account| account.token_created_at)
+        .unwrap_or(0)
+}
+
+/// 导入账户文件
+///
+/// 每个条目都会：校验文件名 → 解码 proto 校验内容并取得邮箱 → 按冲突策略决定写入方式。
+/// 目标文件名始终为 `{email}.json`（rename 策略下为 `{email}_N.json`），不会使用
+/// 调用方提供的文件名拼接路径。`dry_run` 为 true 时只返回计划，不写入任何文件。
+pub async fn import_accounts(
+    config_dir: &std::path::Path,
+    entries: Vec<AccountExportedData>,
+    strategy: ConflictStrategy,
+    dry_run: bool,
+) -> Result<ImportReport, String> {
+    let antigravity_dir = config_dir.join("antigravity-accounts");
+    if !dry_run {
+        fs::create_dir_all(&antigravity_dir).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+
+    let mut report = ImportReport {
+        dry_run,
+        ..Default::default()
+    };
+    // 本批次中已计划写入的文件名 -> token 时间，保证同一批次内的重复账户也按策略处理
+    let mut planned: HashMap<String, i64> = HashMap::new();
+
+    for entry in entries {
+        let account = match validate_import_filename(&entry.filename)
+            .and_then(|_| validate_account_content(&entry.content))
         {
-            Ok(_) => {
-                results.restored_count += 1;
-            }
+            Ok(account) => account,
             Err(e) => {
-                results.failed.push(FailedAccountExportedData {
-                    filename: account_file.filename,
-                    error: e,
+                tracing::warn!(target: "backup::import", filename = %entry.filename, error = %e, "拒绝导入");
+                report.push(ImportItem {
+                    filename: entry.filename,
+                    email: None,
+                    target_filename: None,
+                    action: ImportAction::Invalid,
+                    reason: Some(e),
                 });
+                continue;
+            }
+        };
+
+        let default_name = format!("{}.json", account.email);
+        let exists = |name: &str| planned.contains_key(name) || antigravity_dir.join(name).exists();
+
+        let (action, target_name, reason) = if !exists(&default_name) {
+            (ImportAction::Create, default_name, None)
+        } else {
+            match strategy {
+                ConflictStrategy::Skip => (
+                    ImportAction::Skip,
+                    default_name,
+                    Some("账户已存在".to_string()),
+                ),
+                ConflictStrategy::Overwrite => (ImportAction::Overwrite, default_name, None),
+                ConflictStrategy::KeepNewer => {
+                    let local = planned.get(&default_name).copied().unwrap_or_else(|| {
+                        existing_token_created_at(&antigravity_dir.join(&default_name))
+                    });
+                    if account.token_created_at > local {
+                        (ImportAction::Overwrite, default_name, None)
+                    } else {
+                        (
+                            ImportAction::Skip,
+                            default_name,
+                            Some("本地账户的 token 不比导入数据旧".to_string()),
+                        )
+                    }
+                }
+                ConflictStrategy::Rename => {
+                    let target = (2..)
+                        .map(|n| format!("{}_{}.json", account.email, n))
+                        .find(|name| !exists(name))
+                        .unwrap_or(default_name);
+                    (ImportAction::Rename, target, None)
+                }
+            }
+        };
+
+        let mut item = ImportItem {
+            filename: entry.filename,
+            email: Some(account.email),
+            target_filename: Some(target_name.clone()),
+            action,
+            reason,
+        };
+
+        if action != ImportAction::Skip {
+            planned.insert(target_name.clone(), account.token_created_at);
+
+            if !dry_run {
+                let json = serde_json::to_string_pretty(&account.content)
+                    .map_err(|e| format!("序列化账户数据失败: {}", e))?;
+                if let Err(e) = fs::write(antigravity_dir.join(&target_name), json) {
+                    item.action = ImportAction::Failed;
+                    item.reason = Some(format!("写入文件失败: {}", e));
+                }
             }
         }
+
+        report.push(item);
     }
 
-    Ok(results)
+    tracing::info!(
+        target: "backup::import",
+        dry_run = dry_run,
+        created = report.created,
+        overwritten = report.overwritten,
+        renamed = report.renamed,
+        skipped = report.skipped,
+        invalid = report.invalid,
+        failed = report.failed,
+        "账户导入完成"
+    );
+
+    Ok(report)
 }
 
 /// 删除指定备份
@@ -172,3 +447,142 @@ pub async fn clear_all(config_dir: &std::path::Path) -> Result<String, String> {
         Ok("用户目录不存在，无需清空".to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use prost::Message;
+
+    fn account_content(email: &str, created_at: i64) -> Value {
+        let msg = crate::proto::SessionResponse {
+            auth: Some(crate::proto::AuthInfo {
+                access_token: "ya29.token".to_string(),
+                refresh_token: "1//refresh".to_string(),
+                created_at: Some(crate::proto::Timestamp {
+                    seconds: created_at,
+                }),
+                ..Default::default()
+            }),
+            context: Some(crate::proto::UserContext {
+                email: email.to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let b64 = base64::engine::general_purpose::STANDARD.encode(msg.encode_to_vec());
+        serde_json::json!({ database::AGENT_STATE: b64, database::AUTH_STATUS: "{}" })
+    }
+
+    fn entry(filename: &str, content: Value) -> AccountExportedData {
+        AccountExportedData {
+            filename: filename.to_string(),
+            content,
+            timestamp: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_invalid_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = import_accounts(
+            dir.path(),
+            vec![
+                entry("../evil.json", account_content("a@example.com", 1)),
+                entry("b.json", serde_json::json!({ "foo": "bar" })),
+                entry("c.json", account_content("../c@example.com", 1)),
+            ],
+            ConflictStrategy::Overwrite,
+            false,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report.invalid, 3);
+        assert!(!dir.path().join("evil.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_import_conflict_strategies_and_dry_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let accounts_dir = dir.path().join("antigravity-accounts");
+
+        let report = import_accounts(
+            dir.path(),
+            vec![entry("x.json", account_content("a@example.com", 100))],
+            ConflictStrategy::Overwrite,
+            true,
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.created, 1);
+        assert!(!accounts_dir.join("a@example.com.json").exists());
+
+        import_accounts(
+            dir.path(),
+            vec![entry("x.json", account_content("a@example.com", 100))],
+            ConflictStrategy::Overwrite,
+            false,
+        )
+        .await
+        .unwrap();
+        let written: Value = serde_json::from_str(
+            &fs::read_to_string(accounts_dir.join("a@example.com.json")).unwrap(),
+        )
+        .unwrap();
+        assert!(written.get(database::AGENT_STATE).is_some());
+
+        let older = vec![entry("x.json", account_content("a@example.com", 50))];
+        let report = import_accounts(dir.path(), older, ConflictStrategy::KeepNewer, false)
+            .await
+            .unwrap();
+        assert_eq!(report.skipped, 1);
+
+        let newer = vec![entry("x.json", account_content("a@example.com", 200))];
+        let report = import_accounts(dir.path(), newer, ConflictStrategy::KeepNewer, false)
+            .await
+            .unwrap();
+        assert_eq!(report.overwritten, 1);
+
+        let report = import_accounts(
+            dir.path(),
+            vec![entry("x.json", account_content("a@example.com", 1))],
+            ConflictStrategy::Rename,
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.renamed, 1);
+        assert!(accounts_dir.join("a@example.com_2.json").exists());
+        // 另存的文件仍按其中记录的邮箱归属到原账户
+        assert_eq!(
+            crate::services::account::list_account_emails(dir.path()).unwrap(),
+            vec!["a@example.com".to_string()]
+        );
+        let lookup = |email| crate::services::account::account_file_path(dir.path(), email);
+        assert_eq!(
+            lookup("a@example.com").unwrap(),
+            accounts_dir.join("a@example.com.json")
+        );
+        fs::remove_file(accounts_dir.join("a@example.com.json")).unwrap();
+        assert_eq!(
+            lookup("a@example.com").unwrap(),
+            accounts_dir.join("a@example.com_2.json")
+        );
+        fs::rename(
+            accounts_dir.join("a@example.com_2.json"),
+            accounts_dir.join("a@example.com.json"),
+        )
+        .unwrap();
+
+        let report = import_accounts(
+            dir.path(),
+            vec![entry("x.json", account_content("a@example.com", 1))],
+            ConflictStrategy::Skip,
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.skipped, 1);
+    }
+}
@@ -0,0 +1,488 @@
+//! 账户导出包（bundle）格式
+//!
+//! 单文件 JSON，由 Rust 端生成和读取，包含清单（manifest）、账户内容、账户元数据以及可选的应用设置：
+//! ```text
+//! {
+//!   "manifest": { "format": "antigravity-agent-bundle", "formatVersion": 1, "minReaderVersion": 1, ... },
+//!   "accounts": [ { "filename": "...", "content": {...}, "metadata": {...} } ],
+//!   "settings": { ... }   (可选)
+//! }
+//! ```
+//!
+//! 版本兼容规则：
+//! - `formatVersion` 为写入方使用的格式版本，`minReaderVersion` 为读取所需的最低版本；
+//!   只有 `minReaderVersion` 高于 [`CURRENT_FORMAT_VERSION`] 时才拒绝读取，未知字段一律忽略。
+//! - 版本 0 为旧版导出格式：裸的 `AccountExportedData` 数组。
+//!
+//! 写入文件时可以按接收者公钥加密（age），或用密码加密为 v2 容器（见 [`seal_with_password`]）。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::backup::{self, AccountExportedData, ConflictStrategy, ImportReport};
+use crate::constants::database;
+use crate::security::crypto;
+
+/// 格式标识
+pub const BUNDLE_FORMAT: &str = "antigravity-agent-bundle";
+
+/// 当前写入的格式版本
+pub const CURRENT_FORMAT_VERSION: u32 = 1;
+
+/// 读取当前版本写出的 bundle 所需的最低版本
+const MIN_READER_VERSION: u32 = 1;
+
+/// bundle 清单
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleManifest {
+    pub format: String,
+    pub format_version: u32,
+    #[serde(default)]
+    pub min_reader_version: u32,
+    /// 生成该 bundle 的 Agent 版本
+    pub agent_version: String,
+    /// 创建时间（RFC 3339）
+    pub created_at: String,
+    pub accounts: Vec<ManifestAccount>,
+    /// 设置内容的 sha256，未包含设置时为 None
+    #[serde(default)]
+    pub settings_sha256: Option<String>,
+}
+
+/// 清单中的账户条目
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestAccount {
+    pub filename: String,
+    #[serde(default)]
+    pub email: Option<String>,
+    /// 账户内容的 sha256（hex）
+    pub sha256: String,
+}
+
+/// 账户元数据（仅供展示，导入时不依赖）
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AccountMetadata {
+    pub email: Option<String>,
+    pub plan_name: Option<String>,
+    /// token 创建时间（秒）
+    pub token_created_at: Option<i64>,
+    /// 账户文件导出时间（秒）
+    pub exported_at: Option<u64>,
+}
+
+/// bundle 中的账户
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleAccount {
+    pub filename: String,
+    pub content: Value,
+    #[serde(default)]
+    pub metadata: AccountMetadata,
+}
+
+/// 完整的 bundle
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AccountBundle {
+    pub manifest: BundleManifest,
+    pub accounts: Vec<BundleAccount>,
+    #[serde(default)]
+    pub settings: Option<Value>,
+}
+
+/// 导入 bundle 的结果
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleImportResult {
+    pub manifest: BundleManifest,
+    pub report: ImportReport,
+    /// bundle 中携带的设置（是否应用由调用方决定）
+    #[serde(skip)]
+    pub settings: Option<Value>,
+}
+
+/// 明文 bundle（含旧版数组）以 `{` 或 `[` 开头，密码加密的 bundle 为 Base64 文本
+fn is_json(bytes: &[u8]) -> bool {
+    matches!(
+        bytes.iter().find(|b| !b.is_ascii_whitespace()),
+        Some(b'{' | b'[')
+    )
+}
+
+fn sha256_hex(value: &Value) -> Result<String, String> {
+    let bytes = serde_json::to_vec(value).map_err(|e| format!("序列化失败: {}", e))?;
+    Ok(hex::encode(Sha256::digest(&bytes)))
+}
+
+fn account_metadata(content: &Value, exported_at: u64) -> AccountMetadata {
+    let msg = content
+        .get(database::AGENT_STATE)
+        .and_then(|v| v.as_str())
+        .and_then(|state| crate::antigravity::account::decode_session_response(state).ok());
+
+    let context = msg.as_ref().and_then(|m| m.context.as_ref());
+    AccountMetadata {
+        email: context.map(|c| c.email.clone()).filter(|e| !e.is_empty()),
+        plan_name: context
+            .map(|c| c.plan_name.clone())
+            .filter(|p| !p.is_empty()),
+        token_created_at: msg
+            .as_ref()
+            .and_then(|m| m.auth.as_ref())
+            .and_then(|a| a.created_at.as_ref())
+            .map(|t| t.seconds),
+        exported_at: Some(exported_at),
+    }
+}
+
+/// 根据账户文件和可选的设置构建 bundle
+pub fn build(
+    accounts: Vec<AccountExportedData>,
+    settings: Option<Value>,
+) -> Result<AccountBundle, String> {
+    let mut manifest_accounts = Vec::with_capacity(accounts.len());
+    let mut bundle_accounts = Vec::with_capacity(accounts.len());
+
+    for account in accounts {
+        let metadata = account_metadata(&account.content, account.timestamp);
+        manifest_accounts.push(ManifestAccount {
+            filename: account.filename.clone(),
+            email: metadata.email.clone(),
+            sha256: sha256_hex(&account.content)?,
+        });
+        bundle_accounts.push(BundleAccount {
+            filename: account.filename,
+            content: account.content,
+            metadata,
+        });
+    }
+
+    let settings_sha256 = settings.as_ref().map(sha256_hex).transpose()?;
+
+    Ok(AccountBundle {
+        manifest: BundleManifest {
+            format: BUNDLE_FORMAT.to_string(),
+            format_version: CURRENT_FORMAT_VERSION,
+            min_reader_version: MIN_READER_VERSION,
+            agent_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: chrono::Local::now().to_rfc3339(),
+            accounts: manifest_accounts,
+            settings_sha256,
+        },
+        accounts: bundle_accounts,
+        settings,
+    })
+}
+
+/// 解析 bundle，兼容旧版（版本 0）导出格式并校验 checksum
+pub fn parse(bytes: &[u8]) -> Result<AccountBundle, String> {
+    let value: Value =
+        serde_json::from_slice(bytes).map_err(|e| format!("解析导出文件失败: {}", e))?;
+
+    // 版本 0：旧版导出的裸数组
+    if value.is_array() {
+        let accounts: Vec<AccountExportedData> =
+            serde_json::from_value(value).map_err(|e| format!("解析旧版导出文件失败: {}", e))?;
+        let mut bundle = build(accounts, None)?;
+        bundle.manifest.format_version = 0;
+        bundle.manifest.min_reader_version = 0;
+        bundle.manifest.agent_version = "unknown".to_string();
+        return Ok(bundle);
+    }
+
+    let manifest = value
+        .get("manifest")
+        .cloned()
+        .ok_or_else(|| "导出文件缺少 manifest".to_string())?;
+    let manifest: BundleManifest =
+        serde_json::from_value(manifest).map_err(|e| format!("解析 manifest 失败: {}", e))?;
+
+    if manifest.format != BUNDLE_FORMAT {
+        return Err(format!("未知的导出文件格式: {}", manifest.format));
+    }
+    if manifest.min_reader_version > CURRENT_FORMAT_VERSION {
+        return Err(format!(
+            "导出文件由更新版本的 Antigravity Agent ({}) 创建，格式版本 {} 需要读取版本 >= {}，请升级后再导入",
+            manifest.agent_version, manifest.format_version, manifest.min_reader_version
+        ));
+    }
+
+    let bundle: AccountBundle =
+        serde_json::from_value(value).map_err(|e| format!("解析导出文件失败: {}", e))?;
+    verify(&bundle)?;
+
+    Ok(bundle)
+}
+
+/// 用密码把 bundle 加密为 v2 容器
+///
+/// 清单的 sha256 作为关联数据：无需密码即可从头部读出，解密后再与清单比对，
+/// 保证清单与加密内容是同一次导出的产物。
+pub fn seal_with_password(bundle: &AccountBundle, password: &str) -> Result<String, String> {
+    let json =
+        serde_json::to_vec_pretty(bundle).map_err(|e| format!("序列化导出文件失败: {}", e))?;
+    let manifest = serde_json::to_value(&bundle.manifest)
+        .map_err(|e| format!("序列化 manifest 失败: {}", e))?;
+    let manifest_sha256 = sha256_hex(&manifest)?;
+
+    crypto::encrypt_with_options(
+        &json,
+        password,
+        &crypto::EncryptOptions {
+            associated_data: manifest_sha256.as_bytes(),
+            ..Default::default()
+        },
+    )
+}
+
+/// 解密 [`seal_with_password`] 生成的 bundle，并校验关联数据中的清单哈希
+pub fn open_with_password(data: &str, password: &str) -> Result<AccountBundle, String> {
+    let decrypted = crypto::decrypt_with_options(data, password, None)?;
+    if decrypted.format != crypto::EncryptedFormat::V2 {
+        return Err("导出文件不是 bundle 加密格式".to_string());
+    }
+
+    let value: Value = serde_json::from_str(&decrypted.plaintext)
+        .map_err(|e| format!("解析导出文件失败: {}", e))?;
+    let manifest = value
+        .get("manifest")
+        .ok_or_else(|| "导出文件缺少 manifest".to_string())?;
+    if decrypted.associated_data != sha256_hex(manifest)?.as_bytes() {
+        return Err("导出文件已损坏：manifest 与加密头部不一致".to_string());
+    }
+
+    parse(decrypted.plaintext.as_bytes())
+}
+
+/// 校验清单与内容是否一致
+fn verify(bundle: &AccountBundle) -> Result<(), String> {
+    if bundle.manifest.accounts.len() != bundle.accounts.len() {
+        return Err(format!(
+            "导出文件已损坏：清单记录 {} 个账户，实际包含 {} 个",
+            bundle.manifest.accounts.len(),
+            bundle.accounts.len()
+        ));
+    }
+
+    for (entry, account) in bundle.manifest.accounts.iter().zip(&bundle.accounts) {
+        if entry.filename != account.filename || entry.sha256 != sha256_hex(&account.content)? {
+            return Err(format!(
+                "导出文件已损坏：账户 {} 校验失败",
+                account.filename
+            ));
+        }
+    }
+
+    match (&bundle.manifest.settings_sha256, &bundle.settings) {
+        (Some(expected), Some(settings)) if *expected == sha256_hex(settings)? => Ok(()),
+        (None, None) => Ok(()),
+        _ => Err("导出文件已损坏：设置校验失败".to_string()),
+    }
+}
+
+/// 本地接口使用的 bundle 文件路径：只接受文件名，固定位于 bundle 目录下
+pub fn bundle_path(file_name: &str) -> Result<PathBuf, String> {
+    backup::validate_import_filename(file_name)?;
+    Ok(crate::directories::get_bundles_directory().join(file_name))
+}
+
+/// 导出所有账户（以及可选的设置）到 bundle 文件
+///
+/// `recipients` 非空时，整个 bundle 按接收者公钥加密（同时加密给本机）；提供 `password` 时
+/// 用密码加密（两者只能选其一）。bundle 中包含所有账户的 token，两者都未提供时只有
+/// `allow_plaintext` 为 true 才以明文写出。
+pub async fn export_to_file(
+    config_dir: &Path,
+    path: &Path,
+    settings: Option<Value>,
+    recipients: &[String],
+    password: Option<&str>,
+    allow_plaintext: bool,
+) -> Result<BundleManifest, String> {
+    if !recipients.is_empty() && password.is_some() {
+        return Err("接收者公钥和密码只能选择一种加密方式".to_string());
+    }
+    if recipients.is_empty() && password.is_none() && !allow_plaintext {
+        return Err(
+            "未指定接收者公钥或密码，拒绝导出明文 token（如确需明文导出请显式允许）".to_string(),
+        );
+    }
+
+    let accounts = backup::collect_contents(config_dir).await?;
+    let bundle = build(accounts, settings)?;
+
+    let json = match password {
+        Some(password) => seal_with_password(&bundle, password)?,
+        None => {
+            let json = serde_json::to_string_pretty(&bundle)
+                .map_err(|e| format!("序列化导出文件失败: {}", e))?;
+            if recipients.is_empty() {
+                json
+            } else {
+                crate::security::recipients::encrypt_for_recipients(
+                    json.as_bytes(),
+                    recipients,
+                    true,
+                )?
+            }
+        }
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建导出目录失败: {}", e))?;
+    }
+    fs::write(path, json).map_err(|e| format!("写入导出文件失败: {}", e))?;
+
+    tracing::info!(
+        target: "bundle::export",
+        path = %path.display(),
+        account_count = bundle.manifest.accounts.len(),
+        recipient_count = recipients.len(),
+        "✅ 导出 bundle 完成"
+    );
+    Ok(bundle.manifest)
+}
+
+/// 从 bundle 文件导入账户，账户经过与 `import_accounts` 相同的校验和冲突处理
+///
+/// 发给本机公钥的加密 bundle 会先用本机私钥解密，密码加密的 bundle 需要提供 `password`。
+pub async fn import_from_file(
+    config_dir: &Path,
+    path: &Path,
+    strategy: ConflictStrategy,
+    dry_run: bool,
+    password: Option<&str>,
+) -> Result<BundleImportResult, String> {
+    let mut bytes = fs::read(path).map_err(|e| format!("读取导出文件失败: {}", e))?;
+    if crate::security::recipients::is_age_encrypted(&bytes) {
+        bytes = crate::security::recipients::decrypt_for_self(&bytes)?;
+    }
+    let bundle = if is_json(&bytes) {
+        parse(&bytes)?
+    } else {
+        let password = password.ok_or_else(|| "该导出文件已用密码加密，请提供密码".to_string())?;
+        let data = std::str::from_utf8(&bytes).map_err(|_| "导出文件格式无效".to_string())?;
+        open_with_password(data, password)?
+    };
+
+    tracing::info!(
+        target: "bundle::import",
+        format_version = bundle.manifest.format_version,
+        agent_version = %bundle.manifest.agent_version,
+        account_count = bundle.accounts.len(),
+        "开始导入 bundle"
+    );
+
+    let entries = bundle
+        .accounts
+        .into_iter()
+        .map(|account| AccountExportedData {
+            filename: account.filename,
+            content: account.content,
+            timestamp: account.metadata.exported_at.unwrap_or_default(),
+        })
+        .collect();
+    let report = backup::import_accounts(config_dir, entries, strategy, dry_run).await?;
+
+    Ok(BundleImportResult {
+        manifest: bundle.manifest,
+        report,
+        settings: bundle.settings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legacy_export() -> Value {
+        serde_json::json!([
+            { "filename": "a@example.com.json", "content": { database::AGENT_STATE: "" }, "timestamp": 1 }
+        ])
+    }
+
+    #[test]
+    fn test_roundtrip_and_tamper_detection() {
+        let accounts: Vec<AccountExportedData> = serde_json::from_value(legacy_export()).unwrap();
+        let bundle = build(accounts, Some(serde_json::json!({ "language": "zh" }))).unwrap();
+        let bytes = serde_json::to_vec(&bundle).unwrap();
+
+        let parsed = parse(&bytes).unwrap();
+        assert_eq!(parsed.manifest.format_version, CURRENT_FORMAT_VERSION);
+        assert_eq!(parsed.accounts.len(), 1);
+        assert!(parsed.settings.is_some());
+
+        let mut tampered = bundle.clone();
+        tampered.accounts[0].content = serde_json::json!({ "x": 1 });
+        assert!(parse(&serde_json::to_vec(&tampered).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_bundle_path_stays_in_bundles_directory() {
+        for name in ["../evil.json", "/etc/passwd", "a/b.json", ".hidden", ""] {
+            assert!(bundle_path(name).is_err(), "{:?} 应被拒绝", name);
+        }
+        assert_eq!(
+            bundle_path("export.json").unwrap(),
+            crate::directories::get_bundles_directory().join("export.json")
+        );
+    }
+
+    #[test]
+    fn test_password_sealed_bundle_binds_manifest() {
+        let accounts: Vec<AccountExportedData> = serde_json::from_value(legacy_export()).unwrap();
+        let bundle = build(accounts, None).unwrap();
+        let sealed = seal_with_password(&bundle, "pw").unwrap();
+        assert!(!is_json(sealed.as_bytes()));
+
+        let opened = open_with_password(&sealed, "pw").unwrap();
+        assert_eq!(opened.manifest.accounts.len(), 1);
+        assert!(open_with_password(&sealed, "wrong").is_err());
+
+        // 头部关联数据与清单不一致（例如换成另一次导出的清单哈希）时拒绝
+        let mut other = bundle.clone();
+        other.manifest.created_at = "2000-01-01T00:00:00Z".to_string();
+        let json = serde_json::to_vec(&bundle).unwrap();
+        let other_hash = sha256_hex(&serde_json::to_value(&other.manifest).unwrap()).unwrap();
+        let mismatched = crypto::encrypt_with_options(
+            &json,
+            "pw",
+            &crypto::EncryptOptions {
+                associated_data: other_hash.as_bytes(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let err = open_with_password(&mismatched, "pw").unwrap_err();
+        assert!(err.contains("manifest"));
+    }
+
+    #[test]
+    fn test_read_legacy_version_0() {
+        let bytes = serde_json::to_vec(&legacy_export()).unwrap();
+        let parsed = parse(&bytes).unwrap();
+        assert_eq!(parsed.manifest.format_version, 0);
+        assert_eq!(parsed.accounts[0].filename, "a@example.com.json");
+        assert!(parsed.settings.is_none());
+    }
+
+    #[test]
+    fn test_forward_compatibility() {
+        let accounts: Vec<AccountExportedData> = serde_json::from_value(legacy_export()).unwrap();
+        let mut value = serde_json::to_value(build(accounts, None).unwrap()).unwrap();
+
+        // 更新版本新增的字段被忽略，只要 minReaderVersion 允许即可读取
+        value["manifest"]["formatVersion"] = 2.into();
+        value["manifest"]["compression"] = "none".into();
+        value["extra"] = serde_json::json!({ "future": true });
+        assert!(parse(&serde_json::to_vec(&value).unwrap()).is_ok());
+
+        value["manifest"]["minReaderVersion"] = (CURRENT_FORMAT_VERSION + 1).into();
+        let err = parse(&serde_json::to_vec(&value).unwrap()).unwrap_err();
+        assert!(err.contains("升级"));
+    }
+}
@@ -0,0 +1,351 @@
+//! `antigravity-agent://` 深链接
+//!
+//! 支持的链接：
+//!
+//! - `antigravity-agent://switch/<email>`：切换到指定账户（需确认）
+//! - `antigravity-agent://save-current`：保存当前登录的账户
+//! - `antigravity-agent://import?file=<path>`：从 bundle 文件导入账户（需确认，冲突时保留较新的一份）
+//!
+//! 系统把链接作为启动参数交给应用（macOS 为 `Opened` 事件），已有实例在运行时由其处理，
+//! 见 [`super::launch`]。链接可能来自任意网页或聊天消息，参数全部在这里校验，
+//! 会修改账户的操作执行前先弹窗确认。
+//!
+//! 协议注册：macOS 由应用包的 `Info.plist` 声明；Linux 和 Windows 在每次启动时检查并注册到当前可执行文件
+//! （Linux 写入 `~/.local/share/applications` 下的 desktop 文件并调用 `xdg-mime`，
+//! Windows 写入 `HKCU\Software\Classes`）。
+
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+/// URL scheme
+pub const SCHEME: &str = "antigravity-agent";
+
+/// 深链接对应的操作
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeepLink {
+    Switch { email: String },
+    SaveCurrent,
+    Import { file: PathBuf },
+}
+
+impl DeepLink {
+    /// 解析并校验链接
+    pub fn parse(link: &str) -> Result<Self, String> {
+        let url = url::Url::parse(link).map_err(|e| format!("无效的链接: {}", e))?;
+        if url.scheme() != SCHEME {
+            return Err(format!("不支持的链接协议: {}", url.scheme()));
+        }
+
+        let action = url.host_str().unwrap_or_default();
+        let segments: Vec<String> = url
+            .path_segments()
+            .map(|segments| {
+                segments
+                    .filter(|s| !s.is_empty())
+                    .map(|s| {
+                        percent_encoding::percent_decode_str(s)
+                            .decode_utf8_lossy()
+                            .to_string()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        match (action, segments.as_slice()) {
+            ("switch", [email]) => {
+                super::account::validate_account_name(email)?;
+                if !email.contains('@') {
+                    return Err("链接中的账户邮箱无效".to_string());
+                }
+                Ok(Self::Switch {
+                    email: email.clone(),
+                })
+            }
+            ("save-current", []) => Ok(Self::SaveCurrent),
+            ("import", []) => {
+                let file = url
+                    .query_pairs()
+                    .find(|(key, _)| key == "file")
+                    .map(|(_, value)| PathBuf::from(value.as_ref()))
+                    .ok_or_else(|| "导入链接缺少 file 参数".to_string())?;
+                if !file.is_absolute() {
+                    return Err("导入文件必须是绝对路径".to_string());
+                }
+                Ok(Self::Import { file })
+            }
+            _ => Err(format!("不支持的链接: {}", link)),
+        }
+    }
+
+    /// 是否会修改账户数据或 Antigravity 的登录状态
+    pub fn is_destructive(&self) -> bool {
+        !matches!(self, Self::SaveCurrent)
+    }
+
+    /// 确认弹窗文案
+    fn confirmation(&self, zh: bool) -> String {
+        match (self, zh) {
+            (Self::Switch { email }, true) => {
+                format!("链接请求切换到账户 {}。\n\n将关闭并重启 Antigravity，是否继续？", email)
+            }
+            (Self::Switch { email }, false) => format!(
+                "A link requested switching to {}.\n\nAntigravity will be closed and restarted. Continue?",
+                email
+            ),
+            (Self::Import { file }, true) => format!(
+                "链接请求从以下文件导入账户：\n{}\n\n同名账户将保留较新的一份，是否继续？",
+                file.display()
+            ),
+            (Self::Import { file }, false) => format!(
+                "A link requested importing accounts from:\n{}\n\nExisting accounts keep the newer copy. Continue?",
+                file.display()
+            ),
+            (Self::SaveCurrent, _) => String::new(),
+        }
+    }
+}
+
+/// 处理深链接：校验、按需确认后执行
+pub async fn handle(app: &AppHandle, link: &str) -> Result<String, String> {
+    let deep_link = DeepLink::parse(link)?;
+    tracing::info!(target: "app::deep_link", link = ?deep_link, "收到深链接");
+
+    if deep_link.is_destructive() && !confirm(app, &deep_link).await {
+        tracing::info!(target: "app::deep_link", "用户取消了深链接操作");
+        return Err("操作已取消".to_string());
+    }
+
+    match deep_link {
+        DeepLink::Switch { email } => super::account::switch(email).await,
+        DeepLink::SaveCurrent => super::account::backup_current().await,
+        DeepLink::Import { file } => {
+            let config_dir = {
+                let state = app.state::<crate::AppState>();
+                let inner = state.inner.lock();
+                inner.config_dir.clone()
+            };
+            let result = super::bundle::import_from_file(
+                &config_dir,
+                &file,
+                super::backup::ConflictStrategy::KeepNewer,
+                false,
+                None,
+            )
+            .await?;
+            let report = result.report;
+            Ok(format!(
+                "导入完成：新增 {}，覆盖 {}，跳过 {}，失败 {}",
+                report.created,
+                report.overwritten,
+                report.skipped,
+                report.invalid + report.failed
+            ))
+        }
+    }
+}
+
+/// 弹窗确认，用户点击确认时返回 `true`
+async fn confirm(app: &AppHandle, deep_link: &DeepLink) -> bool {
+    let zh = app
+        .state::<crate::app_settings::AppSettingsManager>()
+        .get_settings()
+        .language
+        .starts_with("zh");
+    let (ok, cancel) = if zh {
+        ("继续", "取消")
+    } else {
+        ("Continue", "Cancel")
+    };
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .message(deep_link.confirmation(zh))
+        .title("Antigravity Agent")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            ok.to_string(),
+            cancel.to_string(),
+        ))
+        .show(move |confirmed| {
+            let _ = tx.send(confirmed);
+        });
+    rx.await.unwrap_or(false)
+}
+
+/// 把 URL scheme 注册到当前可执行文件（已注册时跳过）
+pub fn register_scheme() {
+    let exe = match current_executable() {
+        Ok(exe) => exe,
+        Err(e) => {
+            tracing::warn!(target: "app::deep_link", "无法获取可执行文件路径，跳过协议注册: {}", e);
+            return;
+        }
+    };
+
+    match register_for(&exe) {
+        Ok(true) => tracing::info!(
+            target: "app::deep_link",
+            "已注册 {}:// 协议: {}",
+            SCHEME,
+            exe.display()
+        ),
+        Ok(false) => {}
+        Err(e) => tracing::warn!(target: "app::deep_link", "注册 {}:// 协议失败: {}", SCHEME, e),
+    }
+}
+
+/// 当前可执行文件；AppImage 运行时为 AppImage 文件本身
+fn current_executable() -> std::io::Result<PathBuf> {
+    if let Some(appimage) = std::env::var_os("APPIMAGE") {
+        return Ok(PathBuf::from(appimage));
+    }
+    std::env::current_exe()
+}
+
+/// Linux：desktop 文件 + xdg-mime，返回是否有改动
+#[cfg(target_os = "linux")]
+fn register_for(exe: &std::path::Path) -> Result<bool, String> {
+    use std::process::Command;
+
+    let desktop_name = format!("{}-url-handler.desktop", SCHEME);
+    let applications = dirs::data_dir()
+        .ok_or_else(|| "找不到用户数据目录".to_string())?
+        .join("applications");
+    let desktop_file = applications.join(&desktop_name);
+    let content = desktop_entry(exe);
+
+    if std::fs::read_to_string(&desktop_file).is_ok_and(|existing| existing == content) {
+        return Ok(false);
+    }
+
+    std::fs::create_dir_all(&applications).map_err(|e| format!("创建目录失败: {}", e))?;
+    std::fs::write(&desktop_file, content).map_err(|e| format!("写入 desktop 文件失败: {}", e))?;
+
+    let mime = format!("x-scheme-handler/{}", SCHEME);
+    let status = Command::new("xdg-mime")
+        .args(["default", &desktop_name, &mime])
+        .status()
+        .map_err(|e| format!("执行 xdg-mime 失败: {}", e))?;
+    if !status.success() {
+        return Err(format!("xdg-mime 退出码: {}", status));
+    }
+
+    // 仅用于刷新缓存，不存在时忽略
+    let _ = Command::new("update-desktop-database")
+        .arg(&applications)
+        .status();
+    Ok(true)
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn desktop_entry(exe: &std::path::Path) -> String {
+    format!(
+        "[Desktop Entry]\nType=Application\nName=Antigravity Agent\nExec=\"{}\" %u\nTerminal=false\nNoDisplay=true\nMimeType=x-scheme-handler/{};\n",
+        exe.display(),
+        SCHEME
+    )
+}
+
+/// Windows：写入 HKCU\Software\Classes，返回是否有改动
+#[cfg(windows)]
+fn register_for(exe: &std::path::Path) -> Result<bool, String> {
+    use std::os::windows::process::CommandExt;
+    use std::process::Command;
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    let key = format!(r"HKCU\Software\Classes\{}", SCHEME);
+    let command_key = format!(r"{}\shell\open\command", key);
+    let command = format!("\"{}\" \"%1\"", exe.display());
+
+    let registered = Command::new("reg")
+        .args(["query", &command_key, "/ve"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains(&command));
+    if registered {
+        return Ok(false);
+    }
+
+    let entries: [&[&str]; 3] = [
+        &["add", &key, "/ve", "/d", "URL:Antigravity Agent", "/f"],
+        &["add", &key, "/v", "URL Protocol", "/d", "", "/f"],
+        &["add", &command_key, "/ve", "/d", &command, "/f"],
+    ];
+    for args in entries {
+        let status = Command::new("reg")
+            .args(args)
+            .creation_flags(CREATE_NO_WINDOW)
+            .status()
+            .map_err(|e| format!("执行 reg 失败: {}", e))?;
+        if !status.success() {
+            return Err(format!("reg 退出码: {}", status));
+        }
+    }
+    Ok(true)
+}
+
+/// macOS：由应用包的 Info.plist 声明
+#[cfg(not(any(target_os = "linux", windows)))]
+fn register_for(_exe: &std::path::Path) -> Result<bool, String> {
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_deep_links() {
+        assert_eq!(
+            DeepLink::parse("antigravity-agent://switch/user@x.com"),
+            Ok(DeepLink::Switch {
+                email: "user@x.com".to_string()
+            })
+        );
+        assert_eq!(
+            DeepLink::parse("antigravity-agent://switch/user%40x.com/"),
+            Ok(DeepLink::Switch {
+                email: "user@x.com".to_string()
+            })
+        );
+        assert_eq!(
+            DeepLink::parse("antigravity-agent://save-current"),
+            Ok(DeepLink::SaveCurrent)
+        );
+
+        let import =
+            DeepLink::parse("antigravity-agent://import?file=%2Ftmp%2Fbackup.json").unwrap();
+        assert_eq!(
+            import,
+            DeepLink::Import {
+                file: PathBuf::from("/tmp/backup.json")
+            }
+        );
+        assert!(import.is_destructive());
+        assert!(!DeepLink::SaveCurrent.is_destructive());
+    }
+
+    #[test]
+    fn test_reject_invalid_deep_links() {
+        for link in [
+            "https://switch/user@x.com",
+            "antigravity-agent://switch/",
+            "antigravity-agent://switch/..%2F..%2Fetc",
+            "antigravity-agent://switch/not-an-email",
+            "antigravity-agent://switch/a@x.com/extra",
+            "antigravity-agent://import",
+            "antigravity-agent://import?file=relative.json",
+            "antigravity-agent://delete-all",
+        ] {
+            assert!(DeepLink::parse(link).is_err(), "{} 应被拒绝", link);
+        }
+    }
+
+    #[test]
+    fn test_desktop_entry() {
+        let entry = desktop_entry(std::path::Path::new("/opt/Antigravity Agent/agent"));
+        assert!(entry.contains("Exec=\"/opt/Antigravity Agent/agent\" %u\n"));
+        assert!(entry.contains("MimeType=x-scheme-handler/antigravity-agent;\n"));
+    }
+}
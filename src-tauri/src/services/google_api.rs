@@ -1,12 +1,88 @@
 use base64::Engine;
+use parking_lot::RwLock;
 use prost::Message;
-use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, USER_AGENT};
-use serde::Deserialize;
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
 use tracing::{error, info};
 
+use super::http_retry::{self, CircuitBreaker, RetryPolicy};
+
 pub const CLOUD_CODE_BASE_URL: &str = "https://daily-cloudcode-pa.sandbox.googleapis.com";
+pub const OAUTH_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+pub const USERINFO_URL: &str = "https://www.googleapis.com/oauth2/v2/userinfo";
+
+/// Google 相关接口地址，默认指向官方服务，可在网络设置中改为镜像或测试服务
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiEndpoints {
+    /// Cloud Code 服务根地址（`loadCodeAssist`、`fetchAvailableModels` 等）
+    pub cloud_code_base_url: String,
+    /// OAuth token 刷新地址
+    pub oauth_token_url: String,
+    /// 用户信息地址
+    pub userinfo_url: String,
+}
+
+impl Default for ApiEndpoints {
+    fn default() -> Self {
+        Self {
+            cloud_code_base_url: CLOUD_CODE_BASE_URL.to_string(),
+            oauth_token_url: OAUTH_TOKEN_URL.to_string(),
+            userinfo_url: USERINFO_URL.to_string(),
+        }
+    }
+}
+
+impl ApiEndpoints {
+    /// 校验地址格式，返回去掉首尾空白和末尾 `/` 的副本
+    pub fn normalized(&self) -> Result<Self, String> {
+        fn check(name: &str, url: &str) -> Result<String, String> {
+            let url = url.trim().trim_end_matches('/');
+            let parsed = reqwest::Url::parse(url)
+                .map_err(|e| format!("无效的 {} 地址 {}: {}", name, url, e))?;
+            if !matches!(parsed.scheme(), "http" | "https") {
+                return Err(format!("{} 地址必须使用 http 或 https: {}", name, url));
+            }
+            Ok(url.to_string())
+        }
+
+        Ok(Self {
+            cloud_code_base_url: check("Cloud Code", &self.cloud_code_base_url)?,
+            oauth_token_url: check("OAuth token", &self.oauth_token_url)?,
+            userinfo_url: check("userinfo", &self.userinfo_url)?,
+        })
+    }
+}
+
+lazy_static::lazy_static! {
+    /// 当前生效的接口地址
+    static ref ENDPOINTS: RwLock<ApiEndpoints> = RwLock::new(ApiEndpoints::default());
+}
+
+/// 更新接口地址，地址无效时保留原配置
+pub fn configure_endpoints(endpoints: &ApiEndpoints) -> Result<(), String> {
+    let endpoints = endpoints.normalized()?;
+    if endpoints != ApiEndpoints::default() {
+        tracing::info!(
+            target: "google_api",
+            cloud_code = %endpoints.cloud_code_base_url,
+            oauth_token = %endpoints.oauth_token_url,
+            userinfo = %endpoints.userinfo_url,
+            "使用自定义 Google 接口地址"
+        );
+    }
+    *ENDPOINTS.write() = endpoints;
+    Ok(())
+}
+
+/// 按当前接口地址创建的默认客户端
+pub fn default_client() -> HttpCloudCodeClient {
+    HttpCloudCodeClient::new(ENDPOINTS.read().clone())
+}
 
 #[derive(Deserialize)]
 pub struct UserInfoResponse {
@@ -19,6 +95,329 @@ pub struct RefreshTokenResponse {
     pub access_token: String,
 }
 
+/// 请求失败的分类，用于区分网络问题、限流、服务端故障与凭据失效
+#[derive(Debug)]
+pub enum ApiError {
+    /// 请求未得到响应（网络、超时、DNS 等）
+    Network(String),
+    /// 响应成功但内容无法解析或缺少必要字段，重试无济于事
+    InvalidResponse(String),
+    /// 401：凭据无效或已过期
+    Unauthorized,
+    /// 429：请求过于频繁（重试后仍被限流）
+    RateLimited { retry_after: Option<Duration> },
+    /// 5xx：服务端故障（重试后仍失败）
+    Server(reqwest::StatusCode),
+    /// 其他非成功状态码
+    Status(reqwest::StatusCode),
+    /// 该主机连续失败，暂停访问中
+    CircuitOpen { host: String, retry_in: Duration },
+    /// 本地配置问题（如缺少 OAuth 凭据）
+    Config(String),
+}
+
+impl ApiError {
+    /// 根据非成功响应分类
+    pub fn from_response(res: &reqwest::Response) -> Self {
+        let status = res.status();
+        match status {
+            reqwest::StatusCode::UNAUTHORIZED => ApiError::Unauthorized,
+            reqwest::StatusCode::TOO_MANY_REQUESTS => ApiError::RateLimited {
+                retry_after: super::http_retry::parse_retry_after(res.headers()),
+            },
+            s if s.is_server_error() => ApiError::Server(s),
+            s => ApiError::Status(s),
+        }
+    }
+
+    /// 响应状态码（仅对收到响应的错误）
+    pub fn status(&self) -> Option<reqwest::StatusCode> {
+        match self {
+            ApiError::Unauthorized => Some(reqwest::StatusCode::UNAUTHORIZED),
+            ApiError::RateLimited { .. } => Some(reqwest::StatusCode::TOO_MANY_REQUESTS),
+            ApiError::Server(status) | ApiError::Status(status) => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiError::Network(e) | ApiError::InvalidResponse(e) | ApiError::Config(e) => {
+                write!(f, "{}", e)
+            }
+            ApiError::RateLimited {
+                retry_after: Some(wait),
+            } => write!(f, "Rate limited, retry after {}s", wait.as_secs()),
+            ApiError::CircuitOpen { host, retry_in } => write!(
+                f,
+                "{} 连续请求失败，已暂停访问，{} 秒后重试",
+                host,
+                retry_in.as_secs().max(1)
+            ),
+            other => write!(f, "Status: {}", other.status().unwrap_or_default()),
+        }
+    }
+}
+
+/// 读取响应体失败时的分类：内容无法解码属于无效响应，其余为网络错误
+fn body_error(e: reqwest::Error) -> ApiError {
+    if e.is_decode() {
+        ApiError::InvalidResponse(e.to_string())
+    } else {
+        ApiError::Network(e.to_string())
+    }
+}
+
+/// Google 账户与 Cloud Code 接口
+///
+/// 业务代码只依赖该 trait，测试中可以替换为指向本地 mock 服务的实现。
+#[async_trait::async_trait]
+pub trait CloudCodeClient: Send + Sync {
+    /// 使用 access token 获取用户信息
+    async fn fetch_user_info(&self, access_token: &str) -> Result<UserInfoResponse, ApiError>;
+
+    /// 使用 refresh token 换取新的 access token
+    async fn refresh_access_token(&self, refresh_token: &str) -> Result<String, ApiError>;
+
+    /// 获取账户对应的 Cloud Code project id
+    async fn fetch_code_assist_project(&self, access_token: &str) -> Result<String, ApiError>;
+
+    /// 获取可用模型及配额信息
+    async fn fetch_available_models(
+        &self,
+        access_token: &str,
+        project: &str,
+    ) -> Result<Value, ApiError>;
+
+    /// 向指定模型发送一条最小请求，用于触发配额刷新
+    async fn trigger_minimal_query(
+        &self,
+        access_token: &str,
+        project: &str,
+        model_key: &str,
+    ) -> Result<(), ApiError>;
+}
+
+/// 基于共享 HTTP 客户端的真实实现
+pub struct HttpCloudCodeClient {
+    endpoints: ApiEndpoints,
+    /// 为 None 时从凭据存储读取 OAuth client id / secret
+    oauth_credentials: Option<(String, String)>,
+    retry: RetryPolicy,
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl HttpCloudCodeClient {
+    pub fn new(endpoints: ApiEndpoints) -> Self {
+        Self {
+            endpoints,
+            oauth_credentials: None,
+            retry: RetryPolicy::default(),
+            breaker: http_retry::shared_breaker(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// 使用独立的熔断器，不与默认客户端共享失败计数
+    #[cfg(test)]
+    pub fn with_circuit_breaker(mut self, breaker: Arc<CircuitBreaker>) -> Self {
+        self.breaker = breaker;
+        self
+    }
+
+    /// 使用指定的 OAuth 凭据，不再读取凭据存储
+    #[cfg(test)]
+    pub fn with_oauth_credentials(mut self, client_id: &str, client_secret: &str) -> Self {
+        self.oauth_credentials = Some((client_id.to_string(), client_secret.to_string()));
+        self
+    }
+
+    fn resolve_oauth_credentials(&self) -> Result<(String, String), ApiError> {
+        if let Some(credentials) = &self.oauth_credentials {
+            return Ok(credentials.clone());
+        }
+
+        // 使用安全的凭据管理模块获取 OAuth 凭据
+        let config_dir = crate::directories::get_config_directory();
+        crate::security::credentials::resolve_oauth_credentials(&config_dir)
+            .map_err(ApiError::Config)
+    }
+}
+
+#[async_trait::async_trait]
+impl CloudCodeClient for HttpCloudCodeClient {
+    async fn fetch_user_info(&self, access_token: &str) -> Result<UserInfoResponse, ApiError> {
+        let request = super::http_client::client()
+            .get(&self.endpoints.userinfo_url)
+            .header(AUTHORIZATION, format!("Bearer {}", access_token));
+        let res = http_retry::send_with_retry(request, &self.retry, &self.breaker).await?;
+
+        if !res.status().is_success() {
+            return Err(ApiError::from_response(&res));
+        }
+
+        res.json::<UserInfoResponse>().await.map_err(body_error)
+    }
+
+    async fn refresh_access_token(&self, refresh_token: &str) -> Result<String, ApiError> {
+        let (client_id, client_secret) = self.resolve_oauth_credentials()?;
+        let params = [
+            ("client_id", client_id.as_str()),
+            ("client_secret", client_secret.as_str()),
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+        ];
+
+        let request = super::http_client::client()
+            .post(&self.endpoints.oauth_token_url)
+            .form(&params);
+        let res = http_retry::send_with_retry(request, &self.retry, &self.breaker).await?;
+
+        if !res.status().is_success() {
+            return Err(ApiError::from_response(&res));
+        }
+
+        let json: RefreshTokenResponse = res.json().await.map_err(body_error)?;
+        Ok(json.access_token)
+    }
+
+    async fn fetch_code_assist_project(&self, access_token: &str) -> Result<String, ApiError> {
+        let request = super::http_client::client()
+            .post(format!(
+                "{}/v1internal:loadCodeAssist",
+                self.endpoints.cloud_code_base_url
+            ))
+            .timeout(Duration::from_secs(5))
+            .header(AUTHORIZATION, format!("Bearer {}", access_token))
+            .header(CONTENT_TYPE, "application/json")
+            .body(r#"{"metadata": {"ideType": "ANTIGRAVITY"}}"#);
+        let res = http_retry::send_with_retry(request, &self.retry, &self.breaker).await?;
+
+        if !res.status().is_success() {
+            let err = ApiError::from_response(&res);
+            let text = res.text().await.unwrap_or_default();
+            error!("loadCodeAssist failed ({}): {:.500}", err, text);
+            return Err(err);
+        }
+
+        let text = res
+            .text()
+            .await
+            .map_err(|e| ApiError::Network(e.to_string()))?;
+        let json: Value = serde_json::from_str(&text).map_err(|e| {
+            ApiError::InvalidResponse(format!(
+                "Failed to parse project response: {} | Raw Body: {:.100}",
+                e, text
+            ))
+        })?;
+
+        let project_id = json
+            .get("cloudaicompanionProject")
+            .or_else(|| json.get("project"))
+            .or_else(|| json.get("projectId"))
+            .and_then(|v| v.as_str());
+
+        match project_id {
+            Some(id) => Ok(id.to_string()),
+            None => Err(ApiError::InvalidResponse(
+                "Project ID missing in loadCodeAssist response".to_string(),
+            )),
+        }
+    }
+
+    async fn fetch_available_models(
+        &self,
+        access_token: &str,
+        project: &str,
+    ) -> Result<Value, ApiError> {
+        let body = serde_json::json!({ "project": project });
+
+        let request = super::http_client::client()
+            .post(format!(
+                "{}/v1internal:fetchAvailableModels",
+                self.endpoints.cloud_code_base_url
+            ))
+            .timeout(Duration::from_secs(5))
+            .header(AUTHORIZATION, format!("Bearer {}", access_token))
+            .header(CONTENT_TYPE, "application/json")
+            .json(&body);
+        let res = http_retry::send_with_retry(request, &self.retry, &self.breaker).await?;
+
+        if !res.status().is_success() {
+            let err = ApiError::from_response(&res);
+            let text = res.text().await.unwrap_or_default();
+            error!("fetchAvailableModels failed ({}): {:.500}", err, text);
+            return Err(err);
+        }
+
+        let text = res
+            .text()
+            .await
+            .map_err(|e| ApiError::Network(e.to_string()))?;
+        serde_json::from_str(&text).map_err(|e| {
+            error!(
+                "JSON parse failed for fetchAvailableModels. Raw body: {}",
+                text
+            );
+            ApiError::InvalidResponse(format!(
+                "Failed to parse models JSON: {} | Raw Body: {:.500}",
+                e, text
+            ))
+        })
+    }
+
+    async fn trigger_minimal_query(
+        &self,
+        access_token: &str,
+        project: &str,
+        model_key: &str,
+    ) -> Result<(), ApiError> {
+        let url = format!(
+            "{}/v1internal:generateContent",
+            self.endpoints.cloud_code_base_url
+        );
+
+        let body = serde_json::json!({
+            "project": project,
+            "model": model_key,
+            "request": {
+                "contents": [
+                    {
+                        "role": "user",
+                        "parts": [{ "text": format!("Hi [Ref: {}]", chrono::Utc::now().to_rfc3339()) }]
+                    }
+                ],
+                "generationConfig": {
+                    "maxOutputTokens": 10
+                }
+            }
+        });
+
+        let res = super::http_client::client()
+            .post(&url)
+            .timeout(Duration::from_secs(10))
+            .header(AUTHORIZATION, format!("Bearer {}", access_token))
+            .header(CONTENT_TYPE, "application/json")
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| ApiError::Network(e.to_string()))?;
+
+        if !res.status().is_success() {
+            return Err(ApiError::from_response(&res));
+        }
+
+        Ok(())
+    }
+}
+
 pub struct ValidToken {
     pub access_token: String,
     pub user_id: String,
@@ -29,13 +428,7 @@ pub async fn load_account(
     config_dir: &std::path::Path,
     target_email: &str,
 ) -> Result<(String, Vec<u8>), String> {
-    let antigravity_dir = config_dir.join("antigravity-accounts");
-    let path = antigravity_dir.join(format!("{}.json", target_email));
-
-    if !path.exists() {
-        return Err(format!("账户文件不存在: {}", path.display()));
-    }
-
+    let path = crate::services::account::account_file_path(config_dir, target_email)?;
     let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
     let json: Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
 
@@ -53,157 +446,276 @@ pub async fn load_account(
     Err("无效的账户文件格式".to_string())
 }
 
-pub async fn get_valid_token(email: &str, proto_bytes: &[u8]) -> Result<ValidToken, String> {
+pub async fn get_valid_token(
+    client: &dyn CloudCodeClient,
+    email: &str,
+    proto_bytes: &[u8],
+) -> Result<ValidToken, String> {
     let mut msg = crate::proto::SessionResponse::decode(proto_bytes)
         .map_err(|e| format!("Proto decode failed: {}", e))?;
 
     let auth = msg.auth.as_mut().ok_or("No auth info")?;
     let access_token = auth.access_token.clone();
     let refresh_token = auth.refresh_token.clone();
-    let _email_ctx = msg.context.as_ref().map(|c| c.email.clone()).unwrap_or_default();
 
     // Verify token and get user info
-    match fetch_user_info(&access_token).await {
+    match client.fetch_user_info(&access_token).await {
         Ok(info) => Ok(ValidToken {
             access_token,
             user_id: info.id,
             avatar_url: info.picture,
         }),
-        Err(_) => {
+        // 只有 401 才说明 token 过期，其他错误刷新也无济于事
+        Err(ApiError::Unauthorized) => {
             info!("Token expired for {}, refreshing...", email);
-            let new_token = refresh_access_token(&refresh_token).await?;
+            let new_token =
+                client
+                    .refresh_access_token(&refresh_token)
+                    .await
+                    .map_err(|e| match e.status() {
+                        Some(status) => format!("Refresh failed: {}", status),
+                        None => e.to_string(),
+                    })?;
             // Verify new token
-            let info = fetch_user_info(&new_token).await.map_err(|e| format!("Failed to verify new token: {}", e))?;
+            let info = client
+                .fetch_user_info(&new_token)
+                .await
+                .map_err(|e| format!("Failed to verify new token: {}", e))?;
             Ok(ValidToken {
                 access_token: new_token,
                 user_id: info.id,
                 avatar_url: info.picture,
             })
         }
+        Err(e) => Err(format!("Failed to fetch user info: {}", e)),
     }
 }
 
-pub async fn fetch_user_info(access_token: &str) -> Result<UserInfoResponse, String> {
-    let client = reqwest::Client::new();
-    let res = client
-        .get("https://www.googleapis.com/oauth2/v2/userinfo")
-        .header(AUTHORIZATION, format!("Bearer {}", access_token))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+/// 使用默认客户端获取用户信息，保留错误分类
+pub async fn try_fetch_user_info(access_token: &str) -> Result<UserInfoResponse, ApiError> {
+    default_client().fetch_user_info(access_token).await
+}
+
+/// 使用默认客户端刷新 access token，保留错误分类
+pub async fn try_refresh_access_token(refresh_token: &str) -> Result<String, ApiError> {
+    default_client().refresh_access_token(refresh_token).await
+}
 
-    if !res.status().is_success() {
-        return Err(format!("Status: {}", res.status()));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_string_contains, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn mock_client(server: &MockServer) -> HttpCloudCodeClient {
+        HttpCloudCodeClient::new(ApiEndpoints {
+            cloud_code_base_url: server.uri(),
+            oauth_token_url: format!("{}/token", server.uri()),
+            userinfo_url: format!("{}/userinfo", server.uri()),
+        })
+        .with_oauth_credentials("client-id", "client-secret")
+        .with_circuit_breaker(Arc::new(CircuitBreaker::default()))
+        .with_retry_policy(RetryPolicy {
+            max_attempts: 2,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(50),
+        })
     }
 
-    res.json::<UserInfoResponse>()
-        .await
-        .map_err(|e| e.to_string())
-}
-
-pub async fn refresh_access_token(refresh_token: &str) -> Result<String, String> {
-    // 使用安全的凭据管理模块获取 OAuth 凭据
-    let config_dir = crate::directories::get_config_directory();
-    let (client_id, client_secret) = crate::security::credentials::resolve_oauth_credentials(&config_dir)?;
-    
-    let client = reqwest::Client::new();
-    let params = [
-        ("client_id", client_id.as_str()),
-        ("client_secret", client_secret.as_str()),
-        ("grant_type", "refresh_token"),
-        ("refresh_token", refresh_token),
-    ];
-
-    let res = client
-        .post("https://oauth2.googleapis.com/token")
-        .form(&params)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+    /// 写入只包含 token 的账户文件
+    fn write_account(config_dir: &std::path::Path, email: &str, access_token: &str) {
+        let session = crate::proto::SessionResponse {
+            auth: Some(crate::proto::AuthInfo {
+                access_token: access_token.to_string(),
+                refresh_token: "refresh-1".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let state = base64::engine::general_purpose::STANDARD.encode(session.encode_to_vec());
+
+        let accounts_dir = config_dir.join("antigravity-accounts");
+        fs::create_dir_all(&accounts_dir).unwrap();
+        fs::write(
+            accounts_dir.join(format!("{}.json", email)),
+            serde_json::json!({ "jetskiStateSync.agentManagerInitState": state }).to_string(),
+        )
+        .unwrap();
+    }
 
-    if !res.status().is_success() {
-        return Err(format!("Refresh failed: {}", res.status()));
+    async fn mock_userinfo(server: &MockServer, access_token: &str) {
+        Mock::given(method("GET"))
+            .and(path("/userinfo"))
+            .and(header(
+                "authorization",
+                format!("Bearer {}", access_token).as_str(),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(
+                serde_json::json!({ "id": "42", "picture": "https://example.com/a.png" }),
+            ))
+            .mount(server)
+            .await;
     }
 
-    let json: RefreshTokenResponse = res.json().await.map_err(|e| e.to_string())?;
-    Ok(json.access_token)
-}
+    #[tokio::test]
+    async fn test_expired_token_is_refreshed() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/userinfo"))
+            .and(header("authorization", "Bearer expired"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(body_string_contains("refresh_token=refresh-1"))
+            .and(body_string_contains("client_id=client-id"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "access_token": "fresh" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        mock_userinfo(&server, "fresh").await;
+
+        let dir = tempfile::tempdir().unwrap();
+        write_account(dir.path(), "refresh@example.com", "expired");
+        let (email, proto_bytes) = load_account(dir.path(), "refresh@example.com")
+            .await
+            .unwrap();
+
+        let token = get_valid_token(&mock_client(&server), &email, &proto_bytes)
+            .await
+            .unwrap();
+        assert_eq!(token.access_token, "fresh");
+        assert_eq!(token.user_id, "42");
+    }
 
-pub async fn fetch_code_assist_project(access_token: &str) -> Result<String, String> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()
-        .map_err(|e| e.to_string())?;
+    #[tokio::test]
+    async fn test_server_error_does_not_refresh() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/userinfo"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        write_account(dir.path(), "unavailable@example.com", "valid");
+        let (email, proto_bytes) = load_account(dir.path(), "unavailable@example.com")
+            .await
+            .unwrap();
+
+        let err = get_valid_token(&mock_client(&server), &email, &proto_bytes)
+            .await
+            .err()
+            .unwrap();
+        assert!(err.contains("503"), "{}", err);
+    }
 
-    let res = client
-        .post(format!("{}/v1internal:loadCodeAssist", CLOUD_CODE_BASE_URL))
-        .header(AUTHORIZATION, format!("Bearer {}", access_token))
-        .header(CONTENT_TYPE, "application/json")
-        .header(USER_AGENT, "antigravity/windows/amd64")
-        .body(r#"{"metadata": {"ideType": "ANTIGRAVITY"}}"#)
-        .send()
+    #[tokio::test]
+    async fn test_missing_project() {
+        let server = MockServer::start().await;
+        mock_userinfo(&server, "valid").await;
+        Mock::given(method("POST"))
+            .and(path("/v1internal:loadCodeAssist"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        write_account(dir.path(), "no-project@example.com", "valid");
+
+        let err = crate::services::account::get_metrics_with(
+            &mock_client(&server),
+            dir.path(),
+            "no-project@example.com".to_string(),
+        )
         .await
-        .map_err(|e| e.to_string())?;
-
-    let status = res.status();
-    let text = res.text().await.map_err(|e| e.to_string())?;
-
-    if !status.is_success() {
-        return Err(format!("loadCodeAssist failed status {}: {}", status, text));
+        .unwrap_err();
+        assert!(err.contains("Project ID missing"), "{}", err);
+
+        let err = mock_client(&server)
+            .fetch_code_assist_project("valid")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ApiError::InvalidResponse(_)), "{}", err);
     }
 
-    let json: Value = serde_json::from_str(&text).map_err(|e| {
-        format!(
-            "Failed to parse project response: {} | Raw Body: {:.100}",
-            e, text
+    #[tokio::test]
+    async fn test_quota_parsing() {
+        let server = MockServer::start().await;
+        mock_userinfo(&server, "valid").await;
+        Mock::given(method("POST"))
+            .and(path("/v1internal:loadCodeAssist"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "cloudaicompanionProject": "project-1" })),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1internal:fetchAvailableModels"))
+            .and(body_string_contains("project-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "models": {
+                    "gemini-3-pro-high": {
+                        "quotaInfo": { "remainingFraction": 0.25, "resetTime": "2026-01-01T00:00:00Z" }
+                    },
+                    "claude-opus-4-5-thinking": {
+                        "quotaInfo": { "remainingFraction": 1.0 }
+                    },
+                    "unknown-model": {
+                        "quotaInfo": { "remainingFraction": 0.5 }
+                    }
+                }
+            })))
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        write_account(dir.path(), "quota@example.com", "valid");
+
+        let metrics = crate::services::account::get_metrics_with(
+            &mock_client(&server),
+            dir.path(),
+            "quota@example.com".to_string(),
         )
-    })?;
-
-    let project_id = json
-        .get("cloudaicompanionProject")
-        .or_else(|| json.get("project"))
-        .or_else(|| json.get("projectId"))
-        .and_then(|v| v.as_str());
-
-    match project_id {
-        Some(id) => Ok(id.to_string()),
-        None => Err("Project ID missing in loadCodeAssist response".to_string()),
-    }
-}
-
-pub async fn fetch_available_models(access_token: &str, project: &str) -> Result<Value, String> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()
-        .map_err(|e| e.to_string())?;
-
-    let body = serde_json::json!({ "project": project });
-
-    let res = client
-        .post(format!(
-            "{}/v1internal:fetchAvailableModels",
-            CLOUD_CODE_BASE_URL
-        ))
-        .header(AUTHORIZATION, format!("Bearer {}", access_token))
-        .header(CONTENT_TYPE, "application/json")
-        .header(USER_AGENT, "antigravity/windows/amd64")
-        .json(&body)
-        .send()
         .await
-        .map_err(|e| e.to_string())?;
-
-    let status = res.status();
-    let text = res.text().await.map_err(|e| e.to_string())?;
-
-    if !status.is_success() {
-        return Err(format!(
-            "fetchAvailableModels failed status {}: {}",
-            status, text
-        ));
+        .unwrap();
+
+        assert_eq!(metrics.user_id, "42");
+        assert_eq!(metrics.quotas.len(), 2);
+        assert_eq!(metrics.quotas[0].model_name, "Gemini Pro");
+        assert_eq!(metrics.quotas[0].percentage, 0.25);
+        assert_eq!(metrics.quotas[0].reset_text, "2026-01-01T00:00:00Z");
+        assert_eq!(metrics.quotas[1].model_name, "Claude");
+        assert_eq!(metrics.quotas[1].reset_text, "");
     }
 
-    serde_json::from_str(&text).map_err(|e| {
-        error!("JSON parse failed for fetchAvailableModels. Raw body: {}", text);
-        format!("Failed to parse models JSON: {} | Raw Body: {:.500}", e, text)
-    })
+    #[test]
+    fn test_endpoints_are_validated() {
+        let endpoints = ApiEndpoints {
+            cloud_code_base_url: " https://cloudcode.example.com/ ".to_string(),
+            ..ApiEndpoints::default()
+        };
+        assert_eq!(
+            endpoints.normalized().unwrap().cloud_code_base_url,
+            "https://cloudcode.example.com"
+        );
+
+        let invalid = ApiEndpoints {
+            oauth_token_url: "ftp://example.com/token".to_string(),
+            ..ApiEndpoints::default()
+        };
+        assert!(invalid.normalized().is_err());
+    }
 }
@@ -0,0 +1,392 @@
+//! Google API 请求的重试、退避与熔断
+//!
+//! - 网络错误、429 和 5xx 视为暂时性错误，按指数退避重试；响应带 `Retry-After` 时按其等待
+//! - 同一主机连续失败达到阈值后熔断一段时间，期间请求直接失败，避免服务故障时反复等待超时
+//! - 熔断到期后放行试探请求，成功则恢复，失败则立即重新熔断
+//! - 熔断器由调用方传入，默认客户端共用 [`shared_breaker`]
+
+use parking_lot::Mutex;
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::google_api::ApiError;
+
+/// 连续失败多少次后熔断
+const BREAKER_THRESHOLD: u32 = 5;
+
+/// 熔断持续时间
+const BREAKER_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// 重试策略
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// 最多请求次数（含第一次）
+    pub max_attempts: u32,
+    /// 第一次重试前的等待时间，之后每次翻倍
+    pub base_delay: Duration,
+    /// 单次等待上限；`Retry-After` 超过该值时不再重试，直接返回响应
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 第 `attempt` 次请求失败后的等待时间（指数退避，附加最多 20% 的随机抖动）
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(1u32 << attempt.saturating_sub(1).min(16))
+            .min(self.max_delay);
+        let jitter_ms = rand::thread_rng().gen_range(0..=exp.as_millis() as u64 / 5);
+        exp + Duration::from_millis(jitter_ms)
+    }
+}
+
+#[derive(Default)]
+struct HostState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+/// 按主机统计连续失败次数的熔断器
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    hosts: Mutex<HashMap<String, HostState>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold,
+            cooldown,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 请求前检查，熔断中返回剩余时间；熔断到期后放行试探请求
+    fn check(&self, host: &str, now: Instant) -> Result<(), Duration> {
+        let mut hosts = self.hosts.lock();
+        if let Some(state) = hosts.get_mut(host) {
+            if let Some(until) = state.open_until {
+                if now < until {
+                    return Err(until - now);
+                }
+                // 失败计数保持不变，试探失败会立即重新熔断
+                state.open_until = None;
+            }
+        }
+        Ok(())
+    }
+
+    fn record_success(&self, host: &str) {
+        self.hosts.lock().remove(host);
+    }
+
+    fn record_failure(&self, host: &str, now: Instant) {
+        let mut hosts = self.hosts.lock();
+        let state = hosts.entry(host.to_string()).or_default();
+        state.consecutive_failures += 1;
+        if state.consecutive_failures >= self.threshold && state.open_until.is_none() {
+            state.open_until = Some(now + self.cooldown);
+            tracing::warn!(
+                target: "google_api::retry",
+                host = %host,
+                failures = state.consecutive_failures,
+                cooldown_secs = self.cooldown.as_secs(),
+                "连续请求失败，暂停访问该主机"
+            );
+        }
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(BREAKER_THRESHOLD, BREAKER_COOLDOWN)
+    }
+}
+
+lazy_static::lazy_static! {
+    /// 默认客户端共用的熔断器
+    static ref BREAKER: Arc<CircuitBreaker> = Arc::new(CircuitBreaker::default());
+}
+
+/// 默认客户端共用的熔断器
+pub fn shared_breaker() -> Arc<CircuitBreaker> {
+    BREAKER.clone()
+}
+
+/// 是否为可重试的状态码（429 / 5xx）
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// 限流与请求超时既不说明主机故障，也不说明主机已恢复，不改变熔断状态
+fn is_breaker_neutral(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::REQUEST_TIMEOUT
+}
+
+/// 解析 `Retry-After`（秒数或 HTTP 日期）
+pub fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = date.signed_duration_since(chrono::Utc::now());
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+fn host_key(request: &RequestBuilder) -> String {
+    request
+        .try_clone()
+        .and_then(|r| r.build().ok())
+        .map(|r| {
+            let url = r.url();
+            format!(
+                "{}:{}",
+                url.host_str().unwrap_or_default(),
+                url.port_or_known_default().unwrap_or_default()
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// 发送请求，暂时性错误按策略重试，结果计入 `breaker`
+///
+/// 返回最终得到的响应（包括重试耗尽后的 429 / 5xx 响应），由调用方按状态码处理；
+/// 只有网络错误或熔断时返回 `Err`。请求体必须可克隆（不能是流）。
+/// 响应体的解析由调用方负责，解析失败不影响重试与熔断。
+pub async fn send_with_retry(
+    request: RequestBuilder,
+    policy: &RetryPolicy,
+    breaker: &CircuitBreaker,
+) -> Result<Response, ApiError> {
+    let host = host_key(&request);
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+
+        if let Err(remaining) = breaker.check(&host, Instant::now()) {
+            return Err(ApiError::CircuitOpen {
+                host,
+                retry_in: remaining,
+            });
+        }
+
+        let current = request
+            .try_clone()
+            .ok_or_else(|| ApiError::Config("请求体不支持重试".to_string()))?;
+
+        let delay = match current.send().await {
+            Ok(res) => {
+                let status = res.status();
+                if status.is_server_error() {
+                    breaker.record_failure(&host, Instant::now());
+                } else if !is_breaker_neutral(status) {
+                    breaker.record_success(&host);
+                }
+
+                if !is_retryable_status(status) || attempt >= policy.max_attempts {
+                    return Ok(res);
+                }
+
+                match parse_retry_after(res.headers()) {
+                    // 服务端要求等待过久，不再重试，交给调用方处理
+                    Some(wait) if wait > policy.max_delay => return Ok(res),
+                    Some(wait) => wait,
+                    None => policy.backoff(attempt),
+                }
+            }
+            Err(e) => {
+                breaker.record_failure(&host, Instant::now());
+                if attempt >= policy.max_attempts {
+                    return Err(ApiError::Network(e.to_string()));
+                }
+                policy.backoff(attempt)
+            }
+        };
+
+        tracing::debug!(
+            target: "google_api::retry",
+            host = %host,
+            attempt = attempt,
+            delay_ms = delay.as_millis(),
+            "请求失败，稍后重试"
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(50),
+        }
+    }
+
+    #[test]
+    fn test_circuit_breaker_opens_and_recovers() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(30));
+        let now = Instant::now();
+
+        breaker.record_failure("a:443", now);
+        assert!(breaker.check("a:443", now).is_ok());
+        breaker.record_failure("a:443", now);
+        assert!(breaker.check("a:443", now).is_err());
+        assert!(breaker.check("b:443", now).is_ok());
+
+        // 到期后放行试探请求，试探失败立即重新熔断
+        let later = now + Duration::from_secs(31);
+        assert!(breaker.check("a:443", later).is_ok());
+        breaker.record_failure("a:443", later);
+        assert!(breaker.check("a:443", later).is_err());
+
+        breaker.record_success("a:443");
+        assert!(breaker.check("a:443", later).is_ok());
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, "7".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(7)));
+
+        headers.insert(
+            RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/flaky"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/flaky"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/flaky"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let request = reqwest::Client::new().get(format!("{}/flaky", server.uri()));
+        let res = send_with_retry(request, &fast_policy(), &CircuitBreaker::default())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_client_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/auth"))
+            .respond_with(ResponseTemplate::new(401))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/slow-down"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "3600"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = reqwest::Client::new();
+        let breaker = CircuitBreaker::default();
+        let res = send_with_retry(
+            client.get(format!("{}/auth", server.uri())),
+            &fast_policy(),
+            &breaker,
+        )
+        .await
+        .unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        // Retry-After 超过等待上限时直接返回 429
+        let res = send_with_retry(
+            client.get(format!("{}/slow-down", server.uri())),
+            &fast_policy(),
+            &breaker,
+        )
+        .await
+        .unwrap();
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_does_not_reset_breaker() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/down"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/busy"))
+            .respond_with(ResponseTemplate::new(429))
+            .mount(&server)
+            .await;
+
+        let once = RetryPolicy {
+            max_attempts: 1,
+            ..fast_policy()
+        };
+        let client = reqwest::Client::new();
+        let breaker = CircuitBreaker::default();
+        let down = format!("{}/down", server.uri());
+        let busy = format!("{}/busy", server.uri());
+
+        for _ in 0..BREAKER_THRESHOLD - 1 {
+            send_with_retry(client.get(&down), &once, &breaker)
+                .await
+                .unwrap();
+        }
+        // 429 不计为成功，之后再失败一次即熔断
+        send_with_retry(client.get(&busy), &once, &breaker)
+            .await
+            .unwrap();
+        send_with_retry(client.get(&down), &once, &breaker)
+            .await
+            .unwrap();
+
+        let err = send_with_retry(client.get(&busy), &once, &breaker)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ApiError::CircuitOpen { .. }), "{}", err);
+    }
+}
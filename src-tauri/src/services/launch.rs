@@ -0,0 +1,382 @@
+//! 启动参数与单实例
+//!
+//! 支持的启动参数：
+//!
+//! - `--show`：显示主窗口（不带参数再次启动时的默认行为）
+//! - `--switch <email>`：切换到指定账户
+//! - `--save-current`：保存当前登录的账户
+//! - `antigravity-agent://...`：深链接，见 [`super::deep_link`]
+//!
+//! 启动时先尝试获取配置目录中 `instance.lock` 的排他锁，成功则作为首个实例正常启动，
+//! 启动完成后自行执行参数对应的命令。锁已被占用时说明已有实例在运行（或正在启动），
+//! 通过服务发现文件找到它并把参数作为命令转发
+//! （`POST /api/v1/instance/commands`，携带 `server.token` 中的实例 token），由其执行后退出；
+//! 在限定时间内连接不上则报错退出，不会启动第二个实例。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+use crate::server::discovery::{self, DiscoveryInfo};
+
+/// 转发命令的接口路径
+const FORWARD_PATH: &str = "/api/v1/instance/commands";
+
+/// 连接正在运行的实例的超时
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 等待命令执行完成的超时（切换账户需要重启 Antigravity）
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// 已有实例正在启动、尚未写入服务发现文件时，最多等待的时间
+const STARTUP_WAIT: Duration = Duration::from_secs(15);
+
+/// 等待期间重试转发的间隔
+const STARTUP_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 单实例锁，进程退出时由系统释放
+static INSTANCE_LOCK: OnceLock<fs::File> = OnceLock::new();
+
+/// 启动命令
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum LaunchCommand {
+    /// 显示主窗口
+    Show,
+    /// 切换到指定账户
+    Switch { email: String },
+    /// 保存当前登录的账户
+    SaveCurrent,
+    /// 打开深链接（由执行方解析校验）
+    OpenUrl { url: String },
+}
+
+impl LaunchCommand {
+    /// 解析命令行参数（不含程序名），没有可识别的命令时返回 `None`
+    ///
+    /// 未知参数只记录警告，避免系统附加的参数（如 macOS 的 `-psn_*`）导致无法启动。
+    pub fn from_args<I>(args: I) -> Result<Option<Self>, String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut args = args.into_iter();
+        let mut command = None;
+
+        while let Some(arg) = args.next() {
+            let parsed = match arg.as_str() {
+                "--show" => Self::Show,
+                "--save-current" => Self::SaveCurrent,
+                "--switch" => {
+                    let email = args
+                        .next()
+                        .filter(|value| !value.starts_with("--"))
+                        .ok_or_else(|| "--switch 需要指定账户邮箱".to_string())?;
+                    Self::Switch { email }
+                }
+                link if link.starts_with(&format!("{}:", super::deep_link::SCHEME)) => {
+                    Self::OpenUrl {
+                        url: link.to_string(),
+                    }
+                }
+                other => match other.strip_prefix("--switch=") {
+                    Some(email) if !email.is_empty() => Self::Switch {
+                        email: email.to_string(),
+                    },
+                    Some(_) => return Err("--switch 需要指定账户邮箱".to_string()),
+                    None => {
+                        tracing::warn!(target: "app::launch", "忽略未知启动参数: {}", other);
+                        continue;
+                    }
+                },
+            };
+
+            if command.is_some() {
+                return Err("一次只能指定一个命令".to_string());
+            }
+            command = Some(parsed);
+        }
+
+        Ok(command)
+    }
+}
+
+/// 执行启动命令
+pub async fn execute(app: &AppHandle, command: LaunchCommand) -> Result<String, String> {
+    tracing::info!(target: "app::launch", command = ?command, "执行启动命令");
+    match command {
+        LaunchCommand::Show => super::system::tray::restore(app).await,
+        LaunchCommand::Switch { email } => super::account::switch(email).await,
+        LaunchCommand::SaveCurrent => super::account::backup_current().await,
+        LaunchCommand::OpenUrl { url } => super::deep_link::handle(app, &url).await,
+    }
+}
+
+/// 获取单实例锁，已被其他进程持有时返回 `Ok(None)`
+fn try_lock(path: &Path) -> Result<Option<fs::File>, String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .map_err(|e| format!("打开 {} 失败: {}", path.display(), e))?;
+
+    match file.try_lock() {
+        Ok(()) => Ok(Some(file)),
+        Err(fs::TryLockError::WouldBlock) => Ok(None),
+        Err(fs::TryLockError::Error(e)) => Err(format!("锁定 {} 失败: {}", path.display(), e)),
+    }
+}
+
+/// 获取单实例锁，失败时把命令转发给正在运行的实例
+///
+/// - `Ok(None)`：当前进程是首个实例（已持有锁），应正常启动
+/// - `Ok(Some(message))`：已由正在运行的实例执行
+/// - `Err(e)`：已有实例在运行但无法连接，或命令被拒绝、执行失败
+pub fn forward_to_running_instance(command: &LaunchCommand) -> Result<Option<String>, String> {
+    match try_lock(&crate::directories::get_instance_lock_file()) {
+        Ok(Some(lock)) => {
+            let _ = INSTANCE_LOCK.set(lock);
+            return Ok(None);
+        }
+        Ok(None) => {}
+        Err(e) => {
+            // 无法使用锁文件时退回到只按服务发现文件判断
+            tracing::warn!(target: "app::launch", "无法获取单实例锁: {}", e);
+            return try_forward(command);
+        }
+    }
+
+    // 锁已被占用：对方可能还在启动，等待其写入服务发现文件
+    let deadline = Instant::now() + STARTUP_WAIT;
+    loop {
+        if let Some(message) = try_forward(command)? {
+            return Ok(Some(message));
+        }
+        if Instant::now() >= deadline {
+            return Err("已有实例在运行，但无法连接到它（服务发现文件缺失或已失效）".to_string());
+        }
+        std::thread::sleep(STARTUP_POLL_INTERVAL);
+    }
+}
+
+/// 通过服务发现文件把命令转发给正在运行的实例，找不到可用的实例时返回 `Ok(None)`
+fn try_forward(command: &LaunchCommand) -> Result<Option<String>, String> {
+    let path = crate::directories::get_server_discovery_file();
+    let Some(info) = discovery::read_discovery(&path) else {
+        return Ok(None);
+    };
+    if info.pid == std::process::id() {
+        return Ok(None);
+    }
+    let Some(token) = discovery::read_token() else {
+        return Ok(None);
+    };
+
+    let body = serde_json::to_string(command).map_err(|e| format!("序列化启动命令失败: {}", e))?;
+    let response = match send(&info, &token, &body) {
+        Ok(response) => response,
+        Err(e) => {
+            // 发现文件是上次异常退出的残留
+            tracing::debug!(target: "app::launch", "连接已有实例失败: {}", e);
+            return Ok(None);
+        }
+    };
+    // 端口可能已被其他程序占用，只信任带有相同实例指纹的响应
+    if response.instance.as_deref() != Some(info.token_fingerprint.as_str()) {
+        tracing::debug!(target: "app::launch", "发现文件指向的端口不属于 Antigravity Agent");
+        return Ok(None);
+    }
+    let ForwardResponse { status, body, .. } = response;
+
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+    if (200..300).contains(&status) {
+        Ok(Some(
+            json["message"].as_str().unwrap_or_default().to_string(),
+        ))
+    } else {
+        Err(json["error"]["message"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("正在运行的实例返回 HTTP {}", status)))
+    }
+}
+
+/// 转发请求的响应
+#[derive(Debug, PartialEq)]
+struct ForwardResponse {
+    status: u16,
+    /// 实例指纹响应头
+    instance: Option<String>,
+    body: String,
+}
+
+/// 发送请求
+fn send(info: &DiscoveryInfo, token: &str, body: &str) -> std::io::Result<ForwardResponse> {
+    let request = format!(
+        "POST {} HTTP/1.1\r\nHost: 127.0.0.1\r\nAuthorization: Bearer {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        FORWARD_PATH,
+        token,
+        body.len(),
+        body
+    );
+
+    #[cfg(unix)]
+    if let Some(socket) = info.socket.as_deref() {
+        if let Ok(mut stream) = std::os::unix::net::UnixStream::connect(socket) {
+            stream.set_read_timeout(Some(RESPONSE_TIMEOUT))?;
+            return exchange(&mut stream, &request);
+        }
+    }
+
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], info.port));
+    let mut stream = std::net::TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+    stream.set_read_timeout(Some(RESPONSE_TIMEOUT))?;
+    exchange(&mut stream, &request)
+}
+
+fn exchange<S: Read + Write>(stream: &mut S, request: &str) -> std::io::Result<ForwardResponse> {
+    stream.write_all(request.as_bytes())?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    parse_response(&response)
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "无效的 HTTP 响应"))
+}
+
+/// 解析 HTTP 响应
+///
+/// 只支持带 `Content-Length` 或读到连接关闭为止的响应体，分块传输的响应视为无效。
+fn parse_response(response: &str) -> Option<ForwardResponse> {
+    let (head, body) = response.split_once("\r\n\r\n").unwrap_or((response, ""));
+    let mut lines = head.lines();
+    let status = lines.next()?.split_whitespace().nth(1)?.parse().ok()?;
+
+    let mut instance = None;
+    let mut content_length = None;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, value) = (name.trim(), value.trim());
+        if name.eq_ignore_ascii_case(discovery::INSTANCE_HEADER) {
+            instance = Some(value.to_string());
+        } else if name.eq_ignore_ascii_case("content-length") {
+            content_length = Some(value.parse::<usize>().ok()?);
+        } else if name.eq_ignore_ascii_case("transfer-encoding")
+            && value.to_ascii_lowercase().contains("chunked")
+        {
+            return None;
+        }
+    }
+
+    let body = match content_length {
+        Some(len) => body.get(..len)?,
+        None => body,
+    };
+    Some(ForwardResponse {
+        status,
+        instance,
+        body: body.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<LaunchCommand>, String> {
+        LaunchCommand::from_args(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_launch_args() {
+        assert_eq!(parse(&[]), Ok(None));
+        assert_eq!(parse(&["--show"]), Ok(Some(LaunchCommand::Show)));
+        assert_eq!(
+            parse(&["--switch", "user@x.com"]),
+            Ok(Some(LaunchCommand::Switch {
+                email: "user@x.com".to_string()
+            }))
+        );
+        assert_eq!(
+            parse(&["-psn_0_12345", "--switch=user@x.com"]),
+            Ok(Some(LaunchCommand::Switch {
+                email: "user@x.com".to_string()
+            }))
+        );
+        assert_eq!(
+            parse(&["--save-current"]),
+            Ok(Some(LaunchCommand::SaveCurrent))
+        );
+
+        assert_eq!(
+            parse(&["antigravity-agent://save-current"]),
+            Ok(Some(LaunchCommand::OpenUrl {
+                url: "antigravity-agent://save-current".to_string()
+            }))
+        );
+
+        assert!(parse(&["--switch"]).is_err());
+        assert!(parse(&["--switch", "--show"]).is_err());
+        assert!(parse(&["--show", "--save-current"]).is_err());
+    }
+
+    #[test]
+    fn test_command_wire_format() {
+        let json = serde_json::to_value(LaunchCommand::Switch {
+            email: "user@x.com".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "command": "switch", "email": "user@x.com" })
+        );
+        assert_eq!(
+            serde_json::to_value(LaunchCommand::SaveCurrent).unwrap(),
+            serde_json::json!({ "command": "save-current" })
+        );
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let response = "HTTP/1.1 401 Unauthorized\r\ncontent-length: 2\r\nX-Antigravity-Agent-Instance: sha256:abc\r\n\r\n{}";
+        assert_eq!(
+            parse_response(response),
+            Some(ForwardResponse {
+                status: 401,
+                instance: Some("sha256:abc".to_string()),
+                body: "{}".to_string(),
+            })
+        );
+
+        let response = "HTTP/1.1 404 Not Found\r\n\r\n";
+        assert_eq!(parse_response(response).unwrap().instance, None);
+        assert_eq!(parse_response(""), None);
+
+        let response = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n2\r\n{}\r\n0\r\n\r\n";
+        assert_eq!(parse_response(response), None);
+
+        // 响应体比 Content-Length 短说明读取不完整
+        let response = "HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\n{}";
+        assert_eq!(parse_response(response), None);
+    }
+
+    #[test]
+    fn test_instance_lock_is_exclusive() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("instance.lock");
+
+        let first = try_lock(&path).unwrap();
+        assert!(first.is_some());
+        assert!(try_lock(&path).unwrap().is_none());
+
+        drop(first);
+        assert!(try_lock(&path).unwrap().is_some());
+    }
+}
@@ -0,0 +1,202 @@
+//! 账户配额指标缓存
+//!
+//! - 指标按账户缓存 [`METRICS_TTL`]，批量接口在有效期内直接返回缓存
+//! - project id 几乎不会变化，按账户长期缓存，省去每次的 `loadCodeAssist` 请求；
+//!   `fetchAvailableModels` 失败时清除，下次重新获取
+
+use futures_util::stream::{self, StreamExt};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use super::account::{self, AccountMetrics};
+
+/// 指标缓存有效期
+pub const METRICS_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// 同时拉取指标的账户数量上限
+const MAX_CONCURRENT_FETCHES: usize = 4;
+
+struct CachedMetrics {
+    metrics: AccountMetrics,
+    fetched_at: Instant,
+    /// 拉取时间（RFC 3339），返回给调用方
+    fetched_at_text: String,
+}
+
+/// 指标与 project id 缓存
+pub struct MetricsCache {
+    ttl: Duration,
+    metrics: Mutex<HashMap<String, CachedMetrics>>,
+    projects: Mutex<HashMap<String, String>>,
+}
+
+impl MetricsCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            metrics: Mutex::new(HashMap::new()),
+            projects: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn project(&self, email: &str) -> Option<String> {
+        self.projects.lock().get(email).cloned()
+    }
+
+    pub fn store_project(&self, email: &str, project: &str) {
+        self.projects
+            .lock()
+            .insert(email.to_string(), project.to_string());
+    }
+
+    pub fn invalidate_project(&self, email: &str) {
+        self.projects.lock().remove(email);
+    }
+
+    pub fn store_metrics(&self, metrics: &AccountMetrics) {
+        self.metrics.lock().insert(
+            metrics.email.clone(),
+            CachedMetrics {
+                metrics: metrics.clone(),
+                fetched_at: Instant::now(),
+                fetched_at_text: chrono::Local::now().to_rfc3339(),
+            },
+        );
+    }
+
+    /// 返回未过期的缓存指标及其拉取时间
+    fn fresh_metrics(&self, email: &str, now: Instant) -> Option<(AccountMetrics, String)> {
+        self.metrics
+            .lock()
+            .get(email)
+            .filter(|cached| now.duration_since(cached.fetched_at) < self.ttl)
+            .map(|cached| (cached.metrics.clone(), cached.fetched_at_text.clone()))
+    }
+
+    /// 返回最近一次拉取的指标（不检查是否过期，用于托盘等只需展示的场景）
+    pub fn latest_metrics(&self, email: &str) -> Option<AccountMetrics> {
+        self.metrics
+            .lock()
+            .get(email)
+            .map(|cached| cached.metrics.clone())
+    }
+}
+
+lazy_static::lazy_static! {
+    /// 全局指标缓存
+    pub static ref METRICS_CACHE: MetricsCache = MetricsCache::new(METRICS_TTL);
+}
+
+/// 批量接口中单个账户的结果
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountMetricsEntry {
+    pub email: String,
+    pub metrics: Option<AccountMetrics>,
+    pub error: Option<String>,
+    /// 是否来自缓存
+    pub cached: bool,
+    /// 指标拉取时间（RFC 3339）
+    pub fetched_at: Option<String>,
+}
+
+/// 获取所有账户的指标，单个账户失败不影响其他账户
+///
+/// `force` 为 true 时忽略指标缓存（project id 缓存仍然使用）。
+pub async fn get_all(config_dir: &Path, force: bool) -> Result<Vec<AccountMetricsEntry>, String> {
+    let emails = account::list_account_emails(config_dir)?;
+    let start_time = Instant::now();
+
+    let mut entries: Vec<AccountMetricsEntry> = stream::iter(emails)
+        .map(|email| async move {
+            if !force {
+                if let Some((metrics, fetched_at)) =
+                    METRICS_CACHE.fresh_metrics(&email, Instant::now())
+                {
+                    return AccountMetricsEntry {
+                        email,
+                        metrics: Some(metrics),
+                        error: None,
+                        cached: true,
+                        fetched_at: Some(fetched_at),
+                    };
+                }
+            }
+
+            match account::get_metrics(config_dir, email.clone()).await {
+                Ok(metrics) => AccountMetricsEntry {
+                    email,
+                    metrics: Some(metrics),
+                    error: None,
+                    cached: false,
+                    fetched_at: Some(chrono::Local::now().to_rfc3339()),
+                },
+                Err(e) => {
+                    tracing::warn!(target: "account::metrics", email = %email, error = %e, "获取账户指标失败");
+                    AccountMetricsEntry {
+                        email,
+                        metrics: None,
+                        error: Some(e),
+                        cached: false,
+                        fetched_at: None,
+                    }
+                }
+            }
+        })
+        .buffer_unordered(MAX_CONCURRENT_FETCHES)
+        .collect()
+        .await;
+    entries.sort_by(|a, b| a.email.cmp(&b.email));
+
+    tracing::info!(
+        target: "account::metrics",
+        duration_ms = start_time.elapsed().as_millis(),
+        account_count = entries.len(),
+        cached = entries.iter().filter(|e| e.cached).count(),
+        failed = entries.iter().filter(|e| e.error.is_some()).count(),
+        force = force,
+        "批量获取账户指标完成"
+    );
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics(email: &str) -> AccountMetrics {
+        AccountMetrics {
+            email: email.to_string(),
+            user_id: "1".to_string(),
+            avatar_url: String::new(),
+            quotas: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_metrics_expire_after_ttl() {
+        let cache = MetricsCache::new(Duration::from_secs(60));
+        cache.store_metrics(&metrics("a@example.com"));
+
+        let now = Instant::now();
+        assert!(cache.fresh_metrics("a@example.com", now).is_some());
+        assert!(cache.fresh_metrics("b@example.com", now).is_none());
+        assert!(cache
+            .fresh_metrics("a@example.com", now + Duration::from_secs(61))
+            .is_none());
+    }
+
+    #[test]
+    fn test_project_cache() {
+        let cache = MetricsCache::new(METRICS_TTL);
+        cache.store_project("a@example.com", "project-1");
+        assert_eq!(cache.project("a@example.com").as_deref(), Some("project-1"));
+
+        cache.invalidate_project("a@example.com");
+        assert!(cache.project("a@example.com").is_none());
+    }
+}
@@ -1,8 +1,18 @@
 pub mod account;
+pub mod account_health;
 pub mod backup;
+pub mod bundle;
+pub mod deep_link;
+pub mod launch;
 pub mod settings;
 pub mod platform;
 // crypto 模块已迁移到 security::crypto
 pub mod system;
 pub mod google_api;
+pub mod http_client;
+pub mod http_retry;
+pub mod metrics_cache;
+pub mod notification;
+pub mod quota_alignment;
+pub mod scheduler;
 pub mod window;
@@ -0,0 +1,576 @@
+//! 桌面通知
+//!
+//! 订阅事件总线，按用户配置的规则（见 [`NotificationRule`]）发送通知：
+//!
+//! - 剩余配额低于阈值（可限定模型）、配额重置：由 `quota_updated` 事件与上次的配额比较得出，
+//!   只在越过阈值时通知一次
+//! - token 被吊销、切换账户成功 / 失败、保存了新账户：对应同名事件
+//!
+//! 同一规则对同一账户 / 模型在 `min_interval_secs` 内只通知一次（切换结果除外，它是对用户操作的反馈），
+//! 所有通知合计每小时不超过 `max_per_hour` 条。隐私模式下通知内容中的邮箱会打码。
+//!
+//! 发送方式：
+//!
+//! - Linux：通过 D-Bus 调用 freedesktop 通知服务（`org.freedesktop.Notifications`）
+//! - macOS：`osascript` 的 `display notification`
+//! - Windows：PowerShell 调用 WinRT `ToastNotification`
+//!
+//! 通知在后台线程发送，失败只记录日志，不影响调用方。
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::app_settings::{AppSettings, AppSettingsManager};
+use crate::events::AppEvent;
+use crate::services::account::FULL_QUOTA;
+
+/// 通知中显示的应用名称
+const APP_NAME: &str = "Antigravity Agent";
+
+/// 统计 `max_per_hour` 的时间窗口
+const RATE_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// 通知规则的触发条件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationTrigger {
+    /// 剩余配额低于 `threshold`（百分比，0-100）；`model` 为空时适用于所有模型
+    QuotaLow {
+        threshold: f64,
+        #[serde(default)]
+        model: Option<String>,
+    },
+    /// 配额已重置（剩余配额从未满恢复为满）
+    QuotaReset {
+        #[serde(default)]
+        model: Option<String>,
+    },
+    /// 账户的 refresh token 已被吊销
+    TokenRevoked,
+    /// 切换账户成功
+    SwitchSucceeded,
+    /// 切换账户失败
+    SwitchFailed,
+    /// 保存了此前没有的账户
+    AccountCaptured,
+}
+
+/// 通知规则
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationRule {
+    #[serde(default = "default_rule_enabled")]
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub trigger: NotificationTrigger,
+}
+
+fn default_rule_enabled() -> bool {
+    true
+}
+
+impl NotificationRule {
+    /// 默认规则：每种通知各一条，配额低于 20% 时提醒
+    pub fn defaults() -> Vec<Self> {
+        [
+            NotificationTrigger::QuotaLow {
+                threshold: 20.0,
+                model: None,
+            },
+            NotificationTrigger::QuotaReset { model: None },
+            NotificationTrigger::TokenRevoked,
+            NotificationTrigger::SwitchSucceeded,
+            NotificationTrigger::SwitchFailed,
+            NotificationTrigger::AccountCaptured,
+        ]
+        .into_iter()
+        .map(|trigger| Self {
+            enabled: true,
+            trigger,
+        })
+        .collect()
+    }
+
+    /// 校验规则参数
+    pub fn validate(&self) -> Result<(), String> {
+        if let NotificationTrigger::QuotaLow { threshold, .. } = self.trigger {
+            if !(0.0..=100.0).contains(&threshold) {
+                return Err(format!("配额阈值必须在 0 到 100 之间: {}", threshold));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 规则中的模型是否匹配（为空时匹配所有模型）
+fn model_matches(rule_model: &Option<String>, model_name: &str) -> bool {
+    rule_model
+        .as_deref()
+        .is_none_or(|model| model.eq_ignore_ascii_case(model_name))
+}
+
+/// 一条待发送的通知
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    /// 去重键，为 `None` 时不受 `min_interval_secs` 限制
+    key: Option<String>,
+    pub summary: String,
+    pub body: String,
+}
+
+/// 通知频率限制
+#[derive(Default)]
+struct RateLimiter {
+    last_sent: HashMap<String, Instant>,
+    recent: VecDeque<Instant>,
+}
+
+impl RateLimiter {
+    /// 是否允许发送，允许时记录本次发送
+    fn allow(
+        &mut self,
+        key: Option<&str>,
+        now: Instant,
+        min_interval: Duration,
+        max_per_hour: u32,
+    ) -> bool {
+        if let Some(last) = key.and_then(|key| self.last_sent.get(key)) {
+            if now.duration_since(*last) < min_interval {
+                return false;
+            }
+        }
+        while self
+            .recent
+            .front()
+            .is_some_and(|sent| now.duration_since(*sent) >= RATE_WINDOW)
+        {
+            self.recent.pop_front();
+        }
+        if self.recent.len() >= max_per_hour as usize {
+            return false;
+        }
+
+        if let Some(key) = key {
+            self.last_sent.insert(key.to_string(), now);
+        }
+        self.recent.push_back(now);
+        true
+    }
+}
+
+/// 根据事件与规则生成通知
+#[derive(Default)]
+pub struct NotificationEngine {
+    /// 各账户各模型上次的剩余配额（0..1）
+    last_quotas: HashMap<String, HashMap<String, f64>>,
+    limiter: RateLimiter,
+}
+
+impl NotificationEngine {
+    /// 处理一个事件，返回通过频率限制、需要发送的通知
+    pub fn evaluate(
+        &mut self,
+        event: &AppEvent,
+        settings: &AppSettings,
+        now: Instant,
+    ) -> Vec<Notification> {
+        // 配额记录在通知关闭时也要更新，否则重新开启后会和过期数据比较
+        let previous_quotas = match event {
+            AppEvent::QuotaUpdated { metrics } => {
+                let current = metrics
+                    .quotas
+                    .iter()
+                    .map(|quota| (quota.model_name.clone(), quota.percentage))
+                    .collect();
+                self.last_quotas.insert(metrics.email.clone(), current)
+            }
+            _ => None,
+        };
+
+        let config = &settings.notifications;
+        if !config.enabled {
+            return Vec::new();
+        }
+        let zh = settings.language.starts_with("zh");
+
+        let mut notifications = Vec::new();
+        for rule in config.rules.iter().filter(|rule| rule.enabled) {
+            match (&rule.trigger, event) {
+                (
+                    NotificationTrigger::QuotaLow { threshold, model },
+                    AppEvent::QuotaUpdated { metrics },
+                ) => {
+                    for quota in &metrics.quotas {
+                        let remaining = quota.percentage * 100.0;
+                        let was_above = previous_quotas
+                            .as_ref()
+                            .and_then(|previous| previous.get(&quota.model_name))
+                            .is_none_or(|previous| previous * 100.0 >= *threshold);
+                        if model_matches(model, &quota.model_name)
+                            && remaining < *threshold
+                            && was_above
+                        {
+                            notifications.push(Notification {
+                                key: Some(format!(
+                                    "quota_low:{}:{}:{}",
+                                    threshold, metrics.email, quota.model_name
+                                )),
+                                summary: text(zh, "配额不足", "Quota running low"),
+                                body: if zh {
+                                    format!(
+                                        "{} 的 {} 剩余 {:.0}%",
+                                        metrics.email, quota.model_name, remaining
+                                    )
+                                } else {
+                                    format!(
+                                        "{} on {}: {:.0}% left",
+                                        quota.model_name, metrics.email, remaining
+                                    )
+                                },
+                            });
+                        }
+                    }
+                }
+                (NotificationTrigger::QuotaReset { model }, AppEvent::QuotaUpdated { metrics }) => {
+                    for quota in &metrics.quotas {
+                        let was_used = previous_quotas
+                            .as_ref()
+                            .and_then(|previous| previous.get(&quota.model_name))
+                            .is_some_and(|previous| *previous < FULL_QUOTA);
+                        if model_matches(model, &quota.model_name)
+                            && quota.is_full()
+                            && was_used
+                        {
+                            notifications.push(Notification {
+                                key: Some(format!(
+                                    "quota_reset:{}:{}",
+                                    metrics.email, quota.model_name
+                                )),
+                                summary: text(zh, "配额已重置", "Quota reset"),
+                                body: if zh {
+                                    format!("{} 的 {} 配额已恢复", metrics.email, quota.model_name)
+                                } else {
+                                    format!(
+                                        "{} on {} is available again",
+                                        quota.model_name, metrics.email
+                                    )
+                                },
+                            });
+                        }
+                    }
+                }
+                (NotificationTrigger::TokenRevoked, AppEvent::TokenRevoked { email }) => {
+                    notifications.push(Notification {
+                        key: Some(format!("token_revoked:{}", email)),
+                        summary: text(zh, "账户需要重新登录", "Sign-in required"),
+                        body: if zh {
+                            format!("{} 的登录凭据已失效", email)
+                        } else {
+                            format!("The credentials for {} were revoked", email)
+                        },
+                    });
+                }
+                (NotificationTrigger::SwitchSucceeded, AppEvent::AccountSwitched { email }) => {
+                    notifications.push(Notification {
+                        key: None,
+                        summary: text(zh, "已切换账户", "Account switched"),
+                        body: email.clone(),
+                    });
+                }
+                (
+                    NotificationTrigger::SwitchFailed,
+                    AppEvent::AccountSwitchFailed { email, error },
+                ) => {
+                    notifications.push(Notification {
+                        key: None,
+                        summary: text(zh, "切换账户失败", "Account switch failed"),
+                        body: format!("{}: {}", email, error),
+                    });
+                }
+                (
+                    NotificationTrigger::AccountCaptured,
+                    AppEvent::AccountSaved {
+                        email,
+                        created: true,
+                    },
+                ) => {
+                    notifications.push(Notification {
+                        key: Some(format!("account_captured:{}", email)),
+                        summary: text(zh, "已保存新账户", "New account saved"),
+                        body: email.clone(),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        let min_interval = Duration::from_secs(config.min_interval_secs);
+        notifications.retain(|notification| {
+            let allowed = self.limiter.allow(
+                notification.key.as_deref(),
+                now,
+                min_interval,
+                config.max_per_hour,
+            );
+            if !allowed {
+                tracing::debug!(target: "app::notification", summary = %notification.summary, "通知被频率限制丢弃");
+            }
+            allowed
+        });
+        if settings.private_mode {
+            for notification in &mut notifications {
+                notification.body =
+                    crate::utils::log_sanitizer::sanitize_log_message(&notification.body);
+            }
+        }
+        notifications
+    }
+}
+
+fn text(zh: bool, zh_text: &str, en_text: &str) -> String {
+    if zh { zh_text } else { en_text }.to_string()
+}
+
+/// 订阅事件总线，按规则发送通知
+pub fn watch_app_events(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut receiver = crate::events::subscribe();
+        let mut engine = NotificationEngine::default();
+        loop {
+            let event = match receiver.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!(target: "app::notification", skipped, "事件积压，部分事件未检查通知规则");
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            let settings = app.state::<AppSettingsManager>().get_settings();
+            for notification in engine.evaluate(&event, &settings, Instant::now()) {
+                notify(&notification.summary, &notification.body);
+            }
+        }
+    });
+}
+
+/// 发送一条桌面通知
+pub fn notify(summary: &str, body: &str) {
+    let summary = summary.to_string();
+    let body = body.to_string();
+    std::thread::spawn(move || {
+        if let Err(e) = send(&summary, &body) {
+            tracing::warn!(target: "app::notification", error = %e, summary = %summary, "发送桌面通知失败");
+        }
+    });
+}
+
+#[cfg(target_os = "linux")]
+fn send(summary: &str, body: &str) -> Result<(), String> {
+    use std::collections::HashMap;
+    use zbus::zvariant::Value;
+
+    let connection =
+        zbus::blocking::Connection::session().map_err(|e| format!("连接 D-Bus 失败: {}", e))?;
+    let hints: HashMap<&str, Value> = HashMap::new();
+    connection
+        .call_method(
+            Some("org.freedesktop.Notifications"),
+            "/org/freedesktop/Notifications",
+            Some("org.freedesktop.Notifications"),
+            "Notify",
+            &(
+                APP_NAME,
+                0u32,
+                "",
+                summary,
+                body,
+                Vec::<&str>::new(),
+                hints,
+                -1i32,
+            ),
+        )
+        .map_err(|e| format!("调用通知服务失败: {}", e))?;
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn send(summary: &str, body: &str) -> Result<(), String> {
+    // 文本作为参数传入，避免拼接进脚本
+    let status = std::process::Command::new("osascript")
+        .args([
+            "-e",
+            "on run argv",
+            "-e",
+            "display notification (item 2 of argv) with title (item 1 of argv) subtitle (item 3 of argv)",
+            "-e",
+            "end run",
+            APP_NAME,
+            body,
+            summary,
+        ])
+        .status()
+        .map_err(|e| format!("执行 osascript 失败: {}", e))?;
+    if !status.success() {
+        return Err(format!("osascript 退出码: {}", status));
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn send(summary: &str, body: &str) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    // 文本通过环境变量传入，避免拼接进脚本
+    const SCRIPT: &str = r#"
+[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null
+$template = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02)
+$texts = $template.GetElementsByTagName('text')
+$texts.Item(0).AppendChild($template.CreateTextNode($env:AGENT_NOTIFY_SUMMARY)) > $null
+$texts.Item(1).AppendChild($template.CreateTextNode($env:AGENT_NOTIFY_BODY)) > $null
+$toast = [Windows.UI.Notifications.ToastNotification]::new($template)
+[Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier($env:AGENT_NOTIFY_APP_ID).Show($toast)
+"#;
+
+    let status = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .env("AGENT_NOTIFY_SUMMARY", summary)
+        .env("AGENT_NOTIFY_BODY", body)
+        .env("AGENT_NOTIFY_APP_ID", "com.antigravity-agent.app")
+        .creation_flags(CREATE_NO_WINDOW)
+        .status()
+        .map_err(|e| format!("执行 PowerShell 失败: {}", e))?;
+    if !status.success() {
+        return Err(format!("PowerShell 退出码: {}", status));
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn send(_summary: &str, _body: &str) -> Result<(), String> {
+    Err("当前平台不支持桌面通知".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::account::{AccountMetrics, QuotaItem};
+
+    fn quota_event(email: &str, percentage: f64) -> AppEvent {
+        AppEvent::QuotaUpdated {
+            metrics: AccountMetrics {
+                email: email.to_string(),
+                user_id: "1".to_string(),
+                avatar_url: String::new(),
+                quotas: vec![QuotaItem {
+                    model_name: "Gemini 3 Pro".to_string(),
+                    percentage,
+                    reset_text: String::new(),
+                }],
+            },
+        }
+    }
+
+    fn settings() -> AppSettings {
+        AppSettings {
+            private_mode: false,
+            ..AppSettings::default()
+        }
+    }
+
+    #[test]
+    fn test_quota_low_and_reset_fire_on_crossing() {
+        let settings = settings();
+        let mut engine = NotificationEngine::default();
+        let now = Instant::now();
+
+        assert!(engine
+            .evaluate(&quota_event("a@x.com", 0.5), &settings, now)
+            .is_empty());
+        let sent = engine.evaluate(&quota_event("a@x.com", 0.1), &settings, now);
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].body, "Gemini 3 Pro on a@x.com: 10% left");
+        // 仍低于阈值，不重复通知
+        assert!(engine
+            .evaluate(&quota_event("a@x.com", 0.05), &settings, now)
+            .is_empty());
+
+        let sent = engine.evaluate(&quota_event("a@x.com", 1.0), &settings, now);
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].summary, "Quota reset");
+    }
+
+    #[test]
+    fn test_rule_filters_and_rate_limit() {
+        let mut settings = settings();
+        settings.notifications.rules = vec![NotificationRule {
+            enabled: true,
+            trigger: NotificationTrigger::QuotaLow {
+                threshold: 50.0,
+                model: Some("claude".to_string()),
+            },
+        }];
+        let mut engine = NotificationEngine::default();
+        let now = Instant::now();
+        assert!(engine
+            .evaluate(&quota_event("a@x.com", 0.1), &settings, now)
+            .is_empty());
+
+        settings.notifications.rules = NotificationRule::defaults();
+        settings.notifications.max_per_hour = 2;
+        let switched = |email: &str| AppEvent::AccountSwitched {
+            email: email.to_string(),
+        };
+        assert_eq!(
+            engine.evaluate(&switched("a@x.com"), &settings, now).len(),
+            1
+        );
+        assert_eq!(
+            engine.evaluate(&switched("a@x.com"), &settings, now).len(),
+            1
+        );
+        assert!(engine
+            .evaluate(&switched("b@x.com"), &settings, now)
+            .is_empty());
+        assert_eq!(
+            engine
+                .evaluate(&switched("b@x.com"), &settings, now + RATE_WINDOW)
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_rule_wire_format() {
+        let rule: NotificationRule =
+            serde_json::from_str(r#"{"type":"quota_low","threshold":10,"model":"Gemini 3 Pro"}"#)
+                .unwrap();
+        assert!(rule.enabled);
+        assert_eq!(
+            rule.trigger,
+            NotificationTrigger::QuotaLow {
+                threshold: 10.0,
+                model: Some("Gemini 3 Pro".to_string())
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(NotificationRule {
+                enabled: false,
+                trigger: NotificationTrigger::TokenRevoked
+            })
+            .unwrap(),
+            serde_json::json!({ "enabled": false, "type": "token_revoked" })
+        );
+        assert!(NotificationRule {
+            enabled: true,
+            trigger: NotificationTrigger::QuotaLow {
+                threshold: 120.0,
+                model: None
+            }
+        }
+        .validate()
+        .is_err());
+    }
+}
@@ -1,24 +1,56 @@
-use serde_json::Value;
+use serde::Serialize;
+
+/// 平台信息
+#[derive(Debug, Clone, Serialize)]
+pub struct PlatformInfo {
+    pub os: String,
+    pub arch: String,
+    pub family: String,
+    pub antigravity_available: bool,
+    /// 所有候选的 Antigravity 数据库路径
+    pub antigravity_paths: Vec<String>,
+    pub config_dir: Option<String>,
+    pub data_dir: Option<String>,
+    pub home_dir: Option<String>,
+}
 
-/// 获取平台信息
-pub async fn get_platform_info() -> Result<Value, String> {
-    let os_type = std::env::consts::OS;
-    let arch = std::env::consts::ARCH;
-    let family = std::env::consts::FAMILY;
+/// Antigravity 安装目录 / 可执行文件检测结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Detection {
+    pub found: bool,
+    pub path: Option<String>,
+    /// 是否为用户自定义的路径
+    pub is_custom_path: bool,
+}
 
-    let antigravity_available = crate::platform::is_antigravity_available();
-    let antigravity_paths = crate::platform::get_all_antigravity_db_paths();
+impl Detection {
+    fn not_found() -> Self {
+        Self {
+            found: false,
+            path: None,
+            is_custom_path: false,
+        }
+    }
+}
 
-    Ok(serde_json::json!({
-        "os": os_type,
-        "arch": arch,
-        "family": family,
-        "antigravity_available": antigravity_available,
-        "antigravity_paths": antigravity_paths.iter().map(|p| p.to_string_lossy()).collect::<Vec<_>>(),
-        "config_dir": dirs::config_dir().map(|p| p.to_string_lossy().to_string()),
-        "data_dir": dirs::data_dir().map(|p| p.to_string_lossy().to_string()),
-        "home_dir": dirs::home_dir().map(|p| p.to_string_lossy().to_string())
-    }))
+/// 获取平台信息
+pub async fn get_platform_info() -> Result<PlatformInfo, String> {
+    let to_string = |p: std::path::PathBuf| p.to_string_lossy().to_string();
+
+    Ok(PlatformInfo {
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        family: std::env::consts::FAMILY.to_string(),
+        antigravity_available: crate::platform::is_antigravity_available(),
+        antigravity_paths: crate::platform::get_all_antigravity_db_paths()
+            .into_iter()
+            .map(to_string)
+            .collect(),
+        config_dir: dirs::config_dir().map(to_string),
+        data_dir: dirs::data_dir().map(to_string),
+        home_dir: dirs::home_dir().map(to_string),
+    })
 }
 
 /// 查找 Antigravity 安装位置
@@ -38,7 +70,7 @@ pub async fn validate_antigravity_executable(path: String) -> Result<bool, Strin
 }
 
 /// 检测 Antigravity 安装状态（数据库路径）
-pub async fn detect_antigravity_installation() -> Result<serde_json::Value, String> {
+pub async fn detect_antigravity_installation() -> Result<Detection, String> {
     // 自动检测 Antigravity 数据库路径
     if let Some(db_path) = crate::platform::get_antigravity_db_path() {
         if db_path.exists() {
@@ -50,36 +82,32 @@ pub async fn detect_antigravity_installation() -> Result<serde_json::Value, Stri
             println!("📁 检测到 Antigravity 数据库: {}", db_path.display());
             println!("📂 Antigravity 数据目录: {}", data_dir);
 
-            return Ok(serde_json::json!({
-                "found": true,
-                "path": data_dir,
-                "isCustomPath": false
-            }));
+            return Ok(Detection {
+                found: true,
+                path: Some(data_dir),
+                is_custom_path: false,
+            });
         }
     }
 
     // 未找到
     println!("⚠️ 未找到 Antigravity 数据库");
-    Ok(serde_json::json!({
-        "found": false,
-        "path": null,
-        "isCustomPath": false
-    }))
+    Ok(Detection::not_found())
 }
 
 /// 检测 Antigravity 可执行文件
-pub async fn detect_antigravity_executable() -> Result<serde_json::Value, String> {
+pub async fn detect_antigravity_executable() -> Result<Detection, String> {
     // 1. 尝试从配置读取自定义可执行文件路径
     let custom_exec = crate::antigravity::path_config::get_custom_executable_path().unwrap_or(None);
 
     // 2. 检查自定义可执行文件路径是否有效
     if let Some(ref path) = custom_exec {
         if crate::antigravity::path_config::validate_executable_path(path) {
-            return Ok(serde_json::json!({
-                "found": true,
-                "path": path,
-                "isCustomPath": true
-            }));
+            return Ok(Detection {
+                found: true,
+                path: Some(path.clone()),
+                is_custom_path: true,
+            });
         }
     }
 
@@ -88,20 +116,16 @@ pub async fn detect_antigravity_executable() -> Result<serde_json::Value, String
     if let Some(exec_path) = detected_path {
         println!("✅ 检测到 Antigravity 可执行文件: {}", exec_path.display());
 
-        return Ok(serde_json::json!({
-            "found": true,
-            "path": exec_path.to_string_lossy().to_string(),
-            "isCustomPath": false
-        }));
+        return Ok(Detection {
+            found: true,
+            path: Some(exec_path.to_string_lossy().to_string()),
+            is_custom_path: false,
+        });
     }
 
     // 4. 未找到
     println!("⚠️ 未找到 Antigravity 可执行文件，启动功能可能不可用");
-    Ok(serde_json::json!({
-        "found": false,
-        "path": null,
-        "isCustomPath": false
-    }))
+    Ok(Detection::not_found())
 }
 
 /// 保存用户自定义的 Antigravity 可执行文件路径
@@ -117,9 +141,14 @@ pub async fn save_antigravity_executable(path: String) -> Result<String, String>
     Ok(format!("已保存 Antigravity 可执行文件路径: {}", path))
 }
 
+/// 用户自定义的 Antigravity 可执行文件路径（不检查是否仍然有效）
+pub async fn get_custom_executable() -> Result<Option<String>, String> {
+    crate::antigravity::path_config::get_custom_executable_path()
+}
+
 /// 获取当前配置的路径
 pub async fn get_current_paths() -> Result<serde_json::Value, String> {
-    let exec_path = crate::antigravity::path_config::get_custom_executable_path().unwrap_or(None);
+    let exec_path = get_custom_executable().await.unwrap_or(None);
 
     Ok(serde_json::json!({
         "executablePath": exec_path
@@ -0,0 +1,432 @@
+//! 配额重置窗口对齐
+//!
+//! 模型配额的重置窗口从第一次请求开始计时，`window_minutes` 后重置。这里在选定的时刻为所有
+//! 已保存的账户发送最小请求（`trigger_minimal_query`）开启窗口，让各账户的窗口按计划排开，
+//! 例如 5 个账户、5 小时窗口错开分配时，每小时都有一个账户的 Claude 配额刚刚重置。
+//!
+//! 每个账户分配一个锚点时刻，目标开启时刻为 `锚点 + k × 窗口时长`（只取窗口能在次日锚点前
+//! 结束的 k），每天重复：
+//!
+//! - 配额已满（窗口未开始）：刚过某个目标时刻不超过 [`TRIGGER_TOLERANCE`] 时立即触发，
+//!   否则计划在下一个目标时刻触发
+//! - 配额未满（窗口进行中）：计划在 `resetTime` 之后的第一个目标时刻触发
+//!
+//! 到期的触发由定时任务 `align_quota_windows` 执行，任务间隔应小于 [`TRIGGER_TOLERANCE`]。
+
+use chrono::{Duration, Local, NaiveDateTime, NaiveTime};
+use serde::Serialize;
+use std::path::Path;
+
+use super::account::{self, QuotaItem};
+use super::google_api::{self, CloudCodeClient};
+use crate::app_settings::QuotaAlignmentSettings;
+
+/// 目标时刻过后仍立即触发的时长
+pub const TRIGGER_TOLERANCE: Duration = Duration::minutes(10);
+
+/// 计划中的一次触发
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedTrigger {
+    pub email: String,
+    pub model: String,
+    /// 计划触发时间（RFC 3339）
+    pub at: String,
+    /// 是否已到期（本次执行时会触发）
+    pub due: bool,
+    /// 当前窗口的重置时间（RFC 3339），配额已满时为空
+    pub reset_at: Option<String>,
+}
+
+/// 已执行的触发
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutedTrigger {
+    pub email: String,
+    pub model: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// 无法获取配额的账户
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountFailure {
+    pub email: String,
+    pub error: String,
+}
+
+/// 对齐报告
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlignmentReport {
+    /// 生成时间（RFC 3339）
+    pub generated_at: String,
+    /// 为 true 时只生成计划，不发送触发请求
+    pub dry_run: bool,
+    pub planned: Vec<PlannedTrigger>,
+    pub executed: Vec<ExecutedTrigger>,
+    pub failed_accounts: Vec<AccountFailure>,
+}
+
+impl AlignmentReport {
+    /// 用于日志和定时任务运行记录的摘要
+    pub fn summary(&self) -> String {
+        let succeeded = self.executed.iter().filter(|e| e.success).count();
+        format!(
+            "计划 {} 次触发，已触发 {} 次，失败 {} 次，{} 个账户无法获取配额",
+            self.planned.len(),
+            succeeded,
+            self.executed.len() - succeeded,
+            self.failed_accounts.len()
+        )
+    }
+}
+
+/// 校验对齐设置
+pub fn validate(settings: &QuotaAlignmentSettings) -> Result<(), String> {
+    if !(1..=24 * 60).contains(&settings.window_minutes) {
+        return Err("窗口时长应在 1-1440 分钟之间".to_string());
+    }
+    if settings.models.is_empty() {
+        return Err("至少需要选择一个模型".to_string());
+    }
+    for model in &settings.models {
+        if !account::quota_model_names().any(|name| name.eq_ignore_ascii_case(model)) {
+            return Err(format!(
+                "未知的模型: {}（可选: {}）",
+                model,
+                account::quota_model_names().collect::<Vec<_>>().join(", ")
+            ));
+        }
+    }
+    parse_anchors(&settings.anchors).map(|_| ())
+}
+
+fn parse_anchors(anchors: &[String]) -> Result<Vec<NaiveTime>, String> {
+    if anchors.is_empty() {
+        return Err("至少需要一个锚点时刻".to_string());
+    }
+    anchors
+        .iter()
+        .map(|anchor| {
+            NaiveTime::parse_from_str(anchor.trim(), "%H:%M")
+                .map_err(|_| format!("锚点时刻格式应为 HH:MM: {}", anchor))
+        })
+        .collect()
+}
+
+fn window(settings: &QuotaAlignmentSettings) -> Duration {
+    Duration::minutes(i64::from(settings.window_minutes))
+}
+
+/// 为按邮箱排序的 `count` 个账户分配锚点
+fn assign_anchors(
+    settings: &QuotaAlignmentSettings,
+    count: usize,
+) -> Result<Vec<NaiveTime>, String> {
+    let anchors = parse_anchors(&settings.anchors)?;
+    if settings.stagger {
+        let step = window(settings) / count.max(1) as i32;
+        Ok((0..count).map(|i| anchors[0] + step * i as i32).collect())
+    } else {
+        Ok((0..count).map(|i| anchors[i % anchors.len()]).collect())
+    }
+}
+
+/// `around` 前后各一天内的目标开启时刻（升序）
+fn slots_around(anchor: NaiveTime, window: Duration, around: NaiveDateTime) -> Vec<NaiveDateTime> {
+    let per_day = (Duration::days(1).num_minutes() / window.num_minutes()).max(1);
+    let mut slots: Vec<NaiveDateTime> = [-1, 0, 1]
+        .into_iter()
+        .filter_map(|offset| around.date().checked_add_signed(Duration::days(offset)))
+        .flat_map(|date| {
+            let start = date.and_time(anchor);
+            (0..per_day).map(move |k| start + window * k as i32)
+        })
+        .collect();
+    slots.sort();
+    slots
+}
+
+/// 规划单个账户的触发
+fn plan_account(
+    email: &str,
+    quotas: &[QuotaItem],
+    anchor: NaiveTime,
+    settings: &QuotaAlignmentSettings,
+    now: NaiveDateTime,
+) -> Vec<PlannedTrigger> {
+    let window = window(settings);
+    let to_text = |time: NaiveDateTime| {
+        time.and_local_timezone(Local)
+            .earliest()
+            .map(|time| time.to_rfc3339())
+            .unwrap_or_else(|| time.to_string())
+    };
+
+    quotas
+        .iter()
+        .filter(|quota| {
+            settings
+                .models
+                .iter()
+                .any(|model| model.eq_ignore_ascii_case(&quota.model_name))
+        })
+        .filter_map(|quota| {
+            let (at, due, reset_at) = if quota.is_full() {
+                let slots = slots_around(anchor, window, now);
+                match slots.iter().rev().find(|slot| **slot <= now) {
+                    Some(&slot) if now - slot <= TRIGGER_TOLERANCE => (slot, true, None),
+                    _ => (*slots.iter().find(|slot| **slot > now)?, false, None),
+                }
+            } else {
+                let reset_at = quota.reset_at()?.with_timezone(&Local).naive_local();
+                let from = reset_at.max(now);
+                let next = *slots_around(anchor, window, from)
+                    .iter()
+                    .find(|slot| **slot >= from)?;
+                (next, false, Some(reset_at))
+            };
+            Some(PlannedTrigger {
+                email: email.to_string(),
+                model: quota.model_name.clone(),
+                at: to_text(at),
+                due,
+                reset_at: reset_at.map(to_text),
+            })
+        })
+        .collect()
+}
+
+/// 获取所有账户的配额并生成计划；`dry_run` 为 false 时发送到期的触发请求
+pub async fn run(
+    config_dir: &Path,
+    settings: &QuotaAlignmentSettings,
+    dry_run: bool,
+) -> Result<AlignmentReport, String> {
+    let client = google_api::default_client();
+    run_with(&client, config_dir, settings, dry_run).await
+}
+
+/// 使用指定的 Google API 客户端执行对齐
+pub async fn run_with(
+    client: &dyn CloudCodeClient,
+    config_dir: &Path,
+    settings: &QuotaAlignmentSettings,
+    dry_run: bool,
+) -> Result<AlignmentReport, String> {
+    validate(settings)?;
+    let emails = account::list_account_emails(config_dir)?;
+    let anchors = assign_anchors(settings, emails.len())?;
+    let start_time = std::time::Instant::now();
+
+    let mut report = AlignmentReport {
+        generated_at: Local::now().to_rfc3339(),
+        dry_run,
+        planned: Vec::new(),
+        executed: Vec::new(),
+        failed_accounts: Vec::new(),
+    };
+
+    for (email, anchor) in emails.iter().zip(anchors) {
+        let session = match open_session(client, config_dir, email).await {
+            Ok(session) => session,
+            Err(e) => {
+                tracing::warn!(target: "account::quota_alignment", email = %email, error = %e, "获取配额失败");
+                report.failed_accounts.push(AccountFailure {
+                    email: email.clone(),
+                    error: e,
+                });
+                continue;
+            }
+        };
+
+        let planned = plan_account(
+            email,
+            &session.quotas,
+            anchor,
+            settings,
+            Local::now().naive_local(),
+        );
+        if !dry_run {
+            for trigger in planned.iter().filter(|trigger| trigger.due) {
+                let Some(key) = account::model_key(&trigger.model) else {
+                    continue;
+                };
+                let result = client
+                    .trigger_minimal_query(&session.access_token, &session.project, key)
+                    .await;
+                if let Err(e) = &result {
+                    tracing::warn!(target: "account::quota_alignment", email = %email, model = %trigger.model, error = %e, "触发配额窗口失败");
+                }
+                report.executed.push(ExecutedTrigger {
+                    email: email.clone(),
+                    model: trigger.model.clone(),
+                    success: result.is_ok(),
+                    error: result.err().map(|e| e.to_string()),
+                });
+            }
+        }
+        report.planned.extend(planned);
+    }
+
+    tracing::info!(
+        target: "account::quota_alignment",
+        duration_ms = start_time.elapsed().as_millis(),
+        dry_run = dry_run,
+        "{}",
+        report.summary()
+    );
+    Ok(report)
+}
+
+/// 已登录账户的 token、project 与当前配额
+struct Session {
+    access_token: String,
+    project: String,
+    quotas: Vec<QuotaItem>,
+}
+
+async fn open_session(
+    client: &dyn CloudCodeClient,
+    config_dir: &Path,
+    email: &str,
+) -> Result<Session, String> {
+    let (email, proto_bytes) = google_api::load_account(config_dir, email).await?;
+    let token_info = google_api::get_valid_token(client, &email, &proto_bytes).await?;
+
+    let cache = &super::metrics_cache::METRICS_CACHE;
+    let project = match cache.project(&email) {
+        Some(project) => project,
+        None => {
+            let project = client
+                .fetch_code_assist_project(&token_info.access_token)
+                .await
+                .map_err(|e| format!("获取项目 ID 失败: {}", e))?;
+            cache.store_project(&email, &project);
+            project
+        }
+    };
+    let models_json = match client
+        .fetch_available_models(&token_info.access_token, &project)
+        .await
+    {
+        Ok(json) => json,
+        Err(e) => {
+            cache.invalidate_project(&email);
+            return Err(format!("获取模型列表失败: {}", e));
+        }
+    };
+
+    Ok(Session {
+        access_token: token_info.access_token,
+        project,
+        quotas: account::parse_quotas(&models_json),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn quota(percentage: f64, reset_at: Option<NaiveDateTime>) -> QuotaItem {
+        QuotaItem {
+            model_name: "Claude".to_string(),
+            percentage,
+            reset_text: reset_at
+                .map(|time| {
+                    time.and_local_timezone(Local)
+                        .unwrap()
+                        .with_timezone(&chrono::Utc)
+                        .to_rfc3339()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    fn plan(quota: QuotaItem, anchor: &str, now: &str) -> PlannedTrigger {
+        let settings = QuotaAlignmentSettings::default();
+        let anchor = NaiveTime::parse_from_str(anchor, "%H:%M").unwrap();
+        let mut planned = plan_account("a@example.com", &[quota], anchor, &settings, at(now));
+        assert_eq!(planned.len(), 1);
+        planned.remove(0)
+    }
+
+    fn local(text: &str) -> String {
+        at(text).and_local_timezone(Local).unwrap().to_rfc3339()
+    }
+
+    #[test]
+    fn test_assign_anchors() {
+        let settings = QuotaAlignmentSettings {
+            anchors: vec!["06:00".to_string(), "09:30".to_string()],
+            ..Default::default()
+        };
+        let staggered = assign_anchors(&settings, 5).unwrap();
+        assert_eq!(staggered[1], NaiveTime::from_hms_opt(7, 0, 0).unwrap());
+        assert_eq!(staggered[4], NaiveTime::from_hms_opt(10, 0, 0).unwrap());
+
+        let fixed = assign_anchors(
+            &QuotaAlignmentSettings {
+                stagger: false,
+                ..settings
+            },
+            3,
+        )
+        .unwrap();
+        assert_eq!(fixed[1], NaiveTime::from_hms_opt(9, 30, 0).unwrap());
+        assert_eq!(fixed[2], NaiveTime::from_hms_opt(6, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_plan_account() {
+        // 锚点 07:00、5 小时窗口：每天 07:00 / 12:00 / 17:00 / 22:00
+        let due = plan(quota(1.0, None), "07:00", "2025-01-01 12:05");
+        assert!(due.due);
+        assert_eq!(due.at, local("2025-01-01 12:00"));
+
+        let waiting = plan(quota(1.0, None), "07:00", "2025-01-01 12:30");
+        assert!(!waiting.due);
+        assert_eq!(waiting.at, local("2025-01-01 17:00"));
+
+        // 22:00 之后下一个目标时刻是次日 07:00
+        let overnight = plan(quota(1.0, None), "07:00", "2025-01-01 23:00");
+        assert_eq!(overnight.at, local("2025-01-02 07:00"));
+
+        // 窗口进行中：等到重置之后的第一个目标时刻
+        let running = plan(
+            quota(0.4, Some(at("2025-01-01 13:20"))),
+            "07:00",
+            "2025-01-01 10:00",
+        );
+        assert!(!running.due);
+        assert_eq!(running.at, local("2025-01-01 17:00"));
+        assert_eq!(running.reset_at, Some(local("2025-01-01 13:20")));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(&QuotaAlignmentSettings::default()).is_ok());
+        for settings in [
+            QuotaAlignmentSettings {
+                window_minutes: 0,
+                ..Default::default()
+            },
+            QuotaAlignmentSettings {
+                models: vec!["GPT".to_string()],
+                ..Default::default()
+            },
+            QuotaAlignmentSettings {
+                anchors: vec!["25:00".to_string()],
+                ..Default::default()
+            },
+        ] {
+            assert!(validate(&settings).is_err(), "{:?}", settings);
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! 五段式 cron 表达式：`分 时 日 月 周`
+//!
+//! 每段支持 `*`、数字、范围 `a-b`、步长 `*/n` / `a-b/n` 以及逗号分隔的列表；
+//! 周的取值为 0-7（0 和 7 都是周日）。另支持 `@hourly`、`@daily`、`@weekly`、`@monthly` 简写。
+//! 日和周同时受限（不以 `*` 开头）时按 cron 惯例取并集（满足其一即可），
+//! 否则取交集，例如 `0 0 */2 * 1` 表示奇数日且为周一。
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+
+/// 查找下一次执行时间时最多检查的步数（约可覆盖数年）
+const MAX_SEARCH_STEPS: usize = 100_000;
+
+/// 解析后的 cron 表达式
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// 日字段是否不以 `*` 开头
+    days_restricted: bool,
+    /// 周字段是否不以 `*` 开头
+    weekdays_restricted: bool,
+}
+
+impl CronSchedule {
+    /// 解析表达式
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields.as_slice() else {
+            return Err(format!(
+                "cron 表达式应包含 5 个字段（分 时 日 月 周）: {}",
+                expression
+            ));
+        };
+
+        // 7 和 0 都表示周日
+        let mut weekdays = parse_field(weekday, 0, 7, "周")?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays & !(1 << 7)) | 1;
+        }
+
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, "分")?,
+            hours: parse_field(hour, 0, 23, "时")?,
+            days: parse_field(day, 1, 31, "日")?,
+            months: parse_field(month, 1, 12, "月")?,
+            weekdays,
+            days_restricted: !day.starts_with('*'),
+            weekdays_restricted: !weekday.starts_with('*'),
+        })
+    }
+
+    /// `after` 之后（不含）的下一次执行时间，表达式永远不会匹配时返回 `None`
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut time = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+
+        for _ in 0..MAX_SEARCH_STEPS {
+            let date = time.date();
+            if !contains(self.months, date.month()) {
+                let (year, month) = if date.month() == 12 {
+                    (date.year() + 1, 1)
+                } else {
+                    (date.year(), date.month() + 1)
+                };
+                time = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !self.day_matches(date) {
+                time = date.succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !contains(self.hours, time.hour()) {
+                time = date.and_hms_opt(time.hour(), 0, 0)? + Duration::hours(1);
+                continue;
+            }
+            if !contains(self.minutes, time.minute()) {
+                time += Duration::minutes(1);
+                continue;
+            }
+            return Some(time);
+        }
+        None
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day = contains(self.days, date.day());
+        let weekday = contains(self.weekdays, date.weekday().num_days_from_sunday());
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+}
+
+fn contains(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+/// 解析单个字段为位集合
+fn parse_field(field: &str, min: u32, max: u32, name: &str) -> Result<u64, String> {
+    let invalid = || format!("cron 表达式的{}字段无效: {}", name, field);
+    let number = |value: &str| -> Result<u32, String> {
+        let value: u32 = value.parse().map_err(|_| invalid())?;
+        if (min..=max).contains(&value) {
+            Ok(value)
+        } else {
+            Err(format!(
+                "cron 表达式的{}字段超出范围 {}-{}: {}",
+                name, min, max, field
+            ))
+        }
+    };
+
+    let mut set = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(invalid());
+        }
+        let (start, end) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                Some((start, end)) => (number(start)?, number(end)?),
+                // `a/n` 表示从 a 开始到最大值
+                None if part.contains('/') => (number(range)?, max),
+                None => {
+                    let value = number(range)?;
+                    (value, value)
+                }
+            },
+        };
+        if start > end {
+            return Err(invalid());
+        }
+        for value in (start..=end).step_by(step as usize) {
+            set |= 1 << value;
+        }
+    }
+    Ok(set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn next(expression: &str, after: &str) -> Option<NaiveDateTime> {
+        CronSchedule::parse(expression)
+            .unwrap()
+            .next_after(at(after))
+    }
+
+    #[test]
+    fn test_next_after() {
+        assert_eq!(
+            next("*/15 * * * *", "2025-01-01 10:07"),
+            Some(at("2025-01-01 10:15"))
+        );
+        assert_eq!(
+            next("*/15 * * * *", "2025-01-01 10:15"),
+            Some(at("2025-01-01 10:30"))
+        );
+        assert_eq!(
+            next("@hourly", "2025-01-01 23:30"),
+            Some(at("2025-01-02 00:00"))
+        );
+        assert_eq!(
+            next("30 3 * * 1-5", "2025-01-03 04:00"),
+            Some(at("2025-01-06 03:30"))
+        );
+        assert_eq!(
+            next("0 0 29 2 *", "2025-03-01 00:00"),
+            Some(at("2028-02-29 00:00"))
+        );
+        // 日和周同时受限时满足其一即可：1 号或周日
+        assert_eq!(
+            next("0 12 1 * 7", "2025-01-02 00:00"),
+            Some(at("2025-01-05 12:00"))
+        );
+        // 以 `*` 开头的字段不算受限，`*/2` 与周一同时满足
+        assert_eq!(
+            next("0 0 */2 * 1", "2025-01-01 00:00"),
+            Some(at("2025-01-13 00:00"))
+        );
+        assert_eq!(next("0 0 30 2 *", "2025-01-01 00:00"), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for expression in [
+            "* * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a * * * *",
+            "* * 0 * *",
+        ] {
+            assert!(
+                CronSchedule::parse(expression).is_err(),
+                "{} 应解析失败",
+                expression
+            );
+        }
+    }
+}
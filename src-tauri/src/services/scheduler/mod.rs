@@ -0,0 +1,664 @@
+//! 定时任务
+//!
+//! 在应用设置（`AppSettings.scheduler`）中用 cron 表达式（见 [`cron`]）安排内置任务：
+//! 刷新配额指标、检查 token 健康状态、保存当前账户、为所有账户触发配额刷新、清理过期快照、
+//! 对齐配额重置窗口（见 [`super::quota_alignment`]）。
+//!
+//! 调度循环每 [`TICK_INTERVAL`] 检查一次，每个任务记录上次处理到的时间：
+//!
+//! - 计划时间在 [`MISSED_GRACE`] 内被检查到：正常执行
+//! - 超过宽限期才检查到（应用未运行、系统休眠）：视为错过，按 `missed_run` 策略补跑一次或跳过，
+//!   连续错过多次也只补跑一次
+//! - 新增的任务从添加时开始计时，不会补跑添加之前的计划
+//!
+//! 同一任务不会并发执行。上次处理时间和最近 [`MAX_HISTORY`] 条运行记录保存在
+//! `scheduler_state.json`，重启后继续生效。
+
+pub mod cron;
+
+use chrono::{DateTime, Local, NaiveDateTime};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::app_settings::AppSettingsManager;
+use cron::CronSchedule;
+
+/// 调度循环的检查间隔
+pub const TICK_INTERVAL: Duration = Duration::from_secs(20);
+
+/// 计划时间过去超过该时长才被检查到时视为错过
+pub const MISSED_GRACE: Duration = Duration::from_secs(2 * 60);
+
+/// 保留的运行记录条数
+pub const MAX_HISTORY: usize = 200;
+
+/// 内置任务
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobKind {
+    /// 刷新所有账户的配额指标（忽略缓存）
+    RefreshMetrics,
+    /// 检查所有账户的 token 健康状态
+    CheckTokenHealth,
+    /// 保存当前登录的账户
+    BackupCurrent,
+    /// 为所有账户触发配额刷新
+    TriggerQuotaRefresh,
+    /// 删除早于 `keep_days` 天的 state.vscdb 快照
+    PruneSnapshots { keep_days: u32 },
+    /// 按配额对齐设置触发到期的配额窗口
+    AlignQuotaWindows,
+}
+
+/// 错过计划时间时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissedRunPolicy {
+    /// 跳过，等待下一次计划时间
+    #[default]
+    Skip,
+    /// 立即补跑一次
+    RunOnce,
+}
+
+/// 定时任务配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledJob {
+    /// 任务 ID，在所有任务中唯一
+    pub id: String,
+    #[serde(default = "default_job_enabled")]
+    pub enabled: bool,
+    /// cron 表达式
+    pub schedule: String,
+    #[serde(default)]
+    pub missed_run: MissedRunPolicy,
+    #[serde(flatten)]
+    pub job: JobKind,
+}
+
+fn default_job_enabled() -> bool {
+    true
+}
+
+impl ScheduledJob {
+    /// 默认任务：每种内置任务各一个，均未启用
+    pub fn defaults() -> Vec<Self> {
+        [
+            ("refresh-metrics", "*/15 * * * *", JobKind::RefreshMetrics),
+            (
+                "check-token-health",
+                "0 */6 * * *",
+                JobKind::CheckTokenHealth,
+            ),
+            ("backup-current", "@hourly", JobKind::BackupCurrent),
+            (
+                "trigger-quota-refresh",
+                "0 */5 * * *",
+                JobKind::TriggerQuotaRefresh,
+            ),
+            (
+                "prune-snapshots",
+                "30 3 * * *",
+                JobKind::PruneSnapshots { keep_days: 7 },
+            ),
+            (
+                "align-quota-windows",
+                "*/5 * * * *",
+                JobKind::AlignQuotaWindows,
+            ),
+        ]
+        .into_iter()
+        .map(|(id, schedule, job)| Self {
+            id: id.to_string(),
+            enabled: false,
+            schedule: schedule.to_string(),
+            missed_run: MissedRunPolicy::Skip,
+            job,
+        })
+        .collect()
+    }
+}
+
+/// 校验任务列表：ID 唯一且非空，cron 表达式有效并且会触发
+pub fn validate_jobs(jobs: &[ScheduledJob]) -> Result<(), String> {
+    let mut ids = HashSet::new();
+    for job in jobs {
+        if job.id.trim().is_empty() {
+            return Err("任务 ID 不能为空".to_string());
+        }
+        if !ids.insert(job.id.as_str()) {
+            return Err(format!("任务 ID 重复: {}", job.id));
+        }
+        let schedule = CronSchedule::parse(&job.schedule)?;
+        if schedule.next_after(Local::now().naive_local()).is_none() {
+            return Err(format!("cron 表达式永远不会触发: {}", job.schedule));
+        }
+    }
+    Ok(())
+}
+
+/// 触发方式
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunTrigger {
+    /// 按计划时间执行
+    Scheduled,
+    /// 错过计划时间后补跑
+    CatchUp,
+    /// 手动执行
+    Manual,
+}
+
+/// 运行结果
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Succeeded,
+    Failed,
+    /// 错过计划时间且策略为跳过
+    Skipped,
+}
+
+/// 一条运行记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunRecord {
+    pub job_id: String,
+    pub trigger: RunTrigger,
+    pub status: RunStatus,
+    /// 开始时间（RFC 3339）
+    pub started_at: String,
+    pub duration_ms: u64,
+    pub message: String,
+}
+
+/// 持久化的调度状态
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct SchedulerState {
+    /// 各任务上次处理到的时间
+    last_handled: HashMap<String, DateTime<Local>>,
+    /// 运行记录（最新的在后）
+    history: VecDeque<RunRecord>,
+}
+
+/// 读取调度状态，文件损坏时保留副本并从空状态开始
+fn load_state(path: &Path) -> SchedulerState {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return SchedulerState::default(),
+        Err(e) => {
+            tracing::warn!(target: "scheduler", error = %e, "读取调度状态失败");
+            return SchedulerState::default();
+        }
+    };
+    match serde_json::from_str(&content) {
+        Ok(state) => state,
+        Err(e) => {
+            let backup = crate::app_settings::quarantine(path);
+            tracing::error!(
+                target: "scheduler",
+                error = %e,
+                backup = ?backup,
+                "调度状态文件已损坏，上次执行时间和运行记录已重置"
+            );
+            SchedulerState::default()
+        }
+    }
+}
+
+/// 调度器运行时状态
+struct Scheduler {
+    path: PathBuf,
+    state: Mutex<SchedulerState>,
+    /// 正在执行的任务
+    running: Mutex<HashSet<String>>,
+}
+
+impl Scheduler {
+    fn load(path: PathBuf) -> Self {
+        let state = load_state(&path);
+        Self {
+            path,
+            state: Mutex::new(state),
+            running: Mutex::new(HashSet::new()),
+        }
+    }
+
+    fn save(&self) {
+        // 持有锁直到写完，避免并发保存时旧状态覆盖新状态
+        let state = self.state.lock();
+        let json = match serde_json::to_string_pretty(&*state) {
+            Ok(json) => json,
+            Err(e) => {
+                tracing::warn!(target: "scheduler", error = %e, "序列化调度状态失败");
+                return;
+            }
+        };
+        if let Err(e) = crate::app_settings::write_atomic(&self.path, json.as_bytes()) {
+            tracing::warn!(target: "scheduler", error = %e, "写入调度状态失败");
+        }
+    }
+
+    fn record(&self, record: RunRecord) {
+        {
+            let mut state = self.state.lock();
+            state.history.push_back(record);
+            while state.history.len() > MAX_HISTORY {
+                state.history.pop_front();
+            }
+        }
+        self.save();
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref SCHEDULER: Scheduler =
+        Scheduler::load(crate::directories::get_scheduler_state_file());
+}
+
+/// 本次检查时任务的处理方式
+#[derive(Debug, PartialEq)]
+enum Decision {
+    Wait,
+    Run(RunTrigger),
+    Skip,
+}
+
+/// 根据上次处理时间判断任务是否到期
+fn decide(
+    schedule: &CronSchedule,
+    policy: MissedRunPolicy,
+    last_handled: NaiveDateTime,
+    now: NaiveDateTime,
+) -> Decision {
+    let Some(due) = schedule.next_after(last_handled) else {
+        return Decision::Wait;
+    };
+    if due > now {
+        return Decision::Wait;
+    }
+    let late = (now - due).to_std().unwrap_or_default();
+    match policy {
+        _ if late <= MISSED_GRACE => Decision::Run(RunTrigger::Scheduled),
+        MissedRunPolicy::RunOnce => Decision::Run(RunTrigger::CatchUp),
+        MissedRunPolicy::Skip => Decision::Skip,
+    }
+}
+
+/// 启动调度循环
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(TICK_INTERVAL);
+        loop {
+            interval.tick().await;
+            tick(&app);
+        }
+    });
+}
+
+/// 只保留已启用任务的处理时间，返回是否有变化
+///
+/// 任务（或调度器）重新启用后从启用时刻开始计算，不会把停用期间错过的执行当作补跑。
+fn forget_disabled(state: &mut SchedulerState, is_enabled: impl Fn(&str) -> bool) -> bool {
+    let before = state.last_handled.len();
+    state.last_handled.retain(|id, _| is_enabled(id));
+    state.last_handled.len() != before
+}
+
+/// 检查所有任务，执行到期的任务
+fn tick(app: &AppHandle) {
+    let settings = app.state::<AppSettingsManager>().get_settings().scheduler;
+    let enabled: HashSet<&str> = settings
+        .jobs
+        .iter()
+        .filter(|job| settings.enabled && job.enabled)
+        .map(|job| job.id.as_str())
+        .collect();
+    let forgotten = forget_disabled(&mut SCHEDULER.state.lock(), |id| enabled.contains(id));
+    if forgotten {
+        SCHEDULER.save();
+    }
+    if !settings.enabled {
+        return;
+    }
+
+    let now = Local::now();
+    for job in settings.jobs.into_iter().filter(|job| job.enabled) {
+        let schedule = match CronSchedule::parse(&job.schedule) {
+            Ok(schedule) => schedule,
+            Err(e) => {
+                tracing::warn!(target: "scheduler", job = %job.id, error = %e, "跳过无效的任务");
+                continue;
+            }
+        };
+
+        let last_handled = {
+            let mut state = SCHEDULER.state.lock();
+            *state.last_handled.entry(job.id.clone()).or_insert(now)
+        };
+        let decision = decide(
+            &schedule,
+            job.missed_run,
+            last_handled.naive_local(),
+            now.naive_local(),
+        );
+        if decision == Decision::Wait {
+            continue;
+        }
+
+        SCHEDULER
+            .state
+            .lock()
+            .last_handled
+            .insert(job.id.clone(), now);
+        match decision {
+            Decision::Run(trigger) => {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = run(&app, &job, trigger).await {
+                        tracing::warn!(target: "scheduler", job = %job.id, error = %e, "任务未执行");
+                    }
+                });
+            }
+            Decision::Skip => {
+                tracing::info!(target: "scheduler", job = %job.id, "错过计划时间，按策略跳过");
+                SCHEDULER.record(RunRecord {
+                    job_id: job.id,
+                    trigger: RunTrigger::Scheduled,
+                    status: RunStatus::Skipped,
+                    started_at: now.to_rfc3339(),
+                    duration_ms: 0,
+                    message: "错过计划时间，已跳过".to_string(),
+                });
+            }
+            Decision::Wait => {}
+        }
+    }
+    SCHEDULER.save();
+}
+
+/// 按 ID 查找任务配置
+pub fn find_job(app: &AppHandle, job_id: &str) -> Option<ScheduledJob> {
+    app.state::<AppSettingsManager>()
+        .get_settings()
+        .scheduler
+        .jobs
+        .into_iter()
+        .find(|job| job.id == job_id)
+}
+
+/// 立即执行任务（不影响计划时间，任务未启用时同样执行）
+pub async fn run_now(app: &AppHandle, job: &ScheduledJob) -> Result<RunRecord, String> {
+    run(app, job, RunTrigger::Manual).await
+}
+
+/// 执行任务并记录结果；同一任务正在执行时返回错误
+async fn run(
+    app: &AppHandle,
+    job: &ScheduledJob,
+    trigger: RunTrigger,
+) -> Result<RunRecord, String> {
+    if !SCHEDULER.running.lock().insert(job.id.clone()) {
+        return Err(format!("任务 {} 正在执行", job.id));
+    }
+
+    let started_at = Local::now();
+    let start_time = std::time::Instant::now();
+    tracing::info!(target: "scheduler", job = %job.id, trigger = ?trigger, "开始执行任务");
+    let result = execute(app, &job.job).await;
+    SCHEDULER.running.lock().remove(&job.id);
+
+    let (status, message) = match result {
+        Ok(message) => (RunStatus::Succeeded, message),
+        Err(e) => (RunStatus::Failed, e),
+    };
+    let record = RunRecord {
+        job_id: job.id.clone(),
+        trigger,
+        status,
+        started_at: started_at.to_rfc3339(),
+        duration_ms: start_time.elapsed().as_millis() as u64,
+        message,
+    };
+    tracing::info!(
+        target: "scheduler",
+        job = %job.id,
+        status = ?record.status,
+        duration_ms = record.duration_ms,
+        message = %record.message,
+        "任务执行完成"
+    );
+    SCHEDULER.record(record.clone());
+    Ok(record)
+}
+
+/// 执行内置任务
+async fn execute(app: &AppHandle, job: &JobKind) -> Result<String, String> {
+    let config_dir = {
+        let state = app.state::<crate::AppState>();
+        let inner = state.inner.lock();
+        inner.config_dir.clone()
+    };
+
+    match job {
+        JobKind::RefreshMetrics => {
+            let entries = super::metrics_cache::get_all(&config_dir, true).await?;
+            let failed = entries.iter().filter(|e| e.error.is_some()).count();
+            Ok(format!(
+                "已刷新 {} 个账户的配额，失败 {}",
+                entries.len() - failed,
+                failed
+            ))
+        }
+        JobKind::CheckTokenHealth => {
+            let results = super::account_health::check_all(&config_dir).await?;
+            let revoked = results
+                .iter()
+                .filter(|r| r.status == super::account_health::HealthStatus::Revoked)
+                .count();
+            Ok(format!(
+                "已检查 {} 个账户，{} 个已吊销",
+                results.len(),
+                revoked
+            ))
+        }
+        JobKind::BackupCurrent => super::account::backup_current().await,
+        JobKind::TriggerQuotaRefresh => {
+            let emails = super::account::list_account_emails(&config_dir)?;
+            let mut failed = Vec::new();
+            for email in &emails {
+                match super::account::trigger_quota_refresh(&config_dir, email.clone()).await {
+                    Ok(result) if result.success => {}
+                    Ok(result) => failed.push(format!("{}: {}", email, result.message)),
+                    Err(e) => failed.push(format!("{}: {}", email, e)),
+                }
+            }
+            if failed.is_empty() {
+                Ok(format!("已为 {} 个账户触发配额刷新", emails.len()))
+            } else {
+                Err(format!(
+                    "{} / {} 个账户触发失败：{}",
+                    failed.len(),
+                    emails.len(),
+                    failed.join("；")
+                ))
+            }
+        }
+        JobKind::PruneSnapshots { keep_days } => {
+            let removed = crate::antigravity::snapshot::prune_older_than(chrono::Duration::days(
+                i64::from(*keep_days),
+            ))?;
+            Ok(format!("已删除 {} 个过期快照", removed))
+        }
+        JobKind::AlignQuotaWindows => {
+            let settings = app
+                .state::<AppSettingsManager>()
+                .get_settings()
+                .quota_alignment;
+            let report = super::quota_alignment::run(&config_dir, &settings, false).await?;
+            if report.executed.iter().all(|trigger| trigger.success) {
+                Ok(report.summary())
+            } else {
+                Err(report.summary())
+            }
+        }
+    }
+}
+
+/// 任务的计划与最近一次运行情况
+#[derive(Debug, Clone)]
+pub struct JobStatus {
+    pub job: ScheduledJob,
+    /// 下一次计划时间（RFC 3339），任务未启用或表达式无效时为空
+    pub next_run: Option<String>,
+    pub last_run: Option<RunRecord>,
+    pub running: bool,
+}
+
+/// 所有任务的状态
+pub fn job_statuses(app: &AppHandle) -> Vec<JobStatus> {
+    let settings = app.state::<AppSettingsManager>().get_settings().scheduler;
+    let state = SCHEDULER.state.lock();
+    let running = SCHEDULER.running.lock();
+    let now = Local::now();
+
+    settings
+        .jobs
+        .into_iter()
+        .map(|job| {
+            let next_run = (settings.enabled && job.enabled)
+                .then(|| CronSchedule::parse(&job.schedule).ok())
+                .flatten()
+                .and_then(|schedule| {
+                    let after = state.last_handled.get(&job.id).copied().unwrap_or(now);
+                    schedule.next_after(after.naive_local())
+                })
+                .and_then(|next| next.and_local_timezone(Local).earliest())
+                .map(|next| next.to_rfc3339());
+            let last_run = state
+                .history
+                .iter()
+                .rev()
+                .find(|record| record.job_id == job.id)
+                .cloned();
+            JobStatus {
+                running: running.contains(&job.id),
+                next_run,
+                last_run,
+                job,
+            }
+        })
+        .collect()
+}
+
+/// 运行记录（最新的在前）
+pub fn history(limit: usize) -> Vec<RunRecord> {
+    SCHEDULER
+        .state
+        .lock()
+        .history
+        .iter()
+        .rev()
+        .take(limit)
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_decide() {
+        let schedule = CronSchedule::parse("0 * * * *").unwrap();
+        let decide_at = |policy, now| decide(&schedule, policy, at("2025-01-01 10:00"), at(now));
+
+        assert_eq!(
+            decide_at(MissedRunPolicy::Skip, "2025-01-01 10:59"),
+            Decision::Wait
+        );
+        assert_eq!(
+            decide_at(MissedRunPolicy::Skip, "2025-01-01 11:01"),
+            Decision::Run(RunTrigger::Scheduled)
+        );
+        // 应用未运行期间错过多次
+        assert_eq!(
+            decide_at(MissedRunPolicy::Skip, "2025-01-01 15:30"),
+            Decision::Skip
+        );
+        assert_eq!(
+            decide_at(MissedRunPolicy::RunOnce, "2025-01-01 15:30"),
+            Decision::Run(RunTrigger::CatchUp)
+        );
+    }
+
+    #[test]
+    fn test_forget_disabled() {
+        let mut state = SchedulerState::default();
+        state.last_handled.insert("a".to_string(), Local::now());
+        state.last_handled.insert("b".to_string(), Local::now());
+
+        assert!(!forget_disabled(&mut state, |_| true));
+        assert!(forget_disabled(&mut state, |id| id == "a"));
+        assert!(state.last_handled.contains_key("a"));
+        assert!(!state.last_handled.contains_key("b"));
+        assert!(forget_disabled(&mut state, |_| false));
+        assert!(state.last_handled.is_empty());
+    }
+
+    #[test]
+    fn test_job_config() {
+        let jobs = ScheduledJob::defaults();
+        assert!(validate_jobs(&jobs).is_ok());
+
+        let job: ScheduledJob = serde_json::from_str(
+            r#"{"id":"prune","schedule":"@daily","missed_run":"run_once","type":"prune_snapshots","keep_days":3}"#,
+        )
+        .unwrap();
+        assert!(job.enabled);
+        assert_eq!(job.missed_run, MissedRunPolicy::RunOnce);
+        assert_eq!(job.job, JobKind::PruneSnapshots { keep_days: 3 });
+
+        let mut duplicated = vec![job.clone(), job];
+        assert!(validate_jobs(&duplicated).is_err());
+        duplicated[1].id = "other".to_string();
+        duplicated[1].schedule = "0 0 31 2 *".to_string();
+        assert!(validate_jobs(&duplicated).is_err());
+    }
+
+    #[test]
+    fn test_load_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scheduler_state.json");
+        assert!(load_state(&path).history.is_empty());
+
+        let mut state = SchedulerState::default();
+        state
+            .last_handled
+            .insert("refresh_metrics".to_string(), Local::now());
+        let json = serde_json::to_string_pretty(&state).unwrap();
+        crate::app_settings::write_atomic(&path, json.as_bytes()).unwrap();
+        assert!(load_state(&path)
+            .last_handled
+            .contains_key("refresh_metrics"));
+
+        // 损坏的文件被保留，不会在下次保存时被悄悄覆盖
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(load_state(&path).last_handled.is_empty());
+        let backups = std::fs::read_dir(dir.path())
+            .unwrap()
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().contains(".corrupt-"))
+            .count();
+        assert_eq!(backups, 1);
+    }
+}
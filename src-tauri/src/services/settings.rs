@@ -1,58 +1,174 @@
+//! 应用设置的修改
+//!
+//! 所有修改都经过 [`update_with`]：基于当前设置计算并校验整份设置 → 写入设置文件并广播
+//! `settings_changed` 事件 → 执行已注册的钩子使变化生效（托盘、日志级别、网络等）。
+//! 校验失败时不写入设置；修改与钩子按顺序执行，钩子生效的顺序与保存顺序一致。
+
+use parking_lot::{Mutex, RwLock};
 use tauri::{AppHandle, Manager};
 
-/// 保存系统托盘状态
-pub async fn save_system_tray_state(app: &AppHandle, enabled: bool) -> Result<bool, String> {
-    let system_tray = app.state::<crate::system_tray::SystemTrayManager>();
+use crate::app_settings::{AppSettings, AppSettingsManager};
 
-    if enabled {
-        system_tray.enable(app)?;
-    } else {
-        system_tray.disable(app)?;
-    }
+/// 支持的界面语言
+pub const SUPPORTED_LANGUAGES: &[&str] = &["en", "zh-CN", "zh-TW"];
 
-    let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
-    settings_manager.update_settings(|settings| {
-        settings.system_tray_enabled = enabled;
-    })?;
+/// 设置变更钩子：参数为变更前后的设置，在写入设置文件之后执行
+pub type SettingsHook =
+    Box<dyn Fn(&AppHandle, &AppSettings, &AppSettings) -> Result<(), String> + Send + Sync>;
 
-    let settings = settings_manager.get_settings();
-    Ok(settings.system_tray_enabled)
+lazy_static::lazy_static! {
+    static ref HOOKS: RwLock<Vec<(&'static str, SettingsHook)>> = RwLock::new(Vec::new());
+    /// 串行执行“保存 + 钩子”，避免先保存的修改后生效
+    static ref APPLY_LOCK: Mutex<()> = Mutex::new(());
 }
 
-/// 保存静默启动状态
-pub async fn save_silent_start_state(app: &AppHandle, enabled: bool) -> Result<bool, String> {
-    let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+/// 注册设置变更钩子，按注册顺序执行
+pub fn register_hook<F>(name: &'static str, hook: F)
+where
+    F: Fn(&AppHandle, &AppSettings, &AppSettings) -> Result<(), String> + Send + Sync + 'static,
+{
+    HOOKS.write().push((name, Box::new(hook)));
+}
 
-    settings_manager.update_settings(|settings| {
-        settings.silent_start_enabled = enabled;
-    })?;
+/// 注册内置钩子：托盘启用 / 关闭、Debug 模式日志级别、网络设置
+pub fn register_default_hooks() {
+    register_hook("system_tray", |app, old, new| {
+        if old.system_tray_enabled == new.system_tray_enabled {
+            return Ok(());
+        }
+        let system_tray = app.state::<crate::system_tray::SystemTrayManager>();
+        if new.system_tray_enabled {
+            system_tray.enable(app)
+        } else {
+            system_tray.disable(app)
+        }
+    });
+    register_hook("debug_mode", |_, old, new| {
+        if old.debug_mode == new.debug_mode {
+            return Ok(());
+        }
+        crate::utils::tracing_config::set_debug_mode(new.debug_mode)
+    });
+    register_hook("network", |_, old, new| {
+        if old.network == new.network {
+            return Ok(());
+        }
+        apply_network_settings(&new.network)
+    });
+}
 
-    let settings = settings_manager.get_settings();
-    Ok(settings.silent_start_enabled)
+/// 依次执行已注册的钩子，使 `old` 到 `new` 的变化生效
+fn run_hooks(app: &AppHandle, old: &AppSettings, new: &AppSettings) -> Result<(), String> {
+    for (name, hook) in HOOKS.read().iter() {
+        hook(app, old, new).map_err(|e| {
+            tracing::error!(target: "settings::hooks", hook = name, error = %e, "设置变更钩子执行失败");
+            e
+        })?;
+    }
+    Ok(())
 }
 
-/// 保存隐私模式状态
-pub async fn save_private_mode_state(app: &AppHandle, enabled: bool) -> Result<bool, String> {
-    let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+/// 校验整份设置，返回规范化后的设置（例如去掉接口地址末尾的 `/`）
+pub fn validate(mut settings: AppSettings) -> Result<AppSettings, String> {
+    if !SUPPORTED_LANGUAGES.contains(&settings.language.as_str()) {
+        return Err(format!("Unsupported language: {}", settings.language));
+    }
 
-    settings_manager.update_settings(|settings| {
-        settings.private_mode = enabled;
-    })?;
+    crate::services::http_client::build_client(&settings.network)?;
+    settings.network.endpoints = settings.network.endpoints.normalized()?;
 
-    let settings = settings_manager.get_settings();
-    Ok(settings.private_mode)
+    if settings.server.port == 0 {
+        return Err("端口不能为 0".to_string());
+    }
+
+    for rule in &settings.notifications.rules {
+        rule.validate()?;
+    }
+    if settings.notifications.max_per_hour == 0 {
+        return Err("每小时通知数上限不能为 0".to_string());
+    }
+
+    crate::services::scheduler::validate_jobs(&settings.scheduler.jobs)?;
+    crate::services::quota_alignment::validate(&settings.quota_alignment)?;
+
+    Ok(settings)
 }
 
-/// 保存 Debug Mode 状态
-pub async fn save_debug_mode_state(app: &AppHandle, enabled: bool) -> Result<bool, String> {
-    let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+/// 修改后的设置
+#[derive(Debug)]
+pub struct Updated {
+    /// 已保存的设置
+    pub settings: AppSettings,
+    /// 设置已保存、但钩子未能使其立即生效时的原因
+    pub warning: Option<String>,
+}
 
-    settings_manager.update_settings(|settings| {
-        settings.debug_mode = enabled;
-    })?;
+/// [`patch`] 失败的原因
+#[derive(Debug)]
+pub enum PatchError {
+    /// 修改内容无效（不是对象、字段类型不符或未通过校验），设置未改变
+    Invalid(String),
+    /// 写入设置文件失败，设置未改变
+    Failed(String),
+}
 
-    let settings = settings_manager.get_settings();
-    Ok(settings.debug_mode)
+impl From<String> for PatchError {
+    fn from(message: String) -> Self {
+        Self::Failed(message)
+    }
+}
+
+impl std::fmt::Display for PatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Invalid(message) | Self::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+/// 修改设置：由 `compute` 根据当前设置计算新设置 → 校验 → 写入并广播 `settings_changed`
+/// → 执行钩子，返回修改后的设置
+///
+/// 读取当前设置与写入在设置管理器的同一把锁内完成，并发修改不会丢失。`compute` 的错误
+/// 原样返回；钩子失败时设置已保存，不返回错误，而是在 [`Updated::warning`] 中说明未能立即生效，
+/// 调用方不应重试。
+pub fn update_with<F, E>(app: &AppHandle, compute: F) -> Result<Updated, E>
+where
+    F: FnOnce(&AppSettings) -> Result<AppSettings, E>,
+    E: From<String>,
+{
+    let _apply = APPLY_LOCK.lock();
+    let settings_manager = app.state::<AppSettingsManager>();
+    let (old, new) = settings_manager
+        .try_update(|current| compute(current).and_then(|new| validate(new).map_err(E::from)))?;
+
+    let warning = run_hooks(app, &old, &new)
+        .err()
+        .map(|e| format!("设置已保存，但未能立即生效: {}", e));
+    Ok(Updated {
+        settings: new,
+        warning,
+    })
+}
+
+/// 设置文件被外部修改时重新加载，并执行钩子使其生效
+pub fn apply_external_changes(app: &AppHandle) {
+    let _apply = APPLY_LOCK.lock();
+    let settings_manager = app.state::<AppSettingsManager>();
+    let before = settings_manager.get_settings();
+    match settings_manager.reload_if_changed() {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(e) => {
+            tracing::warn!(target: "app_settings::reload", error = %e, "重新加载设置失败，继续使用当前设置");
+            return;
+        }
+    }
+
+    let after = settings_manager.get_settings();
+    if let Err(e) = run_hooks(app, &before, &after) {
+        tracing::error!(target: "app_settings::reload", error = %e, "重新加载的设置未能完全生效");
+    }
 }
 
 /// 获取所有应用设置
@@ -65,30 +181,88 @@ pub async fn get_all(app: &AppHandle) -> Result<serde_json::Value, String> {
         "silent_start_enabled": settings.silent_start_enabled,
         "debugMode": settings.debug_mode,
         "privateMode": settings.private_mode,
-        "language": settings.language
+        "language": settings.language,
+        "network": settings.network,
+        "server": settings.server,
+        "notifications": settings.notifications,
+        "scheduler": settings.scheduler,
+        "quotaAlignment": settings.quota_alignment
     }))
 }
 
-/// 获取语言偏好设置
-pub async fn get_language(app: &AppHandle) -> Result<String, String> {
+/// 修改任意设置：`patch` 为 [`AppSettings`] 的任意字段子集，对象逐字段合并，其他值整体替换
+pub fn patch(app: &AppHandle, patch: serde_json::Value) -> Result<Updated, PatchError> {
+    if !patch.is_object() {
+        return Err(PatchError::Invalid("请求体应为 JSON 对象".to_string()));
+    }
+
+    update_with(app, |current| {
+        let mut document =
+            serde_json::to_value(current).map_err(|e| format!("序列化设置失败: {}", e))?;
+        crate::app_settings::merge_json(&mut document, patch);
+        let patched: AppSettings = serde_json::from_value(document)
+            .map_err(|e| PatchError::Invalid(format!("设置无效: {}", e)))?;
+        let patched = validate(patched).map_err(PatchError::Invalid)?;
+        Ok(AppSettings {
+            schema_version: current.schema_version,
+            ..patched
+        })
+    })
+}
+
+/// 导出应用设置（用于导出 bundle）
+pub fn export_settings(app: &AppHandle) -> Result<serde_json::Value, String> {
     let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
-    let settings = settings_manager.get_settings();
-    Ok(settings.language.clone())
+    serde_json::to_value(settings_manager.get_settings())
+        .map_err(|e| format!("序列化设置失败: {}", e))
 }
 
-/// 保存语言偏好设置
-pub async fn set_language(app: &AppHandle, language: String) -> Result<(), String> {
-    // Validate language code
-    let valid_languages = vec!["en", "zh-CN", "zh-TW"];
-    if !valid_languages.contains(&language.as_str()) {
-        return Err(format!("Unsupported language: {}", language));
-    }
+/// 导入 bundle 时应用的设置项（界面偏好）
+///
+/// 网络（代理）、本地服务端口、定时任务、配额对齐等与本机环境相关的设置不随导入改变。
+const IMPORTED_SETTINGS: &[&str] = &[
+    "system_tray_enabled",
+    "silent_start_enabled",
+    "private_mode",
+    "language",
+    "notifications",
+];
 
-    let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
-    settings_manager.update_settings(|settings| {
-        settings.language = language.clone();
-    })?;
+/// 应用从 bundle 导入的设置：只合并 [`IMPORTED_SETTINGS`] 中 bundle 含有的项
+pub async fn apply_imported_settings(
+    app: &AppHandle,
+    value: serde_json::Value,
+) -> Result<(), String> {
+    let serde_json::Value::Object(imported) = value else {
+        return Err("导入的设置格式无效".to_string());
+    };
 
-    tracing::info!("Language preference saved: {}", language);
+    let updated = update_with(app, |current| {
+        let mut merged =
+            serde_json::to_value(current).map_err(|e| format!("序列化设置失败: {}", e))?;
+        for key in IMPORTED_SETTINGS {
+            if let Some(value) = imported.get(*key) {
+                merged[*key] = value.clone();
+            }
+        }
+        serde_json::from_value(merged).map_err(|e| format!("解析导入的设置失败: {}", e))
+    })?;
+    // 导入流程只关心设置是否已保存，未能立即生效时记录日志
+    if let Some(warning) = updated.warning {
+        tracing::warn!(target: "settings::import", warning = %warning, "导入的设置未能立即生效");
+    }
     Ok(())
 }
+
+/// 使网络设置生效：重建共享 HTTP 客户端并更新 Google 接口地址
+pub fn apply_network_settings(network: &crate::app_settings::NetworkSettings) -> Result<(), String> {
+    crate::services::http_client::configure(network)?;
+    crate::services::google_api::configure_endpoints(&network.endpoints)
+}
+
+/// 获取语言偏好设置
+pub async fn get_language(app: &AppHandle) -> Result<String, String> {
+    let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+    let settings = settings_manager.get_settings();
+    Ok(settings.language.clone())
+}
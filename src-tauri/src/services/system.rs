@@ -3,14 +3,15 @@ use std::sync::Arc;
 
 pub mod tray {
     use super::*;
-    use crate::system_tray::{update_tray_menu, SystemTrayManager, TrayMenuLabels};
+    use crate::system_tray::{refresh_tray_menu, set_menu_labels, SystemTrayManager, TrayMenuLabels};
 
+    /// 更新菜单文案并按当前账户数据重建菜单
     pub async fn update_menu(
         app: &AppHandle,
-        accounts: Vec<String>,
         labels: Option<TrayMenuLabels>,
     ) -> Result<String, String> {
-        update_tray_menu(app, accounts, labels)?;
+        set_menu_labels(labels);
+        refresh_tray_menu(app).await?;
         Ok("托盘菜单已更新".to_string())
     }
 
@@ -172,18 +173,21 @@ pub mod logging {
 pub mod extension {
     use crate::platform::antigravity::find_antigravity_installations;
     use futures_util::StreamExt;
-    use reqwest::Client;
     use std::io::Write;
     use std::process::Command;
+    use std::time::Duration;
     use tempfile::Builder;
 
+    const VSIX_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
     pub async fn launch_and_install(url: String) -> Result<String, String> {
         tracing::info!("🚀 开始下载插件: {}", url);
 
         // 1. 下载 VSIX 到临时文件
-        let client = Client::new();
-        let res = client
+        let res = crate::services::http_client::client()
             .get(&url)
+            // 插件体积较大，不使用默认的请求总超时
+            .timeout(VSIX_DOWNLOAD_TIMEOUT)
             .send()
             .await
             .map_err(|e| format!("请求失败: {}", e))?;
@@ -1,4 +1,4 @@
-use crate::{app_settings, db_monitor, system_tray, window};
+use crate::{app_settings, db_monitor, events, services, system_tray, window};
 use std::sync::Arc;
 use tauri::{App, Manager};
 
@@ -7,7 +7,20 @@ pub fn init(app: &mut App) -> std::result::Result<(), Box<dyn std::error::Error>
 
     // 初始化应用设置管理器
     let app_handle = app.handle();
-    app.manage(app_settings::AppSettingsManager::new(app_handle));
+
+    // 初始化事件总线（需早于设置管理器，设置变更会发布事件）
+    events::init(app_handle.clone());
+    let settings_manager = app_settings::AppSettingsManager::new(app_handle);
+
+    // 按网络设置配置共享 HTTP 客户端和接口地址，失败时继续使用默认配置
+    if let Err(e) = services::settings::apply_network_settings(&settings_manager.get_settings().network) {
+        tracing::error!(target: "app::setup::network", error = %e, "网络设置无效，使用默认配置");
+    }
+    app.manage(settings_manager);
+    // 设置变更时使托盘、日志级别、网络设置立即生效
+    services::settings::register_default_hooks();
+    // 设置文件被外部修改时自动重新加载
+    app_settings::watch_external_changes(app_handle.clone());
 
     // 初始化系统托盘管理器
     app.manage(system_tray::SystemTrayManager::new());
@@ -24,7 +37,7 @@ pub fn init(app: &mut App) -> std::result::Result<(), Box<dyn std::error::Error>
     }
 
     // 初始化数据库监控器
-    let db_monitor = Arc::new(db_monitor::DatabaseMonitor::new(app.handle().clone()));
+    let db_monitor = Arc::new(db_monitor::DatabaseMonitor::new());
     app.manage(db_monitor.clone());
 
     // 数据库监控将在前端通过命令启动，避免在 setup 中使用 tokio::spawn
@@ -55,6 +68,12 @@ pub fn init(app: &mut App) -> std::result::Result<(), Box<dyn std::error::Error>
     } else {
         tracing::info!(target: "app::setup::tray", "系统托盘已禁用，跳过创建");
     }
+    // 账户或配额变化时自动重建托盘菜单（托盘未启用时跳过）
+    system_tray::watch_app_events(app.handle().clone());
+    // 按通知规则发送桌面通知
+    services::notification::watch_app_events(app.handle().clone());
+    // 启动定时任务调度
+    services::scheduler::start(app.handle().clone());
 
     // 双重检查：如果静默启动但未启用系统托盘，这是不允许的
     if settings.silent_start_enabled && !settings.system_tray_enabled {
@@ -1,18 +1,46 @@
 //! 系统托盘模块
 //!
 //! 使用 Tauri 2.9 内置的 tray API 实现后端控制托盘
+//!
+//! 菜单由后端根据已保存的账户、当前登录账户和缓存的配额构建，不依赖前端：
+//! 托盘创建时立即构建一次，之后在账户切换 / 保存、配额更新、设置变更时自动重建
+//! （见 [`watch_app_events`]）。前端只负责推送多语言文案（见 [`set_menu_labels`]）。
+//!
+//! 点击账户直接在后端执行切换，不经过前端，窗口从未打开（静默启动）时同样可用。
 
 use crate::app_settings::AppSettingsManager;
+use crate::events::AppEvent;
+use crate::services::metrics_cache::METRICS_CACHE;
+use crate::services::notification;
+use parking_lot::RwLock;
 use serde::Deserialize;
-use std::time::{SystemTime, UNIX_EPOCH};
-use tauri::menu::{Menu, MenuBuilder, MenuItem};
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::menu::{CheckMenuItem, Menu, MenuBuilder, MenuItem, SubmenuBuilder};
 use tauri::tray::{TrayIcon, TrayIconBuilder};
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+
+/// 托盘提示的默认文本
+const APP_NAME: &str = "Antigravity Agent";
+
+/// 托盘发起的账户切换是否正在进行
+static SWITCHING: AtomicBool = AtomicBool::new(false);
 
-#[derive(Debug, Deserialize, Clone)]
+/// 合并短时间内连续到达的事件（批量拉取配额时每个账户各发布一次）
+const REBUILD_DEBOUNCE: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(default)]
 pub struct TrayMenuLabels {
     pub show_main: String,
     pub quit: String,
+    pub save_current: String,
+    pub sign_in_new: String,
+    pub refresh_quotas: String,
+    /// 没有订阅信息的账户所在分组
+    pub other_accounts: String,
 }
 
 impl Default for TrayMenuLabels {
@@ -20,18 +48,166 @@ impl Default for TrayMenuLabels {
         Self {
             show_main: "Show Main Window".to_string(),
             quit: "Quit".to_string(),
+            save_current: "Save Current Account".to_string(),
+            sign_in_new: "Sign In New Account".to_string(),
+            refresh_quotas: "Refresh Quotas".to_string(),
+            other_accounts: "Other Accounts".to_string(),
+        }
+    }
+}
+
+impl TrayMenuLabels {
+    /// 前端尚未推送文案时，按语言设置使用内置文案
+    fn for_language(language: &str) -> Self {
+        let labels = |texts: [&str; 6]| Self {
+            show_main: texts[0].to_string(),
+            quit: texts[1].to_string(),
+            save_current: texts[2].to_string(),
+            sign_in_new: texts[3].to_string(),
+            refresh_quotas: texts[4].to_string(),
+            other_accounts: texts[5].to_string(),
+        };
+        match language {
+            "zh-TW" => labels([
+                "顯示主視窗",
+                "退出應用",
+                "儲存目前帳戶",
+                "登入新帳戶",
+                "重新整理配額",
+                "其他帳戶",
+            ]),
+            lang if lang.starts_with("zh") => labels([
+                "显示主窗口",
+                "退出应用",
+                "保存当前账户",
+                "登录新账户",
+                "刷新配额",
+                "其他账户",
+            ]),
+            _ => Self::default(),
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    /// 前端推送的菜单文案
+    static ref MENU_LABELS: RwLock<Option<TrayMenuLabels>> = RwLock::new(None);
+    /// 串行化菜单重建，避免较早读取的数据覆盖较新的菜单
+    static ref REBUILD_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
+
+/// 菜单中的一个账户
+#[derive(Debug, Clone, PartialEq)]
+struct TrayAccount {
+    email: String,
+    /// 订阅计划名称，用于分组
+    group: Option<String>,
+    /// 各模型中最低的剩余配额（0..1），没有缓存的指标时为 `None`
+    remaining: Option<f64>,
+    current: bool,
+}
+
+impl TrayAccount {
+    fn label(&self) -> String {
+        let email = mask_email(&self.email);
+        match self.remaining {
+            Some(remaining) => format!("{}  {:.0}%", email, remaining * 100.0),
+            None => email,
         }
     }
 }
 
+/// 从解码后的账户数据中读取订阅计划名称
+fn account_group(account: &Value) -> Option<String> {
+    [
+        "/subscription/display_name",
+        "/subscription/tier_name",
+        "/context/plan/display_name",
+        "/context/plan_name",
+    ]
+    .iter()
+    .find_map(|pointer| {
+        account
+            .pointer(pointer)
+            .and_then(Value::as_str)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+    })
+}
+
+/// 按订阅计划分组，保持账户原有顺序；没有订阅信息的账户排在最后
+fn group_accounts(accounts: Vec<TrayAccount>) -> Vec<(Option<String>, Vec<TrayAccount>)> {
+    let mut groups: Vec<(Option<String>, Vec<TrayAccount>)> = Vec::new();
+    for account in accounts {
+        match groups.iter_mut().find(|(group, _)| *group == account.group) {
+            Some((_, members)) => members.push(account),
+            None => groups.push((account.group.clone(), vec![account])),
+        }
+    }
+    groups.sort_by_key(|(group, _)| group.is_none());
+    groups
+}
+
+/// 读取构建菜单所需的账户信息
+async fn load_accounts(app: &AppHandle) -> Vec<TrayAccount> {
+    let config_dir = {
+        let state = app.state::<crate::AppState>();
+        let inner = state.inner.lock();
+        inner.config_dir.clone()
+    };
+
+    let accounts = crate::services::account::get_all(&config_dir)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!(target: "app::tray", error = %e, "读取账户列表失败");
+            Vec::new()
+        });
+    // Antigravity 未登录时没有当前账户
+    let current_email = crate::services::account::get_current()
+        .await
+        .ok()
+        .and_then(|current| {
+            current
+                .pointer("/context/email")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        });
+
+    // 同一邮箱可能有多个账户文件（导入时 rename 另存），菜单中只显示一次
+    let mut seen = std::collections::HashSet::new();
+    accounts
+        .iter()
+        .filter_map(|account| {
+            let email = account.pointer("/context/email")?.as_str()?.to_string();
+            if !seen.insert(email.clone()) {
+                return None;
+            }
+            let remaining = METRICS_CACHE.latest_metrics(&email).and_then(|metrics| {
+                metrics
+                    .quotas
+                    .iter()
+                    .map(|quota| quota.percentage)
+                    .reduce(f64::min)
+            });
+            Some(TrayAccount {
+                current: current_email.as_deref() == Some(email.as_str()),
+                group: account_group(account),
+                remaining,
+                email,
+            })
+        })
+        .collect()
+}
+
 /// 创建系统托盘（返回托盘实例）
 pub fn create_tray_with_return(app: &AppHandle) -> Result<TrayIcon, String> {
-    // 创建基础菜单（账户列表将由前端动态更新）
+    // 先用基础菜单创建托盘，账户列表读取完成后再替换
     let menu = create_basic_menu(app)?;
 
     // 构建托盘图标
     let tray = TrayIconBuilder::with_id("main")
         .menu(&menu)
+        .tooltip(APP_NAME)
         .on_menu_event(handle_tray_menu_event)
         .show_menu_on_left_click(true)
         .build(app)
@@ -43,19 +219,27 @@ pub fn create_tray_with_return(app: &AppHandle) -> Result<TrayIcon, String> {
             .map_err(|e| format!("设置托盘图标失败: {e}"))?;
     }
 
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = refresh_tray_menu(&app_handle).await {
+            tracing::warn!(target: "app::tray", error = %e, "构建托盘菜单失败");
+        }
+    });
+
     Ok(tray)
 }
 
 /// 创建基础菜单（不含账户列表）
 fn create_basic_menu(app: &AppHandle) -> Result<Menu<tauri::Wry>, String> {
+    let labels = current_labels(app);
     MenuBuilder::new(app)
         .item(
-            &MenuItem::with_id(app, "show_main", "显示主窗口", true, None::<&str>)
+            &MenuItem::with_id(app, "show_main", &labels.show_main, true, None::<&str>)
                 .map_err(|e| format!("创建显示主窗口菜单失败: {e}"))?,
         )
         .separator()
         .item(
-            &MenuItem::with_id(app, "quit", "退出应用", true, None::<&str>)
+            &MenuItem::with_id(app, "quit", &labels.quit, true, None::<&str>)
                 .map_err(|e| format!("创建退出菜单失败: {e}"))?,
         )
         .build()
@@ -63,36 +247,48 @@ fn create_basic_menu(app: &AppHandle) -> Result<Menu<tauri::Wry>, String> {
 }
 
 /// 处理托盘菜单事件
+///
+/// 菜单 ID 格式为 `{action}#{nonce}`，账户菜单为 `account#{nonce}#{email}`。
 fn handle_tray_menu_event(app: &AppHandle, event: tauri::menu::MenuEvent) {
     tracing::info!("处理托盘菜单事件: {}", event.id.0);
 
-    match event.id.0.as_str() {
-        id if id.starts_with("show_main") => {
+    let id = event.id.0.as_str();
+    match id.split('#').next().unwrap_or_default() {
+        "show_main" => {
             if let Some(window) = app.get_webview_window("main") {
                 let _ = window.unminimize();
                 let _ = window.show();
                 let _ = window.set_focus();
             }
         }
-        id if id.starts_with("quit") => {
+        "quit" => {
             tracing::info!("退出应用");
             app.exit(0);
         }
-        // 账户切换事件
-        account_id if account_id.starts_with("account#") => {
+        "save_current" => spawn_action(app, "保存当前账户", |_| {
+            crate::services::account::backup_current()
+        }),
+        "sign_in_new" => spawn_action(app, "登录新账户", |_| {
+            crate::services::account::sign_in_new()
+        }),
+        "refresh_quotas" => spawn_action(app, "刷新配额", |app| async move {
+            let config_dir = {
+                let state = app.state::<crate::AppState>();
+                let inner = state.inner.lock();
+                inner.config_dir.clone()
+            };
+            let entries = crate::services::metrics_cache::get_all(&config_dir, true).await?;
+            Ok(format!("已刷新 {} 个账户的配额", entries.len()))
+        }),
+        // 账户切换
+        "account" => {
             // ID Format: account#{nonce}#{email}
-            let parts: Vec<&str> = account_id.splitn(3, '#').collect();
+            let parts: Vec<&str> = id.splitn(3, '#').collect();
             if parts.len() < 3 {
-                tracing::warn!("无效的账户菜单ID: {}", account_id);
+                tracing::warn!("无效的账户菜单ID: {}", id);
                 return;
             }
-            let account_email = parts[2];
-            tracing::info!("请求切换到账户: {account_email}");
-
-            // 发射事件到前端
-            if let Err(e) = app.emit("tray-switch-account", account_email) {
-                tracing::error!("发射账户切换事件失败: {e}");
-            }
+            switch_account(app, parts[2].to_string());
         }
         _ => {
             tracing::warn!("未处理的菜单事件: {}", event.id.0);
@@ -100,90 +296,232 @@ fn handle_tray_menu_event(app: &AppHandle, event: tauri::menu::MenuEvent) {
     }
 }
 
-/// 更新托盘菜单（添加账户列表）
-pub fn update_tray_menu(
-    app: &AppHandle,
-    accounts: Vec<String>,
-    labels: Option<TrayMenuLabels>,
-) -> Result<(), String> {
-    // 检查托盘是否应该启用
-    let settings_manager = app.state::<AppSettingsManager>();
-    let settings = settings_manager.get_settings();
+/// 在后台切换账户
+///
+/// 切换期间托盘提示显示进度（Linux 不支持托盘提示，改为发送通知）、账户菜单项不可点击，
+/// 重复点击直接忽略；结果由 [`notification`] 按规则发送通知。
+fn switch_account(app: &AppHandle, email: String) {
+    if SWITCHING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        tracing::warn!(target: "app::tray", "已有账户切换正在进行，忽略切换到 {} 的请求", email);
+        return;
+    }
+    let guard = SwitchGuard;
+    tracing::info!(target: "app::tray", "请求切换到账户: {email}");
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let zh = is_zh(&app);
+        let masked = mask_email(&email);
+        let progress = if zh {
+            format!("正在切换到 {}…", masked)
+        } else {
+            format!("Switching to {}…", masked)
+        };
+        set_tooltip(&app, &progress);
+        // Linux 没有托盘提示，改用通知显示进度
+        #[cfg(target_os = "linux")]
+        notification::notify(APP_NAME, &progress);
+        if let Err(e) = refresh_tray_menu(&app).await {
+            tracing::warn!(target: "app::tray", error = %e, "重建托盘菜单失败");
+        }
+
+        let result = crate::services::account::switch(email).await;
+        drop(guard);
+        set_tooltip(&app, APP_NAME);
+
+        // 结果通知由通知规则（切换成功 / 失败）发送
+        match result {
+            Ok(message) => tracing::info!(target: "app::tray", "托盘切换账户完成: {}", message),
+            Err(e) => tracing::error!(target: "app::tray", error = %e, "托盘切换账户失败"),
+        }
+        if let Err(e) = refresh_tray_menu(&app).await {
+            tracing::warn!(target: "app::tray", error = %e, "重建托盘菜单失败");
+        }
+    });
+}
+
+/// 切换结束（包括任务异常退出）时清除进行中标记
+struct SwitchGuard;
+
+impl Drop for SwitchGuard {
+    fn drop(&mut self) {
+        SWITCHING.store(false, Ordering::SeqCst);
+    }
+}
+
+fn is_zh(app: &AppHandle) -> bool {
+    app.state::<AppSettingsManager>()
+        .get_settings()
+        .language
+        .starts_with("zh")
+}
+
+fn set_tooltip(app: &AppHandle, text: &str) {
+    if let Some(tray) = app.tray_by_id("main") {
+        if let Err(e) = tray.set_tooltip(Some(text)) {
+            tracing::debug!(target: "app::tray", error = %e, "设置托盘提示失败");
+        }
+    }
+}
 
+/// 在后台执行菜单操作，完成后重建菜单
+fn spawn_action<F, Fut>(app: &AppHandle, name: &'static str, action: F)
+where
+    F: FnOnce(AppHandle) -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = Result<String, String>> + Send + 'static,
+{
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match action(app.clone()).await {
+            Ok(message) => tracing::info!(target: "app::tray", "{}完成: {}", name, message),
+            Err(e) => tracing::error!(target: "app::tray", error = %e, "{}失败", name),
+        }
+        if let Err(e) = refresh_tray_menu(&app).await {
+            tracing::warn!(target: "app::tray", error = %e, "重建托盘菜单失败");
+        }
+    });
+}
+
+/// 保存前端推送的菜单文案
+pub fn set_menu_labels(labels: Option<TrayMenuLabels>) {
+    if let Some(labels) = labels {
+        *MENU_LABELS.write() = Some(labels);
+    }
+}
+
+/// 当前使用的菜单文案
+fn current_labels(app: &AppHandle) -> TrayMenuLabels {
+    MENU_LABELS.read().clone().unwrap_or_else(|| {
+        let language = app.state::<AppSettingsManager>().get_settings().language;
+        TrayMenuLabels::for_language(&language)
+    })
+}
+
+/// 根据账户数据重建托盘菜单（托盘未启用时跳过）
+pub async fn refresh_tray_menu(app: &AppHandle) -> Result<(), String> {
+    let _guard = REBUILD_LOCK.lock().await;
+
+    let settings = app.state::<AppSettingsManager>().get_settings();
     if !settings.system_tray_enabled {
-        tracing::info!("托盘已禁用，跳过菜单更新");
+        tracing::debug!(target: "app::tray", "托盘已禁用，跳过菜单更新");
         return Ok(());
     }
-
     let Some(tray) = app.tray_by_id("main") else {
         return Err("未找到系统托盘".to_string());
     };
 
-    // 使用默认或传入的标签
-    let menu_labels = labels.unwrap_or_default();
+    let accounts = load_accounts(app).await;
+    let account_count = accounts.len();
+    let menu = build_menu(app, accounts, &current_labels(app))?;
+    tray.set_menu(Some(menu))
+        .map_err(|e| format!("设置托盘菜单失败: {e}"))?;
 
-    // 创建包含账户列表的完整菜单
-    let mut menu_builder = MenuBuilder::new(app);
+    tracing::info!(target: "app::tray", "✅ 托盘菜单已更新，包含 {} 个账户", account_count);
+    Ok(())
+}
 
+/// 构建完整菜单
+fn build_menu(
+    app: &AppHandle,
+    accounts: Vec<TrayAccount>,
+    labels: &TrayMenuLabels,
+) -> Result<Menu<tauri::Wry>, String> {
     // Generate unique nonce for this update to avoid ID collisions
     let nonce = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_nanos();
-
-    // 显示主窗口
-    menu_builder = menu_builder.item(
-        &MenuItem::with_id(
+    let item = |action: &str, text: &str| {
+        MenuItem::with_id(
             app,
-            format!("show_main#{}", nonce),
-            &menu_labels.show_main,
+            format!("{}#{}", action, nonce),
+            text,
             true,
             None::<&str>,
         )
-        .map_err(|e| format!("创建显示主窗口菜单失败: {e}"))?,
-    );
+        .map_err(|e| format!("创建菜单项 {} 失败: {e}", action))
+    };
+    let account_item = |account: &TrayAccount| {
+        CheckMenuItem::with_id(
+            app,
+            format!("account#{}#{}", nonce, account.email),
+            account.label(),
+            !SWITCHING.load(Ordering::SeqCst),
+            account.current,
+            None::<&str>,
+        )
+        .map_err(|e| format!("创建账户菜单失败: {e}"))
+    };
+
+    let mut menu_builder = MenuBuilder::new(app).item(&item("show_main", &labels.show_main)?);
 
-    // 添加账户列表
+    // 账户列表：只有一种订阅计划时直接平铺，否则按计划分到子菜单
     if !accounts.is_empty() {
         menu_builder = menu_builder.separator();
-
-        for account in &accounts {
-            let masked_email = mask_email(account);
-            menu_builder = menu_builder.item(
-                &MenuItem::with_id(
-                    app,
-                    format!("account#{}#{}", nonce, account),
-                    &masked_email,
-                    true,
-                    None::<&str>,
-                )
-                .map_err(|e| format!("创建账户菜单失败: {e}"))?,
-            );
+        let groups = group_accounts(accounts);
+        if groups.len() == 1 {
+            for account in &groups[0].1 {
+                menu_builder = menu_builder.item(&account_item(account)?);
+            }
+        } else {
+            for (index, (group, members)) in groups.iter().enumerate() {
+                let title = group.as_deref().unwrap_or(&labels.other_accounts);
+                let mut submenu =
+                    SubmenuBuilder::with_id(app, format!("group#{}#{}", nonce, index), title);
+                for account in members {
+                    submenu = submenu.item(&account_item(account)?);
+                }
+                menu_builder = menu_builder.item(
+                    &submenu
+                        .build()
+                        .map_err(|e| format!("创建分组菜单失败: {e}"))?,
+                );
+            }
         }
     }
 
-    // 退出应用
-    menu_builder = menu_builder.separator().item(
-        &MenuItem::with_id(
-            app,
-            format!("quit#{}", nonce),
-            &menu_labels.quit,
-            true,
-            None::<&str>,
-        )
-        .map_err(|e| format!("创建退出菜单失败: {e}"))?,
-    );
-
-    // 构建并设置新菜单
-    let new_menu = menu_builder
+    menu_builder
+        .separator()
+        .item(&item("save_current", &labels.save_current)?)
+        .item(&item("sign_in_new", &labels.sign_in_new)?)
+        .item(&item("refresh_quotas", &labels.refresh_quotas)?)
+        .separator()
+        .item(&item("quit", &labels.quit)?)
         .build()
-        .map_err(|e| format!("构建新菜单失败: {e}"))?;
+        .map_err(|e| format!("构建新菜单失败: {e}"))
+}
 
-    tray.set_menu(Some(new_menu))
-        .map_err(|e| format!("设置托盘菜单失败: {e}"))?;
+/// 订阅事件总线，账户或配额变化时自动重建菜单
+pub fn watch_app_events(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut receiver = crate::events::subscribe();
+        loop {
+            match receiver.recv().await {
+                Ok(
+                    AppEvent::AccountSwitched { .. }
+                    | AppEvent::AccountSaved { .. }
+                    | AppEvent::QuotaUpdated { .. }
+                    | AppEvent::SettingsChanged { .. },
+                )
+                | Err(RecvError::Lagged(_)) => {}
+                Ok(_) => continue,
+                Err(RecvError::Closed) => break,
+            }
 
-    tracing::info!("✅ 托盘菜单已更新，包含 {} 个账户", accounts.len());
-    Ok(())
+            tokio::time::sleep(REBUILD_DEBOUNCE).await;
+            while !matches!(
+                receiver.try_recv(),
+                Err(TryRecvError::Empty | TryRecvError::Closed)
+            ) {}
+
+            if let Err(e) = refresh_tray_menu(&app).await {
+                tracing::warn!(target: "app::tray", error = %e, "重建托盘菜单失败");
+            }
+        }
+    });
 }
 
 /// 邮箱打码函数
@@ -207,3 +545,66 @@ fn mask_email(email: &str) -> String {
         ),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(email: &str, group: Option<&str>) -> TrayAccount {
+        TrayAccount {
+            email: email.to_string(),
+            group: group.map(str::to_string),
+            remaining: None,
+            current: false,
+        }
+    }
+
+    #[test]
+    fn test_group_accounts() {
+        let groups = group_accounts(vec![
+            account("a@x.com", None),
+            account("b@x.com", Some("Pro")),
+            account("c@x.com", Some("Free")),
+            account("d@x.com", Some("Pro")),
+        ]);
+        let summary: Vec<(Option<&str>, Vec<&str>)> = groups
+            .iter()
+            .map(|(group, members)| {
+                (
+                    group.as_deref(),
+                    members.iter().map(|m| m.email.as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Some("Pro"), vec!["b@x.com", "d@x.com"]),
+                (Some("Free"), vec!["c@x.com"]),
+                (None, vec!["a@x.com"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_account_group_and_label() {
+        let value = serde_json::json!({
+            "context": { "email": "user@x.com", "plan_name": "Free" },
+            "subscription": { "display_name": "Google AI Pro", "tier_name": "" }
+        });
+        assert_eq!(account_group(&value).as_deref(), Some("Google AI Pro"));
+        assert_eq!(
+            account_group(&serde_json::json!({ "context": { "plan_name": "Free" } })).as_deref(),
+            Some("Free")
+        );
+        assert_eq!(
+            account_group(&serde_json::json!({ "subscription": null })),
+            None
+        );
+
+        let mut item = account("user@x.com", None);
+        assert_eq!(item.label(), "u***r@x.com");
+        item.remaining = Some(0.426);
+        assert_eq!(item.label(), "u***r@x.com  43%");
+    }
+}
@@ -1,6 +1,41 @@
 //! Tracing 配置模块
 //! 提供统一的结构化日志配置和初始化
 
+use std::sync::OnceLock;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+/// 运行时调整日志过滤器的句柄（设置了 `RUST_LOG` 时为空，以环境变量为准）
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// 默认日志过滤器：默认 info，降低 h2/hyper 噪音
+///
+/// Debug Mode 开启时：仅放开应用相关的 debug（以及 frontend），避免依赖库（如 reqwest）刷屏。
+pub fn default_filter(debug_mode: bool) -> &'static str {
+    if debug_mode {
+        "info,antigravity_agent=debug,frontend=debug,app=debug,window=debug,account=debug,restore=debug,cleanup=debug,backup=debug,h2=warn,hyper=warn"
+    } else {
+        "info,h2=warn,hyper=warn"
+    }
+}
+
+/// 保存日志过滤器句柄，之后可通过 [`set_debug_mode`] 调整日志级别
+pub fn set_filter_handle(handle: reload::Handle<EnvFilter, Registry>) {
+    let _ = FILTER_HANDLE.set(handle);
+}
+
+/// 按 Debug Mode 切换日志级别，立即生效
+pub fn set_debug_mode(enabled: bool) -> Result<(), String> {
+    let Some(handle) = FILTER_HANDLE.get() else {
+        tracing::info!(target: "app::logging", "日志级别由 RUST_LOG 决定，忽略 Debug Mode 变更");
+        return Ok(());
+    };
+    handle
+        .reload(EnvFilter::new(default_filter(enabled)))
+        .map_err(|e| format!("调整日志级别失败: {}", e))?;
+    tracing::info!(target: "app::logging", debug_mode = enabled, "日志级别已调整");
+    Ok(())
+}
+
 /// 记录系统启动信息
 pub fn log_system_info() {
     tracing::info!(
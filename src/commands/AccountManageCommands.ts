@@ -1,5 +1,10 @@
 import { universalInvoke } from '@/lib/invoke-adapter';
-import type { BackupData, RestoreResult } from './types/account-manage.types.ts';
+import type {
+  BackupData,
+  DecryptConfigResult,
+  ReencryptConfigResult,
+  RestoreResult,
+} from './types/account-manage.types.ts';
 
 /**
  * 账户与备份综合命令
@@ -22,14 +27,21 @@ export class AccountManageCommands {
   }
 
   // ==== 配置加解密 ====
-  static encryptConfig(jsonData: string, password: string): Promise<string> {
-    return universalInvoke('encrypt_config_data', { jsonData: jsonData, password });
+  static async encryptConfig(jsonData: string, password: string): Promise<string> {
+    const { result } = await universalInvoke<{ result: string }>('encrypt_config_data', { jsonData: jsonData, password });
+    return result;
   }
 
-  static decryptConfig(encryptedData: string, password: string): Promise<string> {
+  /** 自动识别当前格式与旧版 XOR 格式 */
+  static decryptConfig(encryptedData: string, password: string): Promise<DecryptConfigResult> {
     return universalInvoke('decrypt_config_data', { encryptedData: encryptedData, password });
   }
 
+  /** 将旧格式的加密数据转换为当前格式 */
+  static reencryptConfig(encryptedData: string, password: string): Promise<ReencryptConfigResult> {
+    return universalInvoke('reencrypt_config_data', { encryptedData: encryptedData, password });
+  }
+
   static signInNewAntigravityAccount(): Promise<string> {
     return universalInvoke('sign_in_new_antigravity_account');
   }
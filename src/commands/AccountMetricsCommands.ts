@@ -1,5 +1,5 @@
 import { universalInvoke } from '@/lib/invoke-adapter';
-import { AccountMetrics } from '@/commands/types/account.types.ts';
+import { AccountMetrics, AccountMetricsEntry } from '@/commands/types/account.types.ts';
 
 export class AccountMetricsCommands {
     /**
@@ -9,4 +9,11 @@ export class AccountMetricsCommands {
     static async getAccountMetrics(email: string): Promise<AccountMetrics> {
         return universalInvoke('get_account_metrics', { email });
     }
+
+    /**
+     * 批量获取所有账户的配额指标（后端缓存，单个账户失败时在条目中返回错误）
+     */
+    static async getAllAccountMetrics(): Promise<AccountMetricsEntry[]> {
+        return universalInvoke('get_all_account_metrics');
+    }
 }
@@ -1,27 +1,43 @@
 import { universalInvoke } from '@/lib/invoke-adapter';
-import type { AppSettings } from './types/settings.types';
+import type { AppSettings, NetworkSettings, SavedSettings } from './types/settings.types';
 
 /**
  * 设置管理命令
  */
 export class SettingsCommands {
 
+  /**
+   * 修改设置（PATCH /api/settings），只需传入要修改的字段
+   * @param patch 要修改的设置
+   * @returns 保存后的完整设置
+   */
+  private static async patch(patch: Partial<Omit<SavedSettings, 'warning'>>): Promise<SavedSettings> {
+    const saved = await universalInvoke<SavedSettings>('settings', patch);
+    if (saved.warning) {
+      // 设置已保存，只是未能立即生效，不应当作失败重试
+      console.warn('[SettingsCommands]', saved.warning);
+    }
+    return saved;
+  }
+
   /**
    * 保存系统托盘状态
    * @param enabled 是否启用
    * @returns 保存后的状态
    */
   static async saveSystemTrayState(enabled: boolean): Promise<boolean> {
-    return universalInvoke('save_system_tray_state', { enabled });
+    const saved = await SettingsCommands.patch({ system_tray_enabled: enabled });
+    return saved.system_tray_enabled;
   }
 
   /**
    * 保存静默启动状态
    * @param enabled 是否启用
-   * @returns 保存结果消息
+   * @returns 保存后的状态
    */
   static async saveSilentStartState(enabled: boolean): Promise<boolean> {
-    return universalInvoke('save_silent_start_state', { enabled });
+    const saved = await SettingsCommands.patch({ silent_start_enabled: enabled });
+    return saved.silent_start_enabled;
   }
 
   /**
@@ -30,7 +46,8 @@ export class SettingsCommands {
    * @returns 保存后的状态
    */
   static async savePrivateModeState(enabled: boolean): Promise<boolean> {
-    return universalInvoke('save_private_mode_state', { enabled });
+    const saved = await SettingsCommands.patch({ private_mode: enabled });
+    return saved.private_mode;
   }
 
   /**
@@ -39,7 +56,18 @@ export class SettingsCommands {
    * @returns 保存后的状态
    */
   static async saveDebugModeState(enabled: boolean): Promise<boolean> {
-    return universalInvoke('save_debug_mode_state', { enabled });
+    const saved = await SettingsCommands.patch({ debug_mode: enabled });
+    return saved.debug_mode;
+  }
+
+  /**
+   * 保存网络设置（代理、证书、超时），后端会先校验再生效
+   * @param network 网络设置
+   * @returns 保存后的网络设置
+   */
+  static async saveNetworkSettings(network: NetworkSettings): Promise<NetworkSettings> {
+    const saved = await SettingsCommands.patch({ network });
+    return saved.network;
   }
 
   /**
@@ -63,6 +91,6 @@ export class SettingsCommands {
    * @param language 语言代码 (en, zh-CN, zh-TW)
    */
   static async setLanguage(language: string): Promise<void> {
-    return universalInvoke('set_language', { language });
+    await SettingsCommands.patch({ language });
   }
 }
@@ -23,12 +23,11 @@ export class TrayCommands {
 
 
   /**
-   * 更新托盘菜单
-   * @param accounts 账户邮箱列表
+   * 更新托盘菜单文案并重建菜单（账户列表由后端读取）
    * @param labels 菜单标签（多语言）
    * @returns 更新结果消息
    */
-  static async updateMenu(accounts: string[], labels?: TrayMenuLabels): Promise<string> {
-    return universalInvoke('update_tray_menu_command', { accounts, labels });
+  static async updateMenu(labels?: TrayMenuLabels): Promise<string> {
+    return universalInvoke('update_tray_menu_command', { labels });
   }
 }
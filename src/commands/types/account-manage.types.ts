@@ -48,3 +48,36 @@ export interface RestoreResult {
   /** 失败的备份列表 */
   failed: FailedBackup[];
 }
+
+/**
+ * 加密数据格式
+ */
+export type EncryptedFormat = 'v2' | 'v1' | 'legacy_xor';
+
+/**
+ * 解密结果
+ */
+export interface DecryptConfigResult {
+  /** 解密后的 JSON 文本 */
+  result: string;
+
+  /** 识别出的加密格式 */
+  format: EncryptedFormat;
+
+  /** v2 头部中明文存储、参与认证的关联数据 */
+  associatedData: string;
+
+  /** 是否为旧格式、建议重新加密 */
+  needsReencrypt: boolean;
+}
+
+/**
+ * 重新加密结果
+ */
+export interface ReencryptConfigResult {
+  /** 当前格式的加密数据 */
+  result: string;
+
+  /** 原始加密格式 */
+  previousFormat: EncryptedFormat;
+}
@@ -72,3 +72,14 @@ export interface AccountMetrics {
   avatar_url: string;
   quotas: QuotaItem[];
 }
+
+// 对应 Rust 的 AccountMetricsEntry（批量指标接口的单个条目）
+export interface AccountMetricsEntry {
+  email: string;
+  metrics: AccountMetrics | null;
+  error: string | null;
+  /** 是否来自后端缓存 */
+  cached: boolean;
+  /** 指标拉取时间（RFC 3339） */
+  fetchedAt: string | null;
+}
@@ -20,4 +20,62 @@ export interface AppSettings {
 
   /** 语言偏好设置 */
   language: string;
+
+  /** 网络设置（代理、证书、超时） */
+  network: NetworkSettings;
+}
+
+/**
+ * 修改设置（PATCH /api/settings）后返回的完整设置
+ */
+export interface SavedSettings {
+  system_tray_enabled: boolean;
+  silent_start_enabled: boolean;
+  debug_mode: boolean;
+  private_mode: boolean;
+  language: string;
+  network: NetworkSettings;
+
+  /** 设置已保存、但未能立即生效时的原因 */
+  warning?: string;
+}
+
+/**
+ * 网络设置，所有对外请求共用
+ */
+export interface NetworkSettings {
+  /** 代理地址，支持 http://、https://、socks5://、socks5h:// */
+  proxy_url: string | null;
+
+  /** 不走代理的主机列表（逗号分隔） */
+  no_proxy: string | null;
+
+  /** 额外信任的根证书（PEM 文件路径） */
+  ca_bundle_path: string | null;
+
+  /** 连接超时（秒） */
+  connect_timeout_secs: number;
+
+  /** 请求总超时（秒） */
+  request_timeout_secs: number;
+
+  /** 自定义 User-Agent */
+  user_agent: string | null;
+
+  /** Google 接口地址 */
+  endpoints: ApiEndpoints;
+}
+
+/**
+ * Google 接口地址，默认指向官方服务
+ */
+export interface ApiEndpoints {
+  /** Cloud Code 服务根地址 */
+  cloud_code_base_url: string;
+
+  /** OAuth token 刷新地址 */
+  oauth_token_url: string;
+
+  /** 用户信息地址 */
+  userinfo_url: string;
 }
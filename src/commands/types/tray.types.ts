@@ -11,4 +11,20 @@ export interface TrayMenuLabels {
      * 退出应用文本
      */
     quit: string;
+    /**
+     * 保存当前账户文本
+     */
+    save_current: string;
+    /**
+     * 登录新账户文本
+     */
+    sign_in_new: string;
+    /**
+     * 刷新配额文本
+     */
+    refresh_quotas: string;
+    /**
+     * 未分组账户的分组名
+     */
+    other_accounts: string;
 }
@@ -70,18 +70,15 @@ export function AppContent() {
       clearInterval(fetchAccountAdditionDataTimer.current)
     }
 
-    const task = () => {
-      accounts.forEach(async (user) => {
-        try {
-          await accountAdditionData.update(user)
-        } catch (e) {
-          logger.error(t('notifications:fetchUserDataFailed'), {
-            module: 'AppContent',
-            email: user.context.email,
-            error: e instanceof Error ? e.message : String(e)
-          })
-        }
-      })
+    const task = async () => {
+      try {
+        await accountAdditionData.refreshAll()
+      } catch (e) {
+        logger.error(t('notifications:fetchUserDataFailed'), {
+          module: 'AppContent',
+          error: e instanceof Error ? e.message : String(e)
+        })
+      }
     }
 
     fetchAccountAdditionDataTimer.current = setInterval(() => {
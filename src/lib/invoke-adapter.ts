@@ -5,8 +5,41 @@
 // 假设 VS Code 扩展会注入一个特定的全局变量或者我们构建时设置 VITE_ENV
 const isExtension = !('__TAURI_INTERNALS__' in window) && !('__TAURI__' in window); // 简单的启发式检查
 
-// 本地服务器地址 (与 main.rs 中配置的一致)
-const SERVER_URL = 'http://127.0.0.1:56789/api';
+// 本地服务器默认地址；实际端口可能因配置或端口占用而不同
+const DEFAULT_SERVER_ORIGIN = 'http://127.0.0.1:56789';
+
+declare global {
+  interface Window {
+    // VS Code 扩展根据 ~/.antigravity-agent/server.json 注入的服务地址
+    ANTIGRAVITY_AGENT_URL?: string;
+  }
+}
+
+let serverUrlPromise: Promise<string> | null = null;
+
+/**
+ * 解析本地服务的 API 地址（只解析一次）
+ * 优先使用扩展注入的地址，Tauri 环境下向后端查询实际监听的端口，否则使用默认端口
+ */
+function getServerUrl(): Promise<string> {
+  if (!serverUrlPromise) {
+    serverUrlPromise = (async () => {
+      if (window.ANTIGRAVITY_AGENT_URL) {
+        return `${window.ANTIGRAVITY_AGENT_URL}/api`;
+      }
+      if (!isExtension) {
+        try {
+          const { invoke } = await import('@tauri-apps/api/core');
+          return `${await invoke<string>('get_server_url')}/api`;
+        } catch (error) {
+          console.warn('[InvokeAdapter] 获取本地服务地址失败，使用默认端口:', error);
+        }
+      }
+      return `${DEFAULT_SERVER_ORIGIN}/api`;
+    })();
+  }
+  return serverUrlPromise;
+}
 
 /**
  * 通用命令调用适配器
@@ -32,17 +65,18 @@ const POST_COMMANDS = new Set([
   'sign_in_new_antigravity_account',
   'trigger_quota_refresh',
   'restore_backup_files',
+  'import_accounts',
+  'export_bundle',
+  'import_bundle',
   'delete_backup',
   'clear_all_backups',
-  'save_system_tray_state',
-  'save_silent_start_state',
-  'save_private_mode_state',
-  'save_debug_mode_state',
-  'set_language',
   'validate_antigravity_executable',
   'save_antigravity_executable',
   'encrypt_config_data',
   'decrypt_config_data',
+  'reencrypt_config_data',
+  'encrypt_for_recipients',
+  'decrypt_with_local_key',
   'update_tray_menu_command',
   'minimize_to_tray',
   'restore_from_tray',
@@ -50,6 +84,11 @@ const POST_COMMANDS = new Set([
   'stop_database_monitoring',
 ]);
 
+// 需要 PATCH 方法的命令列表
+const PATCH_COMMANDS = new Set([
+  'settings',
+]);
+
 // 在 HTTP 模式下忽略的命令（返回 undefined）
 const IGNORED_COMMANDS = new Set<string>([
   // All commands are now supported via HTTP
@@ -71,8 +110,8 @@ async function httpInvoke<T>(cmd: string, args?: Record<string, any>): Promise<T
   }
 
   // 直接使用命令名作为路由
-  const url = `${SERVER_URL}/${cmd}`;
-  const method = POST_COMMANDS.has(cmd) ? 'POST' : 'GET';
+  const url = `${await getServerUrl()}/${cmd}`;
+  const method = PATCH_COMMANDS.has(cmd) ? 'PATCH' : POST_COMMANDS.has(cmd) ? 'POST' : 'GET';
 
   const options: RequestInit = {
     method,
@@ -81,8 +120,8 @@ async function httpInvoke<T>(cmd: string, args?: Record<string, any>): Promise<T
     },
   };
 
-  // POST 请求透传参数
-  if (method === 'POST' && args) {
+  // POST / PATCH 请求透传参数
+  if (method !== 'GET' && args) {
     options.body = JSON.stringify(args);
   }
 
@@ -86,7 +86,7 @@ export const useDbMonitoringStore = create<DbMonitoringActions>()(
           };
 
           // 监听后端推送的数据库变化事件
-          globalUnlistenFn = await listen('database-changed', handleDatabaseChange);
+          globalUnlistenFn = await listen('db_changed', handleDatabaseChange);
 
           // 启动后端监控
           await DbMonitorCommands.start();
@@ -1,5 +1,5 @@
 ﻿import { create } from "zustand";
-import { AntigravityAccount } from "@/commands/types/account.types.ts";
+import { AccountMetrics } from "@/commands/types/account.types.ts";
 import { AccountMetricsCommands } from "@/commands/AccountMetricsCommands.ts";
 import { logger } from "@/lib/logger";
 
@@ -8,7 +8,8 @@ type State = {
 }
 
 type Actions = {
-  update: (antigravityAccount: AntigravityAccount) => Promise<void>
+  /** 通过批量接口刷新所有账户的指标（后端带缓存，只发一次请求） */
+  refreshAll: () => Promise<void>
 }
 
 export type UserTier = 'free-tier' | 'g1-pro-tier' | 'g1-ultra-tier';
@@ -26,53 +27,57 @@ export interface AccountAdditionData {
   userId: string
 }
 
-export const useAccountAdditionData = create<State & Actions>((setState, getState) => ({
-  data: {},
-  update: async (antigravityAccount: AntigravityAccount) => {
-    const email = antigravityAccount.context.email;
-
-    try {
-      logger.debug(`开始获取账户指标 (Rust Singular): ${email}`);
+// 映射 Rust 数据结构 -> 前端 Store 结构
+// 注意：后端返回的 quotas 数组需要转换为具名字段
+const toAdditionData = (metric: AccountMetrics): AccountAdditionData => {
+  const findQuota = (name: string) => {
+    const item = metric.quotas.find(q => q.model_name.includes(name));
+    return {
+      percentage: item ? item.percentage : -1,
+      resetText: item ? item.reset_text : ""
+    };
+  };
 
-      const metric = await AccountMetricsCommands.getAccountMetrics(email);
+  const geminiPro = findQuota("Gemini Pro");
+  const geminiFlash = findQuota("Gemini Flash");
+  const geminiImage = findQuota("Gemini Image");
+  const claude = findQuota("Claude");
 
-      // 映射 Rust 数据结构 -> 前端 Store 结构
-      // 注意：后端返回的 quotas 数组需要转换为具名字段
-      const findQuota = (name: string) => {
-        const item = metric.quotas.find(q => q.model_name.includes(name));
-        return {
-          percentage: item ? item.percentage : -1,
-          resetText: item ? item.reset_text : ""
-        };
-      };
+  return {
+    geminiProQuote: geminiPro.percentage,
+    geminiProQuoteRestIn: geminiPro.resetText,
+    geminiFlashQuote: geminiFlash.percentage,
+    geminiFlashQuoteRestIn: geminiFlash.resetText,
+    geminiImageQuote: geminiImage.percentage,
+    geminiImageQuoteRestIn: geminiImage.resetText,
+    claudeQuote: claude.percentage,
+    claudeQuoteRestIn: claude.resetText,
+    userAvatar: metric.avatar_url,
+    userId: metric.user_id,
+  };
+};
 
-      const geminiPro = findQuota("Gemini Pro");
-      const geminiFlash = findQuota("Gemini Flash");
-      const geminiImage = findQuota("Gemini Image");
-      const claude = findQuota("Claude");
+export const useAccountAdditionData = create<State & Actions>((setState, getState) => ({
+  data: {},
+  refreshAll: async () => {
+    try {
+      logger.debug("开始批量获取账户指标 (Rust Bulk)");
 
-      logger.debug(`获取账户指标成功 (Rust Singular): ${email}`);
+      const entries = await AccountMetricsCommands.getAllAccountMetrics();
 
-      setState({
-        data: {
-          ...getState().data,
-          [email]: {
-            geminiProQuote: geminiPro.percentage,
-            geminiProQuoteRestIn: geminiPro.resetText,
-            geminiFlashQuote: geminiFlash.percentage,
-            geminiFlashQuoteRestIn: geminiFlash.resetText,
-            geminiImageQuote: geminiImage.percentage,
-            geminiImageQuoteRestIn: geminiImage.resetText,
-            claudeQuote: claude.percentage,
-            claudeQuoteRestIn: claude.resetText,
-            userAvatar: metric.avatar_url,
-            userId: metric.user_id,
-          }
+      const data = { ...getState().data };
+      for (const entry of entries) {
+        if (entry.metrics) {
+          data[entry.email] = toAdditionData(entry.metrics);
+        } else {
+          logger.error(`获取账户指标失败 (Rust): ${entry.email}`, entry.error);
         }
-      });
+      }
 
+      logger.debug(`批量获取账户指标完成 (Rust Bulk): ${entries.length} 个账户`);
+      setState({ data });
     } catch (error) {
-      logger.error(`获取账户指标失败 (Rust): ${email}`, error);
+      logger.error("批量获取账户指标失败 (Rust)", error);
     }
   }
 }))
@@ -4,7 +4,7 @@
  */
 
 import { create } from 'zustand';
-import { open, save } from '@tauri-apps/plugin-dialog';
+import { ask, open, save } from '@tauri-apps/plugin-dialog';
 import { readTextFile } from '@tauri-apps/plugin-fs';
 import { logger } from '@/lib/logger.ts';
 import toast from 'react-hot-toast';
@@ -19,6 +19,29 @@ interface EncryptedConfigData {
   backups: BackupData[];
 }
 
+/**
+ * 询问用户是否将旧格式的导出文件原地转换为当前加密格式
+ */
+async function offerReencrypt(path: string, encryptedFile: string, password: string): Promise<void> {
+  const confirmed = await ask(i18n.t('notifications:backup.legacyFormatPrompt'), { kind: 'warning' });
+  if (!confirmed) {
+    return;
+  }
+
+  try {
+    const { result } = await AccountManageCommands.reencryptConfig(encryptedFile, password);
+    await LoggingCommands.writeTextFile(path, result);
+    logger.info('旧格式导出文件已转换为当前格式', { module: 'useImportExportAccount', path });
+    toast.success(i18n.t('notifications:backup.reencryptSuccess'));
+  } catch (error) {
+    logger.error('重新加密失败', {
+      module: 'useImportExportAccount',
+      error: error instanceof Error ? error.message : String(error)
+    });
+    toast.error(i18n.t('notifications:backup.reencryptFailed', { error: error instanceof Error ? error.message : String(error) }));
+  }
+}
+
 // Store 状态
 interface ConfigState {
   isImporting: boolean;
@@ -110,8 +133,8 @@ export const useImportExportAccount = create<ConfigState & ConfigActions>()(
 
           // 读取文件并解密
           const encryptedFile = await readTextFile(pendingImportPath);
-          const decryptedJson: string = await AccountManageCommands.decryptConfig(encryptedFile, password);
-          const configData: EncryptedConfigData = JSON.parse(decryptedJson);
+          const decrypted = await AccountManageCommands.decryptConfig(encryptedFile, password);
+          const configData: EncryptedConfigData = JSON.parse(decrypted.result);
 
           // 验证配置数据格式
           if (!configData.version || !configData.backups || !Array.isArray(configData.backups)) {
@@ -141,6 +164,11 @@ export const useImportExportAccount = create<ConfigState & ConfigActions>()(
             });
             toast.success(i18n.t('notifications:backup.restoreSuccess', { count: result.restoredCount }));
           }
+
+          // 旧版 XOR 格式：提示用户转换为当前加密格式
+          if (decrypted.needsReencrypt) {
+            await offerReencrypt(pendingImportPath, encryptedFile, password);
+          }
         } catch (error) {
           logger.error('导入失败', {
             module: 'useImportExportAccount',
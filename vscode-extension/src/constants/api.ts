@@ -1,10 +1,15 @@
+import { getApiBaseUrl } from '../services/agent-discovery';
+
 /**
  * Shared constants for API configuration
  */
 export const API_CONFIG = {
-    BASE_URL: 'http://127.0.0.1:56789/api',
+    /** Resolved from the agent's discovery file on every access */
+    get BASE_URL(): string {
+        return getApiBaseUrl();
+    },
     ENDPOINTS: {
         GET_CURRENT_ACCOUNT: 'get_current_antigravity_account_info',
-        GET_METRICS: 'get_account_metrics',
+        GET_ALL_METRICS: 'get_all_account_metrics',
     }
 };
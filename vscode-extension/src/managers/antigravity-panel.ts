@@ -4,6 +4,7 @@ import { Logger } from '../utils/logger';
 import { AutoAcceptManager } from './auto-accept-manager';
 import { TranslationManager } from './translation-manager';
 import { StatusBarManager } from './status-bar-manager';
+import { getAgentOrigin, getAgentPort } from '../services/agent-discovery';
 
 // Declare global function injected by Vite build or shim
 // declare const __getWebviewHtml__: (options: any) => string;
@@ -205,13 +206,14 @@ export class AntigravityPanel {
             // Replace Placeholders using strict CSP logic
             html = html.replace(/{{cspSource}}/g, webview.cspSource);
             html = html.replace(/{{nonce}}/g, nonce);
+            html = html.replace(/{{agentHost}}/g, `127.0.0.1:${getAgentPort()}`);
 
             // Fix absolute asset paths to webview URIs
             const rootUri = webview.asWebviewUri(distPath);
             html = html.replace(/(href|src)="(\.?\/)?assets\//g, `$1="${rootUri}/assets/`);
 
             // Inject Language
-            const languageScript = `<script nonce="${nonce}">window.VSCODE_LANGUAGE = "${vscode.env.language}";</script>`;
+            const languageScript = `<script nonce="${nonce}">window.VSCODE_LANGUAGE = "${vscode.env.language}";window.ANTIGRAVITY_AGENT_URL = "${getAgentOrigin()}";</script>`;
             html = html.replace('</head>', `${languageScript}</head>`);
 
             return html;
@@ -1,10 +1,11 @@
 import * as vscode from 'vscode';
 import dayjs from 'dayjs';
 import { Logger } from '../utils/logger';
-import { AccountMetrics } from '@/commands/types/account.types';
+import { AccountMetrics, AccountMetricsEntry } from '@/commands/types/account.types';
 import { getQuotaCategory } from '../constants/model-mappings';
 import { TranslationManager } from './translation-manager';
 import { API_CONFIG } from '../constants/api';
+import { agentFetch } from '../services/agent-discovery';
 // Dynamic import or require is used inside render to avoid top-level issues if needed, 
 // but standard import is better if file exists. 
 // However, since we just added the file, let's use standard import.
@@ -31,7 +32,6 @@ export class StatusBarManager {
     private static interval: NodeJS.Timeout | undefined;
     private static metricsItem: vscode.StatusBarItem; // Display Model & Quota
     private static userItem: vscode.StatusBarItem;    // Display User Email
-    private static readonly API_BASE = API_CONFIG.BASE_URL;
 
     private static currentMetrics: AccountMetrics | null = null;
     private static currentAccount: CurrentAccount | null = null;
@@ -162,7 +162,7 @@ export class StatusBarManager {
         const t = TranslationManager.getInstance().t.bind(TranslationManager.getInstance());
         try {
             // 1. Get Current Account
-            const accRes = await fetch(`${this.API_BASE}/${API_CONFIG.ENDPOINTS.GET_CURRENT_ACCOUNT}`);
+            const accRes = await agentFetch(API_CONFIG.ENDPOINTS.GET_CURRENT_ACCOUNT);
 
             // Connection successful - reset warning visual
             this.metricsItem.color = undefined;
@@ -191,20 +191,18 @@ export class StatusBarManager {
 
             const email = currentAccount.context.email;
 
-            // 2. Get Metrics
-            const metricRes = await fetch(`${this.API_BASE}/${API_CONFIG.ENDPOINTS.GET_METRICS}`, {
-                method: 'POST',
-                headers: { 'Content-Type': 'application/json' },
-                body: JSON.stringify({ email })
-            });
+            // 2. Get Metrics (cached bulk endpoint, shared with the desktop UI)
+            const metricRes = await agentFetch(API_CONFIG.ENDPOINTS.GET_ALL_METRICS);
+            const entries = metricRes.ok ? await metricRes.json() as AccountMetricsEntry[] : [];
+            const entry = entries.find(e => e.email === email);
 
-            if (!metricRes.ok) {
+            if (!entry?.metrics) {
                 this.metricsItem.tooltip = `Current: ${email}\n${t('status.failedMetrics')}`;
                 this.userItem.text = `$(account) ${email}`;
                 return;
             }
 
-            this.currentMetrics = await metricRes.json() as AccountMetrics;
+            this.currentMetrics = entry.metrics;
             this.render(this.currentMetrics, currentAccount);
 
         } catch (error) {
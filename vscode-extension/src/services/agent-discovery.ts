@@ -0,0 +1,158 @@
+/**
+ * # Agent 服务发现
+ *
+ * Tauri 应用启动本地服务后会在 `~/.antigravity-agent/server.json` 写入实际监听的端口、
+ * PID 等信息（端口可能因配置或端口占用而不是默认的 56789）。
+ * 本模块读取该文件得到服务地址，文件不存在、格式错误或对应进程已退出时回退到默认端口。
+ *
+ * 每次调用都会重新读取，Agent 重启后换了端口也能在下次请求 / 重连时生效。
+ *
+ * Unix 下 Agent 还会监听权限为 0600 的 `agent.sock`，HTTP 请求优先走该套接字
+ * （见 {@link agentFetch}）。WebSocket 使用的全局 `WebSocket` 不支持 Unix 套接字，仍走 TCP 端口。
+ *
+ * Agent 开启 `server.requireToken` 后，TCP 端口上的请求需携带 `server.token` 中的实例 token，
+ * 走 TCP 时统一附带 {@link getAuthHeaders}。
+ *
+ * @module agent-discovery
+ */
+
+import * as fs from 'fs';
+import * as http from 'http';
+import * as os from 'os';
+import * as path from 'path';
+
+/** 默认端口，与 Rust 端 `server::discovery::DEFAULT_PORT` 一致 */
+const DEFAULT_PORT = 56789;
+
+/** 发现文件路径 */
+const DISCOVERY_FILE = path.join(os.homedir(), '.antigravity-agent', 'server.json');
+
+/** 实例 token 文件路径（权限 0600） */
+const TOKEN_FILE = path.join(os.homedir(), '.antigravity-agent', 'server.token');
+
+/** 发现文件内容 */
+export interface AgentDiscoveryInfo {
+    port: number;
+    pid: number;
+    url: string;
+    /** Unix 域套接字路径，未启用时不存在 */
+    socket?: string;
+    tokenFingerprint: string;
+    version: string;
+    startedAt: string;
+}
+
+/** 进程是否仍在运行 */
+function isProcessAlive(pid: number): boolean {
+    try {
+        process.kill(pid, 0);
+        return true;
+    } catch (e: any) {
+        // EPERM 表示进程存在但无权发送信号
+        return e?.code === 'EPERM';
+    }
+}
+
+/**
+ * 读取发现文件，无效或已过期时返回 `null`
+ */
+export function readAgentDiscovery(): AgentDiscoveryInfo | null {
+    try {
+        const info = JSON.parse(fs.readFileSync(DISCOVERY_FILE, 'utf-8')) as AgentDiscoveryInfo;
+        if (typeof info.port !== 'number' || !isProcessAlive(info.pid)) {
+            return null;
+        }
+        return info;
+    } catch {
+        return null;
+    }
+}
+
+/** Agent 服务端口 */
+export function getAgentPort(): number {
+    return readAgentDiscovery()?.port ?? DEFAULT_PORT;
+}
+
+/** Agent 服务根地址，如 `http://127.0.0.1:56789` */
+export function getAgentOrigin(): string {
+    return `http://127.0.0.1:${getAgentPort()}`;
+}
+
+/** HTTP API 地址，如 `http://127.0.0.1:56789/api` */
+export function getApiBaseUrl(): string {
+    return `${getAgentOrigin()}/api`;
+}
+
+/** WebSocket 地址，如 `ws://127.0.0.1:56789/ws` */
+export function getWebSocketUrl(): string {
+    return `ws://127.0.0.1:${getAgentPort()}/ws`;
+}
+
+/** 携带实例 token 的请求头，token 文件不存在时为空 */
+export function getAuthHeaders(): Record<string, string> {
+    try {
+        const token = fs.readFileSync(TOKEN_FILE, 'utf-8').trim();
+        return token ? { Authorization: `Bearer ${token}` } : {};
+    } catch {
+        return {};
+    }
+}
+
+/** {@link agentFetch} 的响应，只保留调用方用到的部分 */
+export interface AgentResponse {
+    ok: boolean;
+    status: number;
+    json(): Promise<any>;
+}
+
+/** {@link agentFetch} 的请求参数 */
+export interface AgentRequestInit {
+    method?: string;
+    headers?: Record<string, string>;
+    body?: string;
+}
+
+/** 通过 Unix 域套接字发送 HTTP 请求 */
+function requestOverSocket(socketPath: string, requestPath: string, init: AgentRequestInit): Promise<AgentResponse> {
+    return new Promise((resolve, reject) => {
+        const req = http.request(
+            { socketPath, path: requestPath, method: init.method ?? 'GET', headers: init.headers },
+            (res) => {
+                const chunks: Buffer[] = [];
+                res.on('data', (chunk: Buffer) => chunks.push(chunk));
+                res.on('end', () => {
+                    const status = res.statusCode ?? 0;
+                    const text = Buffer.concat(chunks).toString('utf-8');
+                    resolve({
+                        ok: status >= 200 && status < 300,
+                        status,
+                        json: async () => JSON.parse(text),
+                    });
+                });
+                res.on('error', reject);
+            }
+        );
+        req.on('error', reject);
+        if (init.body) {
+            req.write(init.body);
+        }
+        req.end();
+    });
+}
+
+/**
+ * 调用 Agent 的 HTTP API，`command` 为 `/api/` 之后的路径
+ *
+ * 发现文件中有可用的 Unix 套接字时走套接字，否则走 TCP 端口。
+ */
+export async function agentFetch(command: string, init: AgentRequestInit = {}): Promise<AgentResponse> {
+    const info = readAgentDiscovery();
+    if (info?.socket && fs.existsSync(info.socket)) {
+        return requestOverSocket(info.socket, `/api/${command}`, init);
+    }
+    const port = info?.port ?? DEFAULT_PORT;
+    return fetch(`http://127.0.0.1:${port}/api/${command}`, {
+        ...init,
+        headers: { ...getAuthHeaders(), ...init.headers },
+    });
+}
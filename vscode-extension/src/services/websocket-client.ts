@@ -48,19 +48,12 @@
 
 import * as vscode from 'vscode';
 import { Logger } from '../utils/logger';
+import { getAuthHeaders, getWebSocketUrl } from './agent-discovery';
 
 // =============================================================================
 // 常量配置
 // =============================================================================
 
-/**
- * WebSocket 服务器地址
- *
- * 与 Tauri 后端的 Actix 服务器地址一致。
- * 默认运行在 localhost:56789。
- */
-const WS_URL = 'ws://127.0.0.1:56789/ws';
-
 /**
  * 重连延迟（毫秒）
  *
@@ -193,7 +186,9 @@ export class WebSocketClient {
         Logger.log('🔌 正在连接 WebSocket...');
 
         try {
-            this.ws = new WebSocket(WS_URL);
+            // 每次连接都重新读取发现文件，Agent 重启后换了端口也能连上
+            // Node 内置的 WebSocket（undici）支持自定义请求头
+            this.ws = new WebSocket(getWebSocketUrl(), { headers: getAuthHeaders() } as any);
 
             this.ws.onopen = () => {
                 this.isConnecting = false;